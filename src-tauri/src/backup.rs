@@ -0,0 +1,231 @@
+use crate::db::{apply_schema, get_setting_i64, record_background_result, DbState};
+use crate::models::{BackupInfo, BackupVerification};
+use rusqlite::backup::{Backup, Progress};
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+/// Number of copies kept per backup reason unless `backup_retention` is set
+pub const DEFAULT_RETENTION: i64 = 10;
+/// Hours between scheduled backups unless `backup_interval_hours` is set
+pub const DEFAULT_INTERVAL_HOURS: i64 = 24;
+
+/// Setting holding the last failed startup or scheduled backup
+pub const LAST_ERROR_KEY: &str = "last_backup_error";
+
+const FILE_PREFIX: &str = "books-";
+const FILE_SUFFIX: &str = ".db";
+/// Length of the `YYYYMMDD-HHMMSS.mmm` stamp embedded in backup file names
+const STAMP_LEN: usize = 19;
+/// Stamps written before milliseconds were added (`YYYYMMDD-HHMMSS`)
+const LEGACY_STAMP_LEN: usize = 15;
+
+pub fn backup_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("backups")
+}
+
+/// Snapshot the live database into `backups/books-<stamp>-<reason>.db` using
+/// SQLite's online backup API, then drop the oldest copies for that reason.
+pub fn create_backup(conn: &Connection, data_dir: &Path, reason: &str) -> Result<BackupInfo, String> {
+    let dir = backup_dir(data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create backup dir: {}", e))?;
+
+    // Millisecond stamps keep back-to-back backups (startup then pre-import)
+    // from overwriting each other; wait out the rare same-millisecond clash.
+    let (file_name, path) = loop {
+        let stamp: String = conn
+            .query_row("SELECT strftime('%Y%m%d-%H%M%f', 'now')", [], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        let file_name = format!("{}{}-{}{}", FILE_PREFIX, stamp, reason, FILE_SUFFIX);
+        let path = dir.join(&file_name);
+        if !path.exists() {
+            break (file_name, path);
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    };
+
+    {
+        let mut dst = Connection::open(&path).map_err(|e| format!("Failed to open backup file: {}", e))?;
+        let backup = Backup::new(conn, &mut dst).map_err(|e| format!("Backup failed: {}", e))?;
+        backup
+            .run_to_completion(256, Duration::ZERO, None)
+            .map_err(|e| format!("Backup failed: {}", e))?;
+    }
+
+    let retention = get_setting_i64(conn, "backup_retention", DEFAULT_RETENTION).max(1) as usize;
+    rotate(data_dir, reason, retention)?;
+
+    parse_backup(&path).ok_or_else(|| format!("Backup file missing after write: {}", file_name))
+}
+
+/// All backups in the backup dir, newest first.
+pub fn list_backups(data_dir: &Path) -> Result<Vec<BackupInfo>, String> {
+    let dir = backup_dir(data_dir);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_backup(&entry.path()))
+        .collect();
+    // The timestamp prefix makes file names sort chronologically
+    backups.sort_by(|a, b| b.file_name.cmp(&a.file_name));
+    Ok(backups)
+}
+
+/// Run `PRAGMA integrity_check` against a backup without modifying it.
+pub fn verify_backup(data_dir: &Path, file_name: &str) -> Result<BackupVerification, String> {
+    let path = resolve_backup_path(data_dir, file_name)?;
+    let conn = Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("Failed to open backup: {}", e))?;
+
+    let messages: Vec<String> = match conn.prepare("PRAGMA integrity_check") {
+        Ok(mut stmt) => stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect(),
+        Err(e) => vec![e.to_string()],
+    };
+    let ok = messages.len() == 1 && messages[0] == "ok";
+
+    let book_count: Option<i64> = conn
+        .query_row("SELECT count(*) FROM books", [], |r| r.get(0))
+        .ok();
+
+    Ok(BackupVerification {
+        file_name: file_name.to_string(),
+        ok,
+        messages,
+        book_count,
+    })
+}
+
+/// Replace the live database with a verified backup. The current state is
+/// snapshotted as `pre-restore` first so a restore can itself be undone.
+pub fn restore_backup(conn: &mut Connection, data_dir: &Path, file_name: &str) -> Result<BackupInfo, String> {
+    let verification = verify_backup(data_dir, file_name)?;
+    if !verification.ok {
+        return Err(format!(
+            "Backup failed integrity check: {}",
+            verification.messages.join("; ")
+        ));
+    }
+    let path = resolve_backup_path(data_dir, file_name)?;
+
+    let safety = create_backup(conn, data_dir, "pre-restore")?;
+    conn.restore(DatabaseName::Main, &path, None::<fn(Progress)>)
+        .map_err(|e| format!("Restore failed: {}", e))?;
+
+    // Older backups may predate the latest migrations
+    apply_schema(conn)?;
+
+    Ok(safety)
+}
+
+/// Background thread that takes a `scheduled` backup whenever the newest one
+/// is older than `backup_interval_hours`. Checking the file age (rather than
+/// sleeping a full interval) keeps the schedule across app restarts.
+pub fn spawn_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let state = app.state::<DbState>();
+        let due = {
+            let interval_hours = match state.conn.lock() {
+                Ok(conn) => get_setting_i64(&conn, "backup_interval_hours", DEFAULT_INTERVAL_HOURS),
+                Err(_) => DEFAULT_INTERVAL_HOURS,
+            };
+            // Zero or negative disables scheduled backups
+            interval_hours > 0 && newest_age(&state.data_dir, "scheduled")
+                .is_none_or(|age| age >= Duration::from_secs(interval_hours as u64 * 3600))
+        };
+
+        if due {
+            if let Ok(conn) = state.conn.lock() {
+                let result = create_backup(&conn, &state.data_dir, "scheduled");
+                record_background_result(&conn, LAST_ERROR_KEY, &result);
+            }
+        }
+
+        std::thread::sleep(Duration::from_secs(15 * 60));
+    });
+}
+
+/// Age of the most recent backup taken for `reason`.
+fn newest_age(data_dir: &Path, reason: &str) -> Option<Duration> {
+    let newest = list_backups(data_dir)
+        .ok()?
+        .into_iter()
+        .find(|b| b.reason == reason)?;
+    let modified = std::fs::metadata(backup_dir(data_dir).join(newest.file_name))
+        .and_then(|m| m.modified())
+        .ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+/// Keep only the newest `keep` backups that were taken for `reason`.
+fn rotate(data_dir: &Path, reason: &str, keep: usize) -> Result<(), String> {
+    let dir = backup_dir(data_dir);
+    let stale = list_backups(data_dir)?
+        .into_iter()
+        .filter(|b| b.reason == reason)
+        .skip(keep);
+    for backup in stale {
+        std::fs::remove_file(dir.join(&backup.file_name))
+            .map_err(|e| format!("Failed to remove old backup {}: {}", backup.file_name, e))?;
+    }
+    Ok(())
+}
+
+/// Map a user-supplied file name to a path inside the backup dir, rejecting
+/// anything that could escape it.
+fn resolve_backup_path(data_dir: &Path, file_name: &str) -> Result<PathBuf, String> {
+    let valid = file_name.starts_with(FILE_PREFIX)
+        && file_name.ends_with(FILE_SUFFIX)
+        && !file_name.contains(['/', '\\'])
+        && !file_name.contains("..");
+    if !valid {
+        return Err(format!("Invalid backup name: {}", file_name));
+    }
+    let path = backup_dir(data_dir).join(file_name);
+    if !path.is_file() {
+        return Err(format!("Backup not found: {}", file_name));
+    }
+    Ok(path)
+}
+
+/// Parse `books-YYYYMMDD-HHMMSS.mmm-<reason>.db` (or the older form
+/// without milliseconds) into a `BackupInfo`.
+fn parse_backup(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let body = file_name.strip_prefix(FILE_PREFIX)?.strip_suffix(FILE_SUFFIX)?;
+    let stamp_len = if body.as_bytes().get(LEGACY_STAMP_LEN) == Some(&b'.') { STAMP_LEN } else { LEGACY_STAMP_LEN };
+    if body.len() <= stamp_len + 1 || !body.is_char_boundary(stamp_len) {
+        return None;
+    }
+    let (stamp, rest) = body.split_at(stamp_len);
+    let reason = rest.strip_prefix('-')?.to_string();
+    let valid_stamp = stamp.bytes().enumerate().all(|(i, b)| match i {
+        8 => b == b'-',
+        15 => b == b'.',
+        _ => b.is_ascii_digit(),
+    });
+    if !valid_stamp {
+        return None;
+    }
+
+    let created_at = format!(
+        "{}-{}-{} {}:{}:{}",
+        &stamp[0..4], &stamp[4..6], &stamp[6..8], &stamp[9..11], &stamp[11..13], &stamp[13..15]
+    );
+    let size_bytes = std::fs::metadata(path).ok()?.len();
+
+    Some(BackupInfo {
+        file_name,
+        reason,
+        created_at,
+        size_bytes,
+    })
+}
@@ -0,0 +1,88 @@
+use crate::models::BookType;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Type given to books that don't say otherwise; can't be deleted
+pub const DEFAULT_TYPE: &str = "traditional";
+
+/// Every configured type with how many books (including trashed) use it,
+/// in the order they were added.
+pub fn list(conn: &Connection) -> Result<Vec<BookType>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.name, t.label, t.beq_baseline, t.default_binding, t.aliases, t.cover_style,
+                (SELECT count(*) FROM books b WHERE b.book_type = t.name)
+             FROM book_types t ORDER BY t.rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let types = stmt
+        .query_map([], |r| {
+            Ok(BookType {
+                name: r.get(0)?,
+                label: r.get(1)?,
+                beq_baseline: r.get(2)?,
+                default_binding: r.get(3)?,
+                aliases: r
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                cover_style: r.get(5)?,
+                book_count: r.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(types)
+}
+
+/// Check that `name` is a configured type, returning it trimmed.
+pub fn validate(conn: &Connection, name: &str) -> Result<String, String> {
+    let name = name.trim();
+    let known = conn
+        .query_row("SELECT 1 FROM book_types WHERE name = ?1", params![name], |_| Ok(()))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .is_some();
+    if known {
+        Ok(name.to_string())
+    } else {
+        Err(format!("Unknown book type: {}", name))
+    }
+}
+
+/// Binding to fill in for a new book of this type that has none.
+pub fn default_binding(conn: &Connection, name: &str) -> Option<String> {
+    conn.query_row(
+        "SELECT default_binding FROM book_types WHERE name = ?1",
+        params![name],
+        |r| r.get(0),
+    )
+    .optional()
+    .ok()
+    .flatten()
+    .flatten()
+}
+
+/// Map a value from an imported "Format"/"Type" (or binding) column to a
+/// configured type by name, label or alias, ignoring case. `None` when
+/// nothing matches.
+pub fn match_value(types: &[BookType], raw: &str) -> Option<String> {
+    let value = raw.trim().to_lowercase();
+    if value.is_empty() {
+        return None;
+    }
+    types
+        .iter()
+        .find(|t| {
+            t.name.to_lowercase() == value
+                || t.label.to_lowercase() == value
+                || t.aliases.iter().any(|a| a.trim().to_lowercase() == value)
+        })
+        .map(|t| t.name.clone())
+}
+
+/// Type names are stored on books and used in settings, so keep them to
+/// lower-case letters, digits and underscores.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
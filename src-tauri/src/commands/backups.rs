@@ -0,0 +1,28 @@
+use crate::backup;
+use crate::db::DbState;
+use crate::models::*;
+use tauri::State;
+
+#[tauri::command]
+pub fn list_backups(state: State<DbState>) -> Result<Vec<BackupInfo>, String> {
+    backup::list_backups(&state.data_dir)
+}
+
+#[tauri::command]
+pub fn create_backup(state: State<DbState>) -> Result<BackupInfo, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    backup::create_backup(&conn, &state.data_dir, "manual")
+}
+
+#[tauri::command]
+pub fn verify_backup(state: State<DbState>, file_name: String) -> Result<BackupVerification, String> {
+    backup::verify_backup(&state.data_dir, &file_name)
+}
+
+/// Restore a backup over the live database. Returns the `pre-restore`
+/// snapshot taken of the state being replaced.
+#[tauri::command]
+pub fn restore_backup(state: State<DbState>, file_name: String) -> Result<BackupInfo, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    backup::restore_backup(&mut conn, &state.data_dir, &file_name)
+}
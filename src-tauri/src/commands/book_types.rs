@@ -0,0 +1,158 @@
+use crate::beq;
+use crate::book_types::{self, DEFAULT_TYPE};
+use crate::db::DbState;
+use crate::history;
+use crate::models::BookType;
+use crate::placeholder;
+use rusqlite::{params, OptionalExtension};
+use tauri::State;
+
+#[tauri::command]
+pub fn get_book_types(state: State<DbState>) -> Result<Vec<BookType>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    book_types::list(&conn)
+}
+
+/// Add a type, or update the label, baseline, default binding, aliases and
+/// cover style of an existing one. Changing the cover style redraws the
+/// type's generated placeholder covers. `book_count` is ignored.
+#[tauri::command]
+pub fn save_book_type(state: State<DbState>, book_type: BookType) -> Result<Vec<BookType>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let name = book_type.name.trim();
+    if !book_types::is_valid_name(name) {
+        return Err(format!(
+            "Invalid book type name '{}': use lower-case letters, digits and underscores",
+            name
+        ));
+    }
+    let label = book_type.label.trim();
+    if label.is_empty() {
+        return Err("Book type label can't be empty".to_string());
+    }
+    if let Some(pages) = book_type.beq_baseline {
+        if !(pages.is_finite() && pages > 0.0) {
+            return Err(format!("Baseline for {} must be a positive number of pages", name));
+        }
+    }
+    let default_binding = book_type
+        .default_binding
+        .as_deref()
+        .map(str::trim)
+        .filter(|b| !b.is_empty());
+    let aliases: Vec<&str> = book_type
+        .aliases
+        .iter()
+        .map(|a| a.trim())
+        .filter(|a| !a.is_empty())
+        .collect();
+    let aliases = serde_json::to_string(&aliases).map_err(|e| e.to_string())?;
+    let cover_style = book_type
+        .cover_style
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty());
+    if let Some(style) = cover_style {
+        if !placeholder::STYLES.contains(&style) {
+            return Err(format!("Unknown cover style: {}", style));
+        }
+    }
+    let previous_style: Option<Option<String>> = conn
+        .query_row("SELECT cover_style FROM book_types WHERE name = ?1", params![name], |r| r.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO book_types (name, label, beq_baseline, default_binding, aliases, cover_style)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(name) DO UPDATE SET label = excluded.label, beq_baseline = excluded.beq_baseline,
+             default_binding = excluded.default_binding, aliases = excluded.aliases,
+             cover_style = excluded.cover_style",
+        params![name, label, book_type.beq_baseline, default_binding, aliases, cover_style],
+    )
+    .map_err(|e| e.to_string())?;
+    if previous_style.is_some_and(|previous| previous.as_deref() != cover_style) {
+        let ids: Vec<i64> = {
+            let mut stmt = tx
+                .prepare("SELECT id FROM books WHERE book_type = ?1 AND cover_cached_from = ?2")
+                .map_err(|e| e.to_string())?;
+            let ids = stmt
+                .query_map(params![name, placeholder::GENERATED_SOURCE], |r| r.get(0))
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .collect();
+            ids
+        };
+        for id in ids {
+            let before = history::snapshot(&tx, id)?;
+            if placeholder::refresh(&tx, &state.data_dir, id)? {
+                history::record(&tx, id, "update", before.as_ref(), Some("Placeholder cover"))?;
+            }
+        }
+    }
+    beq::invalidate(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    book_types::list(&conn)
+}
+
+/// Remove a type. Books (including trashed ones) still using it block the
+/// delete unless `reassign_to` names the type to move them to. Moving the
+/// books and removing the type happen in one transaction.
+#[tauri::command]
+pub fn delete_book_type(
+    state: State<DbState>,
+    name: String,
+    reassign_to: Option<String>,
+) -> Result<Vec<BookType>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let name = book_types::validate(&conn, &name)?;
+    if name == DEFAULT_TYPE {
+        return Err(format!("The {} type can't be deleted", DEFAULT_TYPE));
+    }
+
+    let book_ids: Vec<i64> = {
+        let mut stmt = conn
+            .prepare("SELECT id FROM books WHERE book_type = ?1")
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![name], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        ids
+    };
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if !book_ids.is_empty() {
+        let target = match reassign_to {
+            Some(ref target) => book_types::validate(&tx, target)?,
+            None => {
+                return Err(format!(
+                    "{} books use the {} type; choose a type to move them to",
+                    book_ids.len(),
+                    name
+                ))
+            }
+        };
+        if target == name {
+            return Err("Can't move books to the type being deleted".to_string());
+        }
+        let note = format!("Book type {} deleted", name);
+        for id in book_ids {
+            let before = history::snapshot(&tx, id)?;
+            tx.execute(
+                "UPDATE books SET book_type = ?1, updated_at = datetime('now') WHERE id = ?2",
+                params![target, id],
+            )
+            .map_err(|e| e.to_string())?;
+            history::record(&tx, id, "update", before.as_ref(), Some(&note))?;
+        }
+    }
+
+    tx.execute("DELETE FROM book_types WHERE name = ?1", params![name])
+        .map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM book_type_stats WHERE book_type = ?1", params![name])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    book_types::list(&conn)
+}
@@ -1,24 +1,32 @@
+use crate::backup;
+use crate::beq;
+use crate::book_types;
+use crate::cover_cache;
 use crate::db::DbState;
+use crate::history;
+use crate::isbn;
 use crate::models::*;
+use crate::palette;
+use crate::placeholder;
 use rusqlite::params;
 use tauri::State;
 
-/// SQL expression that computes BEq on-the-fly using per-book-type average pages
-const BEQ_EXPR: &str = "CASE WHEN books.pages > 0 THEN ROUND(books.pages * 1.0 / NULLIF((SELECT AVG(b2.pages) FROM books b2 WHERE b2.book_type = books.book_type AND b2.exclusive_shelf = 'read' AND b2.pages > 0), 0), 2) ELSE NULL END";
-
-/// All columns we SELECT (with computed beq instead of stored beq)
-fn select_columns() -> String {
-    format!(
+/// All columns we SELECT. `beq` is stored; call `beq::sync` first so it
+/// reflects the current averages.
+pub(crate) fn select_columns() -> String {
+    String::from(
         "books.id, books.goodreads_id, books.title, books.author, books.author_sort, books.additional_authors, \
          books.isbn, books.isbn13, books.my_rating, books.average_rating, books.publisher, books.binding, \
-         books.pages, {BEQ_EXPR} AS beq, books.edition_published, books.year_published, \
+         books.pages, ROUND(books.beq, 2) AS beq, books.edition_published, books.year_published, \
          books.date_read, books.year_read, books.date_added, books.exclusive_shelf, books.my_review, \
          books.read_count, books.owned_copies, books.cover_url, books.open_library_key, \
-         books.created_at, books.updated_at, books.book_type"
+         books.created_at, books.updated_at, books.book_type, books.deleted_at, \
+         books.subjects, books.description, books.language, books.series, \
+         books.cover_path, books.cover_hash, books.cover_color, books.cover_accent, books.date_started, books.revision",
     )
 }
 
-fn row_to_book(row: &rusqlite::Row) -> rusqlite::Result<Book> {
+pub(crate) fn row_to_book(row: &rusqlite::Row) -> rusqlite::Result<Book> {
     Ok(Book {
         id: row.get(0)?,
         goodreads_id: row.get(1)?,
@@ -47,16 +55,34 @@ fn row_to_book(row: &rusqlite::Row) -> rusqlite::Result<Book> {
         open_library_key: row.get(24)?,
         created_at: row.get(25)?,
         updated_at: row.get(26)?,
-        book_type: row.get::<_, Option<String>>(27)?.unwrap_or_else(|| "traditional".to_string()),
+        book_type: row.get::<_, Option<String>>(27)?.unwrap_or_else(|| book_types::DEFAULT_TYPE.to_string()),
+        deleted_at: row.get(28)?,
+        subjects: row
+            .get::<_, Option<String>>(29)?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        description: row.get(30)?,
+        language: row.get(31)?,
+        series: row.get(32)?,
+        cover_path: row.get(33)?,
+        cover_hash: row.get(34)?,
+        cover_color: row.get(35)?,
+        cover_accent: row.get(36)?,
+        date_started: row.get(37)?,
+        revision: row.get(38)?,
     })
 }
 
+/// `cover_color` filters by color family (see `palette::FAMILIES`, plus
+/// `neutral`); sorting by `coverColor` orders covers by hue, greys last.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub fn get_books(
     state: State<DbState>,
     search: Option<String>,
     shelf: Option<String>,
     exclusive_shelf: Option<String>,
+    cover_color: Option<String>,
     sort_by: Option<String>,
     sort_dir: Option<String>,
     page: Option<i64>,
@@ -66,21 +92,23 @@ pub fn get_books(
     let page = page.unwrap_or(1);
     let limit = limit.unwrap_or(50);
     let offset = (page - 1) * limit;
+    beq::sync(&conn)?;
 
     let sort_col = match sort_by.as_deref() {
         Some("title") => "books.title",
         Some("author") => "books.author_sort",
         Some("myRating") => "books.my_rating",
         Some("pages") => "books.pages",
-        Some("beq") => "beq",
+        Some("beq") => "books.beq",
         Some("dateRead") => "books.date_read",
         Some("yearPublished") => "books.year_published",
         Some("averageRating") => "books.average_rating",
+        Some("coverColor") => "books.cover_hue IS NULL, books.cover_hue",
         _ => "books.date_added",
     };
     let dir = if sort_dir.as_deref() == Some("asc") { "ASC" } else { "DESC" };
 
-    let mut conditions: Vec<String> = vec![];
+    let mut conditions: Vec<String> = vec!["books.deleted_at IS NULL".to_string()];
     let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = vec![];
 
     if let Some(ref s) = search {
@@ -110,11 +138,15 @@ pub fn get_books(
         }
     }
 
-    let where_clause = if conditions.is_empty() {
-        String::new()
-    } else {
-        format!("WHERE {}", conditions.join(" AND "))
-    };
+    if let Some(ref family) = cover_color {
+        if !family.is_empty() && family != "all" {
+            let condition = palette::family_condition(family)
+                .ok_or_else(|| format!("Unknown cover color: {}", family))?;
+            conditions.push(condition);
+        }
+    }
+
+    let where_clause = format!("WHERE {}", conditions.join(" AND "));
 
     // Count
     let count_sql = format!("SELECT count(*) FROM books {}", where_clause);
@@ -122,7 +154,7 @@ pub fn get_books(
         .query_row(&count_sql, rusqlite::params_from_iter(param_values.iter().map(|p| p.as_ref())), |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
-    // Query with computed BEq
+    // Query with stored BEq
     let query_sql = format!(
         "SELECT {} FROM books {} ORDER BY {} {} LIMIT {} OFFSET {}",
         select_columns(), where_clause, sort_col, dir, limit, offset
@@ -137,9 +169,23 @@ pub fn get_books(
     Ok(BooksResult { books, total })
 }
 
+/// Fail unless book `id` exists and isn't in the trash. Trashed books are
+/// only reachable through the trash commands until they are restored.
+pub(crate) fn ensure_live(conn: &rusqlite::Connection, id: i64) -> Result<(), String> {
+    let deleted_at: Option<String> = conn
+        .query_row("SELECT deleted_at FROM books WHERE id = ?1", params![id], |r| r.get(0))
+        .map_err(|_| format!("Book not found: {}", id))?;
+    match deleted_at {
+        Some(_) => Err(format!("Book {} is in the trash; restore it first", id)),
+        None => Ok(()),
+    }
+}
+
 #[tauri::command]
 pub fn get_book(state: State<DbState>, id: i64) -> Result<BookWithShelves, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    ensure_live(&conn, id)?;
+    beq::sync(&conn)?;
 
     let sql = format!("SELECT {} FROM books WHERE books.id = ?1", select_columns());
     let book = conn
@@ -163,7 +209,7 @@ pub fn get_book(state: State<DbState>, id: i64) -> Result<BookWithShelves, Strin
     Ok(BookWithShelves { book, shelves })
 }
 
-fn link_shelves(conn: &rusqlite::Connection, book_id: i64, shelf_names: &[String]) -> Result<(), String> {
+pub(crate) fn link_shelves(conn: &rusqlite::Connection, book_id: i64, shelf_names: &[String]) -> Result<(), String> {
     for name in shelf_names {
         let trimmed = name.trim();
         if trimmed.is_empty() { continue; }
@@ -184,6 +230,14 @@ fn link_shelves(conn: &rusqlite::Connection, book_id: i64, shelf_names: &[String
     Ok(())
 }
 
+/// Subjects are stored as a JSON array so names may contain commas.
+fn subjects_json(subjects: &Option<Vec<String>>) -> Option<String> {
+    subjects
+        .as_ref()
+        .filter(|s| !s.is_empty())
+        .and_then(|s| serde_json::to_string(s).ok())
+}
+
 fn get_cover_url(isbn: &Option<String>, isbn13: &Option<String>) -> Option<String> {
     let id = isbn13.as_deref().or(isbn.as_deref())?;
     if id.is_empty() { return None; }
@@ -193,19 +247,27 @@ fn get_cover_url(isbn: &Option<String>, isbn13: &Option<String>) -> Option<Strin
 #[tauri::command]
 pub fn create_book(state: State<DbState>, data: NewBook) -> Result<Book, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let cover_url = data.cover_url.clone().or_else(|| get_cover_url(&data.isbn, &data.isbn13));
+    let isbns = isbn::resolve(data.isbn.as_deref(), data.isbn13.as_deref())?;
+    let cover_url = data.cover_url.clone().or_else(|| get_cover_url(&isbns.isbn, &isbns.isbn13));
     let date_added = data.date_added.clone().unwrap_or_else(chrono_today);
-    let book_type = data.book_type.as_deref().unwrap_or("traditional");
+    let book_type = book_types::validate(&conn, data.book_type.as_deref().unwrap_or(book_types::DEFAULT_TYPE))?;
+    let binding = data
+        .binding
+        .clone()
+        .filter(|b| !b.trim().is_empty())
+        .or_else(|| book_types::default_binding(&conn, &book_type));
+    let subjects = subjects_json(&data.subjects);
 
     conn.execute(
-        "INSERT INTO books (goodreads_id, title, author, author_sort, additional_authors, isbn, isbn13, my_rating, average_rating, publisher, binding, pages, edition_published, year_published, date_read, year_read, date_added, exclusive_shelf, my_review, read_count, owned_copies, cover_url, book_type)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)",
+        "INSERT INTO books (goodreads_id, title, author, author_sort, additional_authors, isbn, isbn13, my_rating, average_rating, publisher, binding, pages, edition_published, year_published, date_read, year_read, date_added, exclusive_shelf, my_review, read_count, owned_copies, cover_url, book_type, open_library_key, subjects, description, language, series, date_started)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27, ?28, ?29)",
         params![
             data.goodreads_id, data.title, data.author, data.author_sort, data.additional_authors,
-            data.isbn, data.isbn13, data.my_rating.unwrap_or(0), data.average_rating,
-            data.publisher, data.binding, data.pages, data.edition_published, data.year_published,
+            isbns.isbn, isbns.isbn13, data.my_rating.unwrap_or(0), data.average_rating,
+            data.publisher, binding, data.pages, data.edition_published, data.year_published,
             data.date_read, data.year_read, date_added, data.exclusive_shelf.as_deref().unwrap_or("to-read"),
             data.my_review, data.read_count.unwrap_or(0), data.owned_copies.unwrap_or(0), cover_url, book_type,
+            data.open_library_key, subjects, data.description, data.language, data.series, data.date_started,
         ],
     ).map_err(|e| e.to_string())?;
 
@@ -214,6 +276,9 @@ pub fn create_book(state: State<DbState>, data: NewBook) -> Result<Book, String>
     if let Some(ref names) = data.shelf_names {
         link_shelves(&conn, book_id, names)?;
     }
+    placeholder::refresh(&conn, &state.data_dir, book_id)?;
+    history::record(&conn, book_id, "create", None, None)?;
+    beq::sync(&conn)?;
 
     let sql = format!("SELECT {} FROM books WHERE books.id = ?1", select_columns());
     conn.query_row(&sql, params![book_id], row_to_book)
@@ -223,21 +288,37 @@ pub fn create_book(state: State<DbState>, data: NewBook) -> Result<Book, String>
 #[tauri::command]
 pub fn update_book(state: State<DbState>, id: i64, data: NewBook) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    let cover_url = data.cover_url.clone().or_else(|| get_cover_url(&data.isbn, &data.isbn13));
-    let book_type = data.book_type.as_deref().unwrap_or("traditional");
+    ensure_live(&conn, id)?;
+    let isbns = isbn::resolve(data.isbn.as_deref(), data.isbn13.as_deref())?;
+    let before = history::snapshot(&conn, id)?;
+    // An uploaded cover has no URL; don't replace it with the ISBN fallback
+    let has_upload = before
+        .as_ref()
+        .and_then(|b| b.get("cover_cached_from"))
+        .is_some_and(|v| v == cover_cache::UPLOAD_SOURCE);
+    let cover_url = data
+        .cover_url
+        .clone()
+        .or_else(|| (!has_upload).then(|| get_cover_url(&isbns.isbn, &isbns.isbn13)).flatten());
+    let book_type = book_types::validate(&conn, data.book_type.as_deref().unwrap_or(book_types::DEFAULT_TYPE))?;
 
     conn.execute(
         "UPDATE books SET title=?1, author=?2, author_sort=?3, additional_authors=?4, isbn=?5, isbn13=?6,
          my_rating=?7, average_rating=?8, publisher=?9, binding=?10, pages=?11,
          edition_published=?12, year_published=?13, date_read=?14, year_read=?15,
          exclusive_shelf=?16, my_review=?17, read_count=?18, owned_copies=?19, cover_url=?20,
-         book_type=?21, updated_at=datetime('now') WHERE id=?22",
+         book_type=?21, open_library_key=coalesce(?23, open_library_key), subjects=coalesce(?24, subjects),
+         description=coalesce(?25, description), language=coalesce(?26, language), series=coalesce(?27, series),
+         date_started=?28, updated_at=datetime('now') WHERE id=?22",
         params![
             data.title, data.author, data.author_sort, data.additional_authors,
-            data.isbn, data.isbn13, data.my_rating.unwrap_or(0), data.average_rating,
+            isbns.isbn, isbns.isbn13, data.my_rating.unwrap_or(0), data.average_rating,
             data.publisher, data.binding, data.pages, data.edition_published, data.year_published,
             data.date_read, data.year_read, data.exclusive_shelf, data.my_review,
             data.read_count.unwrap_or(0), data.owned_copies.unwrap_or(0), cover_url, book_type, id,
+            // Lookup-only fields the edit form doesn't carry are kept unless given
+            data.open_library_key, subjects_json(&data.subjects), data.description, data.language, data.series,
+            data.date_started,
         ],
     ).map_err(|e| e.to_string())?;
 
@@ -246,22 +327,259 @@ pub fn update_book(state: State<DbState>, id: i64, data: NewBook) -> Result<(),
             .map_err(|e| e.to_string())?;
         link_shelves(&conn, id, names)?;
     }
+    placeholder::refresh(&conn, &state.data_dir, id)?;
+    history::record(&conn, id, "update", before.as_ref(), None)?;
 
     Ok(())
 }
 
+/// Apply a partial update: only fields present in `patch` are written, and
+/// shelves are added/removed individually instead of being relinked.
+#[tauri::command]
+pub fn patch_book(state: State<DbState>, id: i64, patch: BookPatch) -> Result<Book, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    ensure_live(&tx, id)?;
+
+    if let Some(expected) = patch.expected_revision {
+        let (revision, updated_at): (i64, Option<String>) = tx
+            .query_row("SELECT revision, updated_at FROM books WHERE id = ?1", params![id], |r| {
+                Ok((r.get(0)?, r.get(1)?))
+            })
+            .map_err(|e| format!("Book not found: {}", e))?;
+        if revision != expected {
+            return Err(format!(
+                "Conflict: book {} was modified at {} after it was loaded",
+                id,
+                updated_at.unwrap_or_default()
+            ));
+        }
+    }
+
+    let before = history::snapshot(&tx, id)?;
+    apply_patch(&tx, id, &patch)?;
+    placeholder::refresh(&tx, &state.data_dir, id)?;
+    history::record(&tx, id, "update", before.as_ref(), None)?;
+    beq::sync(&tx)?;
+
+    let sql = format!("SELECT {} FROM books WHERE books.id = ?1", select_columns());
+    let book = tx
+        .query_row(&sql, params![id], row_to_book)
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(book)
+}
+
+/// Write the fields set in `patch` and apply its shelf deltas.
+pub(crate) fn apply_patch(conn: &rusqlite::Connection, id: i64, patch: &BookPatch) -> Result<(), String> {
+    let mut sets: Vec<(&str, Box<dyn rusqlite::types::ToSql>)> = vec![];
+    let isbn10 = patch_isbn(&patch.isbn, 10)?;
+    let isbn13 = patch_isbn(&patch.isbn13, 13)?;
+    if let Some(rating) = patch.my_rating.filter(|r| !(0..=5).contains(r)) {
+        return Err(format!("Rating must be between 0 and 5, got {}", rating));
+    }
+
+    for (name, value) in [("title", &patch.title), ("author", &patch.author)] {
+        if let Some(v) = value {
+            if v.trim().is_empty() {
+                return Err(format!("{} cannot be empty", name));
+            }
+            sets.push((name, Box::new(v.clone())));
+        }
+    }
+    for (name, value) in [
+        ("author_sort", &patch.author_sort),
+        ("additional_authors", &patch.additional_authors),
+        ("isbn", &isbn10),
+        ("isbn13", &isbn13),
+        ("publisher", &patch.publisher),
+        ("binding", &patch.binding),
+        ("date_read", &patch.date_read),
+        ("date_started", &patch.date_started),
+        ("exclusive_shelf", &patch.exclusive_shelf),
+        ("my_review", &patch.my_review),
+        ("cover_url", &patch.cover_url),
+        ("open_library_key", &patch.open_library_key),
+        ("description", &patch.description),
+        ("language", &patch.language),
+        ("series", &patch.series),
+    ] {
+        if let Some(v) = value {
+            sets.push((name, Box::new(v.clone())));
+        }
+    }
+    for (name, value) in [
+        ("goodreads_id", &patch.goodreads_id),
+        ("pages", &patch.pages),
+        ("edition_published", &patch.edition_published),
+        ("year_published", &patch.year_published),
+        ("year_read", &patch.year_read),
+    ] {
+        if let Some(v) = value {
+            sets.push((name, Box::new(*v)));
+        }
+    }
+    for (name, value) in [
+        ("my_rating", &patch.my_rating),
+        ("read_count", &patch.read_count),
+        ("owned_copies", &patch.owned_copies),
+    ] {
+        if let Some(v) = value {
+            sets.push((name, Box::new(*v)));
+        }
+    }
+    if let Some(v) = patch.average_rating {
+        sets.push(("average_rating", Box::new(v)));
+    }
+    if let Some(ref v) = patch.date_added {
+        sets.push(("date_added", Box::new(v.clone())));
+    }
+    if let Some(ref v) = patch.book_type {
+        sets.push(("book_type", Box::new(book_types::validate(conn, v)?)));
+    }
+    if let Some(ref v) = patch.subjects {
+        sets.push(("subjects", Box::new(subjects_json(v))));
+    }
+
+    let shelves_changed = patch.add_shelves.as_ref().is_some_and(|s| !s.is_empty())
+        || patch.remove_shelves.as_ref().is_some_and(|s| !s.is_empty());
+    if sets.is_empty() && !shelves_changed {
+        return Ok(());
+    }
+
+    let assignments: Vec<String> = sets
+        .iter()
+        .enumerate()
+        .map(|(i, (name, _))| format!("{} = ?{}", name, i + 1))
+        .chain(std::iter::once("updated_at = datetime('now')".to_string()))
+        .collect();
+    let sql = format!(
+        "UPDATE books SET {} WHERE id = ?{}",
+        assignments.join(", "),
+        sets.len() + 1
+    );
+    let mut values: Vec<&dyn rusqlite::types::ToSql> = sets.iter().map(|(_, v)| v.as_ref()).collect();
+    values.push(&id);
+    conn.execute(&sql, rusqlite::params_from_iter(values))
+        .map_err(|e| e.to_string())?;
+
+    if let Some(ref names) = patch.remove_shelves {
+        for name in names {
+            conn.execute(
+                "DELETE FROM book_shelves WHERE book_id = ?1 AND shelf_id IN (SELECT id FROM shelves WHERE name = ?2)",
+                params![id, name.trim()],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    if let Some(ref names) = patch.add_shelves {
+        link_shelves(conn, id, names)?;
+    }
+
+    Ok(())
+}
+
+/// Find books whose stored ISBNs aren't in normalized form: formatting
+/// noise, a missing ISBN-10/13 counterpart, or values that fail their
+/// checksum. With `apply`, rewrite them (invalid values are cleared) after
+/// taking a backup; every change goes through history so it can be reverted.
+#[tauri::command]
+pub fn fix_invalid_isbns(state: State<DbState>, apply: bool) -> Result<Vec<IsbnFix>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+
+    let rows: Vec<(i64, String, Option<String>, Option<String>)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, title, isbn, isbn13 FROM books
+                 WHERE coalesce(isbn, '') != '' OR coalesce(isbn13, '') != '' ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        rows
+    };
+
+    let fixes: Vec<IsbnFix> = rows
+        .into_iter()
+        .filter_map(|(book_id, title, old_isbn, old_isbn13)| {
+            let (isbns, invalid) = isbn::resolve_lenient(old_isbn.as_deref(), old_isbn13.as_deref());
+            let unchanged = isbns.isbn == old_isbn && isbns.isbn13 == old_isbn13;
+            (!unchanged).then_some(IsbnFix {
+                book_id,
+                title,
+                old_isbn,
+                old_isbn13,
+                isbn: isbns.isbn,
+                isbn13: isbns.isbn13,
+                invalid,
+            })
+        })
+        .collect();
+
+    if apply && !fixes.is_empty() {
+        backup::create_backup(&conn, &state.data_dir, "pre-isbn-fix")?;
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        for fix in &fixes {
+            let before = history::snapshot(&tx, fix.book_id)?;
+            tx.execute(
+                "UPDATE books SET isbn = ?1, isbn13 = ?2, updated_at = datetime('now') WHERE id = ?3",
+                params![fix.isbn, fix.isbn13, fix.book_id],
+            )
+            .map_err(|e| e.to_string())?;
+            history::record(&tx, fix.book_id, "update", before.as_ref(), Some("ISBN cleanup"))?;
+        }
+        tx.commit().map_err(|e| e.to_string())?;
+    }
+
+    Ok(fixes)
+}
+
+/// Normalize an ISBN set by a patch and check it belongs in its column.
+/// Unlike full updates, the other half of the pair is left alone.
+fn patch_isbn(value: &Option<Option<String>>, len: usize) -> Result<Option<Option<String>>, String> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let parsed = value.as_deref().map(isbn::parse).transpose()?.flatten();
+    if let Some(ref v) = parsed {
+        if v.len() != len {
+            return Err(format!("Expected a {}-digit ISBN, got {}", len, v));
+        }
+    }
+    Ok(Some(parsed))
+}
+
+/// Move a book to the trash. Its row and shelf links are kept so it can be
+/// restored; see `commands::trash` for restore and purge.
 #[tauri::command]
 pub fn delete_book(state: State<DbState>, id: i64) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    conn.execute("DELETE FROM books WHERE id = ?1", params![id])
+    trash_book(&conn, id)
+}
+
+pub(crate) fn trash_book(conn: &rusqlite::Connection, id: i64) -> Result<(), String> {
+    let before = history::snapshot(conn, id)?.ok_or_else(|| "Book not found".to_string())?;
+    let changed = conn
+        .execute(
+            "UPDATE books SET deleted_at = datetime('now') WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+        )
         .map_err(|e| e.to_string())?;
-    Ok(())
+    if changed == 0 {
+        return Err(format!("Book {} is already in the trash", id));
+    }
+    history::record(conn, id, "delete", Some(&before), None)
 }
 
 #[tauri::command]
 pub fn clear_database(state: State<DbState>) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
-    conn.execute_batch("DELETE FROM book_shelves; DELETE FROM shelves; DELETE FROM books;")
+    backup::create_backup(&conn, &state.data_dir, "pre-clear")
+        .map_err(|e| format!("Aborted: could not back up before clearing: {}", e))?;
+    conn.execute_batch("DELETE FROM book_shelves; DELETE FROM shelves; DELETE FROM books; DELETE FROM book_history;")
         .map_err(|e| e.to_string())?;
     Ok(())
 }
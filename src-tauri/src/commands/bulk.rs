@@ -0,0 +1,192 @@
+use crate::beq;
+use crate::book_types;
+use crate::commands::books::{apply_patch, row_to_book, select_columns, trash_book};
+use crate::commands::metadata::merge_records;
+use crate::db::DbState;
+use crate::history;
+use crate::models::*;
+use crate::providers;
+use reqwest::Client;
+use rusqlite::{params, Connection};
+use tauri::State;
+
+/// Apply one operation to every book in `ids` inside a single transaction.
+/// Each book runs in its own savepoint, so one failure doesn't undo the rest.
+#[tauri::command]
+pub fn bulk_edit(
+    state: State<DbState>,
+    ids: Vec<i64>,
+    operation: BulkOperation,
+) -> Result<Vec<BulkItemResult>, String> {
+    let patch = match &operation {
+        BulkOperation::SetExclusiveShelf { shelf } => {
+            if shelf.trim().is_empty() {
+                return Err("Exclusive shelf cannot be empty".to_string());
+            }
+            Some(BookPatch { exclusive_shelf: Some(Some(shelf.trim().to_string())), ..Default::default() })
+        }
+        BulkOperation::AddShelves { shelves } => {
+            Some(BookPatch { add_shelves: Some(shelves.clone()), ..Default::default() })
+        }
+        BulkOperation::RemoveShelves { shelves } => {
+            Some(BookPatch { remove_shelves: Some(shelves.clone()), ..Default::default() })
+        }
+        BulkOperation::SetBookType { book_type } => {
+            Some(BookPatch { book_type: Some(book_type.clone()), ..Default::default() })
+        }
+        BulkOperation::SetRating { rating } => {
+            Some(BookPatch { my_rating: Some(*rating), ..Default::default() })
+        }
+        BulkOperation::SetOwnedCopies { copies } => {
+            if *copies < 0 {
+                return Err("Owned copies cannot be negative".to_string());
+            }
+            Some(BookPatch { owned_copies: Some(*copies), ..Default::default() })
+        }
+        BulkOperation::Delete => None,
+    };
+
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut results = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        let outcome = in_savepoint(&mut tx, |conn| {
+            ensure_active(conn, id)?;
+            match patch {
+                Some(ref p) => {
+                    let before = history::snapshot(conn, id)?;
+                    apply_patch(conn, id, p)?;
+                    history::record(conn, id, "update", before.as_ref(), Some("Bulk edit"))
+                }
+                None => trash_book(conn, id),
+            }
+        });
+        results.push(item_result(id, outcome));
+    }
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(results)
+}
+
+/// Re-fetch provider metadata for the selected books, filling only
+/// fields that are currently empty (publisher, pages, year published, cover).
+#[tauri::command]
+pub async fn bulk_refetch_metadata(
+    state: State<'_, DbState>,
+    client: State<'_, Client>,
+    ids: Vec<i64>,
+) -> Result<Vec<BulkItemResult>, String> {
+    let (providers, types, books): (_, _, Vec<(i64, Option<Book>)>) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        beq::sync(&conn)?;
+        let sql = format!(
+            "SELECT {} FROM books WHERE books.id = ?1 AND books.deleted_at IS NULL",
+            select_columns()
+        );
+        let books = ids
+            .iter()
+            .map(|id| {
+                let book = conn
+                    .query_row(&sql, params![id], row_to_book)
+                    .ok();
+                (*id, book)
+            })
+            .collect();
+        (providers::load(&conn), book_types::list(&conn)?, books)
+    };
+    // Lock is dropped here
+
+    let mut patches: Vec<(i64, Result<BookPatch, String>)> = Vec::with_capacity(books.len());
+    for (id, book) in books {
+        let Some(book) = book else {
+            patches.push((id, Err("Book not found".to_string())));
+            continue;
+        };
+        let Some(isbn) = book.isbn13.clone().or(book.isbn.clone()).filter(|s| !s.is_empty()) else {
+            patches.push((id, Err("No ISBN".to_string())));
+            continue;
+        };
+        let patch = match providers::lookup_all(&state, &providers, &client, &isbn).await {
+            Ok(records) => merge_records(&isbn, &records, &types)
+                .map(|found| fill_missing(&book, &found.draft))
+                .ok_or_else(|| "No metadata found".to_string()),
+            Err(e) => Err(e),
+        };
+        patches.push((id, patch));
+    }
+
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut results = Vec::with_capacity(patches.len());
+    for (id, patch) in patches {
+        let outcome = patch.and_then(|p| {
+            in_savepoint(&mut tx, |conn| {
+                let before = history::snapshot(conn, id)?;
+                apply_patch(conn, id, &p)?;
+                history::record(conn, id, "update", before.as_ref(), Some("Metadata refresh"))
+            })
+        });
+        results.push(item_result(id, outcome));
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(results)
+}
+
+/// Patch that sets only the fields the book is missing.
+fn fill_missing(book: &Book, data: &NewBook) -> BookPatch {
+    let mut patch = BookPatch::default();
+    if book.publisher.as_deref().is_none_or(str::is_empty) {
+        if let Some(ref p) = data.publisher {
+            patch.publisher = Some(Some(p.clone()));
+        }
+    }
+    if book.pages.is_none_or(|p| p <= 0) {
+        if let Some(p) = data.pages {
+            patch.pages = Some(Some(p));
+        }
+    }
+    if book.year_published.is_none() {
+        if let Some(y) = data.year_published {
+            patch.year_published = Some(Some(y));
+        }
+    }
+    if book.cover_url.is_none() {
+        if let Some(url) = data.cover_url.clone() {
+            patch.cover_url = Some(Some(url));
+        }
+    }
+    patch
+}
+
+fn ensure_active(conn: &Connection, id: i64) -> Result<(), String> {
+    let exists: i64 = conn
+        .query_row(
+            "SELECT count(*) FROM books WHERE id = ?1 AND deleted_at IS NULL",
+            params![id],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if exists == 0 {
+        return Err("Book not found".to_string());
+    }
+    Ok(())
+}
+
+/// Run `f` in a savepoint, rolling back just its changes on error.
+fn in_savepoint<F>(tx: &mut rusqlite::Transaction, f: F) -> Result<(), String>
+where
+    F: FnOnce(&Connection) -> Result<(), String>,
+{
+    let sp = tx.savepoint().map_err(|e| e.to_string())?;
+    f(&sp)?;
+    sp.commit().map_err(|e| e.to_string())
+}
+
+fn item_result(id: i64, outcome: Result<(), String>) -> BulkItemResult {
+    match outcome {
+        Ok(()) => BulkItemResult { id, ok: true, error: None },
+        Err(e) => BulkItemResult { id, ok: false, error: Some(e) },
+    }
+}
@@ -1,235 +1,723 @@
-use crate::db::DbState;
+use crate::beq;
+use crate::cover_cache;
+use crate::db::{get_setting_i64, set_setting, DbState};
+use crate::history;
+use crate::jobs::JobRegistry;
+use crate::commands::books::{ensure_live, row_to_book, select_columns};
+use crate::models::{Book, CoverCandidate, CoverColorResult, CoverPruneResult, CoverValidation, JobProgress};
+use crate::placeholder;
+use crate::providers::http::Throttle;
+use crate::providers::{self, MetadataProvider};
+use reqwest::Client;
 use rusqlite::params;
-use serde::{Deserialize, Serialize};
-use tauri::State;
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GoogleBooksResponse {
-    total_items: Option<i64>,
-    items: Option<Vec<GoogleBooksItem>>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GoogleBooksItem {
-    volume_info: Option<GoogleBooksVolumeInfo>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct GoogleBooksVolumeInfo {
-    image_links: Option<GoogleBooksImageLinks>,
-}
-
-#[derive(Debug, Deserialize)]
-struct GoogleBooksImageLinks {
-    thumbnail: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct FixCoversResult {
-    pub fixed: i64,
-    pub checked: i64,
-}
-
-/// Extract and clean a thumbnail URL from a Google Books response.
-fn extract_google_thumbnail(resp: &GoogleBooksResponse) -> Option<String> {
-    let url = resp
-        .items
-        .as_ref()?
-        .first()?
-        .volume_info
-        .as_ref()?
-        .image_links
-        .as_ref()?
-        .thumbnail
-        .as_ref()?;
-
-    // Fix http → https and request larger image
-    let cleaned = url
-        .replace("http://", "https://")
-        .replace("&zoom=1", "&zoom=0")
-        .replace("zoom=1", "zoom=0");
-    Some(cleaned)
-}
-
-/// Try Google Books API by ISBN.
-async fn google_books_by_isbn(client: &reqwest::Client, isbn: &str) -> Option<String> {
-    let url = format!(
-        "https://www.googleapis.com/books/v1/volumes?q=isbn:{}&maxResults=1",
-        isbn
-    );
-    let resp: GoogleBooksResponse = client.get(&url).send().await.ok()?.json().await.ok()?;
-    extract_google_thumbnail(&resp)
-}
-
-/// Try Google Books API by title + author.
-async fn google_books_by_title_author(
-    client: &reqwest::Client,
-    title: &str,
-    author: &str,
-) -> Option<String> {
-    let query = format!("intitle:{}+inauthor:{}", title, author);
-    let url = format!(
-        "https://www.googleapis.com/books/v1/volumes?q={}&maxResults=1",
-        urlencoding::encode(&query)
-    );
-    let resp: GoogleBooksResponse = client.get(&url).send().await.ok()?.json().await.ok()?;
-    extract_google_thumbnail(&resp)
-}
-
-/// Validate an Open Library cover URL via HEAD request.
-/// Returns false for 1x1 pixel responses (~43 bytes).
-async fn validate_image_url(client: &reqwest::Client, url: &str) -> bool {
-    let resp = match client.head(url).send().await {
-        Ok(r) => r,
-        Err(_) => return false,
-    };
-    if !resp.status().is_success() {
-        return false;
-    }
-    // Open Library returns a tiny image when no cover exists
-    resp.headers()
-        .get("content-length")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| v.parse::<u64>().ok())
-        .is_some_and(|len| len > 1000)
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task::{self, JoinSet};
+
+pub const JOB_NAME: &str = "fix-covers";
+const PROGRESS_EVENT: &str = "fix-covers-progress";
+pub const CACHE_JOB_NAME: &str = "cache-covers";
+const CACHE_PROGRESS_EVENT: &str = "cache-covers-progress";
+/// Settings key holding the id of the last book a run finished with
+const CURSOR_KEY: &str = "fix_covers_cursor";
+/// Title + author matches offered per provider by `get_cover_candidates`
+const MAX_SEARCH_COVERS: usize = 3;
+/// Books looked up at once unless `fix_covers_concurrency` is set
+pub const DEFAULT_CONCURRENCY: i64 = 4;
+
+struct PendingCover {
+    id: i64,
+    isbn: Option<String>,
+    isbn13: Option<String>,
+    title: String,
+    author: String,
+    cover_url: Option<String>,
 }
 
-/// The fallback chain: Google Books (ISBN) → Open Library (ISBN, validated) → Google Books (title+author).
+/// The fallback chain: each provider's ISBN cover in priority order, then
+/// each provider's best title + author match. Every URL must pass
+/// `cover_cache::check_url`, so placeholders and broken images are skipped.
+/// `Ok(None)` only when every provider answered; if nothing was found and a
+/// request failed, the last error is returned instead.
 pub async fn resolve_cover_url(
-    client: &reqwest::Client,
+    db: &DbState,
+    client: &Client,
+    providers: &[Box<dyn MetadataProvider>],
     isbn: Option<&str>,
     isbn13: Option<&str>,
     title: &str,
     author: &str,
-) -> Option<String> {
+) -> Result<Option<String>, String> {
+    let mut last_err = None;
     let isbn_val = isbn13
         .or(isbn)
         .filter(|s| !s.is_empty());
 
-    // 1. Google Books by ISBN
+    // 1. By ISBN
     if let Some(id) = isbn_val {
-        if let Some(url) = google_books_by_isbn(client, id).await {
-            return Some(url);
+        for provider in providers {
+            match providers::cover_by_isbn(db, provider.as_ref(), client, id).await {
+                Ok(Some(url)) => match is_valid_cover(db, client, &url).await {
+                    Ok(true) => return Ok(Some(url)),
+                    Ok(false) => {}
+                    Err(e) => last_err = Some(e),
+                },
+                Ok(None) => {}
+                Err(e) => last_err = Some(format!("{}: {}", provider.id(), e)),
+            }
         }
     }
 
-    // 2. Open Library by ISBN (validate with HEAD request)
-    if let Some(id) = isbn_val {
-        let ol_url = format!("https://covers.openlibrary.org/b/isbn/{}-M.jpg", id);
-        if validate_image_url(client, &ol_url).await {
-            return Some(ol_url);
+    // 2. By title + author
+    if !title.is_empty() && !author.is_empty() {
+        for provider in providers {
+            let matches = match providers::search(db, provider.as_ref(), client, title, author).await {
+                Ok(matches) => matches,
+                Err(e) => {
+                    last_err = Some(format!("{}: {}", provider.id(), e));
+                    continue;
+                }
+            };
+            if let Some(url) = matches.into_iter().find_map(|m| m.cover_url) {
+                match is_valid_cover(db, client, &url).await {
+                    Ok(true) => return Ok(Some(url)),
+                    Ok(false) => {}
+                    Err(e) => last_err = Some(e),
+                }
+            }
         }
     }
 
-    // 3. Google Books by title + author
-    if !title.is_empty() && !author.is_empty() {
-        if let Some(url) = google_books_by_title_author(client, title, author).await {
-            return Some(url);
-        }
+    match last_err {
+        Some(e) => Err(e),
+        None => Ok(None),
     }
+}
 
-    None
+/// Whether the URL serves a usable cover. Errors mean it couldn't be checked.
+async fn is_valid_cover(db: &DbState, client: &Client, url: &str) -> Result<bool, String> {
+    cover_cache::check_url(db, client, url).await.map(|v| v.valid)
 }
 
 /// Look up a single book's cover using the fallback chain.
 #[tauri::command]
 pub async fn lookup_cover(
+    state: State<'_, DbState>,
+    client: State<'_, Client>,
     isbn: Option<String>,
     isbn13: Option<String>,
     title: String,
     author: String,
 ) -> Result<Option<String>, String> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .connect_timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| e.to_string())?;
-    Ok(resolve_cover_url(
+    let providers = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        providers::load(&conn)
+    };
+    resolve_cover_url(
+        &state,
         &client,
+        &providers,
         isbn.as_deref(),
         isbn13.as_deref(),
         &title,
         &author,
     )
-    .await)
+    .await
 }
 
-/// Re-fetch covers for books with no cover or an invalid Open Library placeholder.
+/// Start a background job that re-fetches covers for books with no cover or
+/// an Open Library placeholder. Books are processed a few at a time (see
+/// `fix_covers_concurrency`), each provider keeps to its own rate limit, and
+/// progress is emitted as `fix-covers-progress`. The last finished book is
+/// saved as a cursor, so a cancelled or interrupted run resumes where it
+/// stopped unless `restart` is set. A book whose lookup failed (network or
+/// provider errors) is left unchanged and the cursor stays before it, so
+/// the next run retries it. Returns the number of books queued.
 #[tauri::command]
-#[allow(clippy::type_complexity)]
-pub async fn fix_missing_covers(state: State<'_, DbState>) -> Result<FixCoversResult, String> {
-    // Collect candidates: books with NULL cover OR openlibrary cover (may be placeholder)
-    let candidates: Vec<(i64, Option<String>, Option<String>, String, String, Option<String>)> = {
+pub fn fix_missing_covers(
+    app: AppHandle,
+    state: State<DbState>,
+    jobs: State<JobRegistry>,
+    restart: Option<bool>,
+) -> Result<i64, String> {
+    let (providers, candidates, concurrency) = {
         let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        if restart.unwrap_or(false) {
+            conn.execute("DELETE FROM settings WHERE key = ?1", params![CURSOR_KEY])
+                .map_err(|e| e.to_string())?;
+        }
+        let cursor = get_setting_i64(&conn, CURSOR_KEY, 0);
         let mut stmt = conn
             .prepare(
                 "SELECT id, isbn, isbn13, title, author, cover_url FROM books \
-                 WHERE cover_url IS NULL OR cover_url LIKE '%openlibrary%'",
+                 WHERE deleted_at IS NULL AND (cover_url IS NULL OR cover_url LIKE '%openlibrary%') \
+                 AND coalesce(cover_cached_from, '') != 'upload' \
+                 AND coalesce(cover_url, '') NOT IN (SELECT url FROM cover_validations WHERE valid = 1) \
+                 AND id > ?1 ORDER BY id",
             )
             .map_err(|e| e.to_string())?;
-        let rows = stmt
-            .query_map([], |row| {
-                Ok((
-                    row.get::<_, i64>(0)?,
-                    row.get::<_, Option<String>>(1)?,
-                    row.get::<_, Option<String>>(2)?,
-                    row.get::<_, String>(3)?,
-                    row.get::<_, String>(4)?,
-                    row.get::<_, Option<String>>(5)?,
-                ))
+        let rows: Vec<PendingCover> = stmt
+            .query_map(params![cursor], |row| {
+                Ok(PendingCover {
+                    id: row.get(0)?,
+                    isbn: row.get(1)?,
+                    isbn13: row.get(2)?,
+                    title: row.get(3)?,
+                    author: row.get(4)?,
+                    cover_url: row.get(5)?,
+                })
             })
             .map_err(|e| e.to_string())?
             .filter_map(|r| r.ok())
-            .collect::<Vec<_>>();
-        rows
+            .collect();
+        let concurrency = get_setting_i64(&conn, "fix_covers_concurrency", DEFAULT_CONCURRENCY).clamp(1, 16);
+        (providers::load(&conn), rows, concurrency as usize)
     };
     // Lock is dropped here
 
-    let checked = candidates.len() as i64;
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(10))
-        .connect_timeout(std::time::Duration::from_secs(5))
-        .build()
-        .map_err(|e| e.to_string())?;
-    let mut updates: Vec<(i64, String)> = Vec::new();
+    let cancel = jobs.start(JOB_NAME)?;
+    let total = candidates.len() as i64;
+    tauri::async_runtime::spawn(run_fix_covers(app, cancel, Arc::new(providers), candidates, concurrency));
+    Ok(total)
+}
 
-    for (id, isbn, isbn13, title, author, existing_cover) in &candidates {
-        // If book already has an openlibrary URL, validate it first — skip if it's real
-        if let Some(ref url) = existing_cover {
-            if url.contains("openlibrary") && validate_image_url(&client, url).await {
-                continue; // existing cover is valid, no need to re-fetch
+async fn run_fix_covers(
+    app: AppHandle,
+    cancel: Arc<AtomicBool>,
+    providers: Arc<Vec<Box<dyn MetadataProvider>>>,
+    candidates: Vec<PendingCover>,
+    concurrency: usize,
+) {
+    let total = candidates.len() as i64;
+    let mut processed = 0;
+    let mut fixed = 0;
+    let mut cancelled = false;
+    let mut in_flight: BTreeSet<i64> = BTreeSet::new();
+    let mut failed: BTreeSet<i64> = BTreeSet::new();
+    let mut last_started = None;
+    let mut tasks = JoinSet::new();
+    // Task id → (book id, title), so a panicked task can still be accounted for
+    let mut books: HashMap<task::Id, (i64, String)> = HashMap::new();
+    let mut queue = candidates.into_iter();
+
+    loop {
+        while tasks.len() < concurrency && !cancelled {
+            if cancel.load(Ordering::Relaxed) {
+                cancelled = true;
+                break;
             }
+            let Some(book) = queue.next() else { break };
+            in_flight.insert(book.id);
+            last_started = Some(book.id);
+            let info = (book.id, book.title.clone());
+            let handle = tasks.spawn(fix_one(app.clone(), providers.clone(), book));
+            books.insert(handle.id(), info);
         }
 
-        if let Some(url) = resolve_cover_url(
-            &client,
-            isbn.as_deref(),
-            isbn13.as_deref(),
-            title,
-            author,
-        )
-        .await
-        {
-            updates.push((*id, url));
+        let Some(joined) = tasks.join_next_with_id().await else { break };
+        let (task_id, outcome) = match joined {
+            Ok((task_id, outcome)) => (task_id, outcome),
+            Err(e) => (e.id(), Err(format!("Cover fix stopped unexpectedly: {}", e))),
+        };
+        let Some((id, title)) = books.remove(&task_id) else { continue };
+        in_flight.remove(&id);
+        processed += 1;
+        let mut error = match outcome {
+            Ok((updated, warning)) => {
+                fixed += updated as i64;
+                warning
+            }
+            Err(e) => {
+                failed.insert(id);
+                Some(e)
+            }
+        };
+
+        // Everything up to just before the oldest unfinished or failed book is done
+        let oldest = in_flight.first().into_iter().chain(failed.first()).min();
+        if let Some(cursor) = oldest.map(|first| first - 1).or(last_started) {
+            if let Err(e) = save_cursor(&app, Some(cursor)) {
+                error.get_or_insert(e);
+            }
+        }
+        let _ = app.emit(PROGRESS_EVENT, JobProgress {
+            job: JOB_NAME.to_string(),
+            processed,
+            total,
+            book_id: Some(id),
+            title: Some(title),
+            succeeded: fixed,
+            failed: failed.len() as i64,
+            error,
+            done: false,
+            cancelled: false,
+        });
+    }
+
+    let error = if !cancelled && failed.is_empty() { save_cursor(&app, None).err() } else { None };
+    app.state::<JobRegistry>().finish(JOB_NAME);
+    let _ = app.emit(PROGRESS_EVENT, JobProgress {
+        job: JOB_NAME.to_string(),
+        processed,
+        total,
+        book_id: None,
+        title: None,
+        succeeded: fixed,
+        failed: failed.len() as i64,
+        error,
+        done: true,
+        cancelled,
+    });
+}
+
+/// Look up and store a cover for one book. Returns whether it was updated
+/// and, if the new cover couldn't be downloaded into the cache, why. On
+/// errors the book is left as it was.
+async fn fix_one(
+    app: AppHandle,
+    providers: Arc<Vec<Box<dyn MetadataProvider>>>,
+    book: PendingCover,
+) -> Result<(bool, Option<String>), String> {
+    let state = app.state::<DbState>();
+    let client = app.state::<Client>();
+
+    // An existing Open Library URL may be a real cover; keep it if so
+    if let Some(ref url) = book.cover_url {
+        if is_valid_cover(&state, &client, url).await? {
+            return Ok((false, None));
         }
     }
 
-    // Batch update with lock
-    let fixed = updates.len() as i64;
+    let found = resolve_cover_url(
+        &state,
+        &client,
+        &providers,
+        book.isbn.as_deref(),
+        book.isbn13.as_deref(),
+        &book.title,
+        &book.author,
+    )
+    .await?;
+    let Some(url) = found else {
+        return use_placeholder(&state, &book).map(|_| (false, None));
+    };
+
     {
         let conn = state.conn.lock().map_err(|e| e.to_string())?;
-        for (id, url) in &updates {
-            conn.execute(
-                "UPDATE books SET cover_url = ?1 WHERE id = ?2",
-                params![url, id],
+        let before = history::snapshot(&conn, book.id)?;
+        conn.execute(
+            "UPDATE books SET cover_url = ?1, updated_at = datetime('now') WHERE id = ?2",
+            params![url, book.id],
+        )
+        .map_err(|e| e.to_string())?;
+        placeholder::refresh(&conn, &state.data_dir, book.id)?;
+        history::record(&conn, book.id, "update", before.as_ref(), Some("Cover refresh"))?;
+    }
+    let uncached = cover_cache::cache_from_url(&state, &client, book.id, &url)
+        .await
+        .err()
+        .map(|e| format!("Cover updated but not cached: {}", e));
+    Ok((true, uncached))
+}
+
+/// Every provider answered without a cover: drop the current URL, which was
+/// checked and isn't a usable image, and generate a placeholder.
+fn use_placeholder(state: &DbState, book: &PendingCover) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let before = history::snapshot(&conn, book.id)?;
+    if book.cover_url.is_some() {
+        conn.execute(
+            "UPDATE books SET cover_url = NULL, updated_at = datetime('now') WHERE id = ?1 AND cover_url = ?2",
+            params![book.id, book.cover_url],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    placeholder::refresh(&conn, &state.data_dir, book.id)?;
+    history::record(&conn, book.id, "update", before.as_ref(), Some("Placeholder cover"))
+}
+
+/// Store (or, with `None`, clear) the resume point of the fix-covers job.
+fn save_cursor(app: &AppHandle, cursor: Option<i64>) -> Result<(), String> {
+    let state = app.state::<DbState>();
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    match cursor {
+        Some(id) => set_setting(&conn, CURSOR_KEY, &id.to_string()),
+        None => conn
+            .execute("DELETE FROM settings WHERE key = ?1", params![CURSOR_KEY])
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    }
+    .map_err(|e| format!("Failed to save cover fix progress: {}", e))
+}
+
+/// Download a single book's current cover into the local cache. Returns the
+/// cover hash, or `None` if the book has no remote cover.
+#[tauri::command]
+pub async fn cache_book_cover(
+    state: State<'_, DbState>,
+    client: State<'_, Client>,
+    id: i64,
+) -> Result<Option<String>, String> {
+    let url: Option<String> = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        conn.query_row("SELECT cover_url FROM books WHERE id = ?1", params![id], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+    };
+    match url.filter(|u| u.starts_with("http")) {
+        Some(url) => cover_cache::cache_from_url(&state, &client, id, &url).await.map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Start a background job that downloads every remote cover not cached yet
+/// (or changed since). Emits `cache-covers-progress`; returns the number queued.
+#[tauri::command]
+pub fn cache_covers(app: AppHandle, state: State<DbState>, jobs: State<JobRegistry>) -> Result<i64, String> {
+    let candidates = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        cover_cache::uncached(&conn)?
+    };
+    let cancel = jobs.start(CACHE_JOB_NAME)?;
+    let total = candidates.len() as i64;
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<DbState>();
+        let client = app.state::<Client>();
+        let mut processed = 0;
+        let mut cached = 0;
+        let mut failed = 0;
+        let mut cancelled = false;
+
+        for (id, title, url) in candidates {
+            if cancel.load(Ordering::Relaxed) {
+                cancelled = true;
+                break;
+            }
+            let error = match cover_cache::cache_from_url(&state, &client, id, &url).await {
+                Ok(_) => {
+                    cached += 1;
+                    None
+                }
+                Err(e) => {
+                    failed += 1;
+                    Some(e)
+                }
+            };
+            processed += 1;
+            let _ = app.emit(CACHE_PROGRESS_EVENT, JobProgress {
+                job: CACHE_JOB_NAME.to_string(),
+                processed,
+                total,
+                book_id: Some(id),
+                title: Some(title),
+                succeeded: cached,
+                failed,
+                error,
+                done: false,
+                cancelled: false,
+            });
+        }
+
+        app.state::<JobRegistry>().finish(CACHE_JOB_NAME);
+        let _ = app.emit(CACHE_PROGRESS_EVENT, JobProgress {
+            job: CACHE_JOB_NAME.to_string(),
+            processed,
+            total,
+            book_id: None,
+            title: None,
+            succeeded: cached,
+            failed,
+            error: None,
+            done: true,
+            cancelled,
+        });
+    });
+    Ok(total)
+}
+
+/// Remove cached cover files that no book refers to any more.
+#[tauri::command]
+pub fn prune_cover_cache(state: State<DbState>) -> Result<CoverPruneResult, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    cover_cache::prune(&conn, &state.data_dir)
+}
+
+/// Use an image the user picked as the book's cover. It is validated,
+/// scaled down if oversized and stored in the cover cache; the remote
+/// `cover_url` is cleared and the cover re-fetch job leaves the book alone.
+///
+/// Takes the file's contents rather than its path: the form picks the image
+/// with a webview file input, which never exposes the local path.
+#[tauri::command]
+pub fn set_custom_cover(state: State<DbState>, id: i64, bytes: Vec<u8>) -> Result<Book, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    ensure_live(&conn, id)?;
+    let stored = cover_cache::store_upload(&state.data_dir, &bytes)?;
+    let before = history::snapshot(&conn, id)?.ok_or_else(|| "Book not found".to_string())?;
+    conn.execute(
+        "UPDATE books SET cover_url = NULL, cover_path = ?1, cover_hash = ?2, cover_cached_from = ?3,
+         cover_color = ?5, cover_accent = ?6, cover_hue = ?7, updated_at = datetime('now') WHERE id = ?4",
+        params![
+            stored.path,
+            stored.hash,
+            cover_cache::UPLOAD_SOURCE,
+            id,
+            stored.palette.dominant,
+            stored.palette.accent,
+            stored.palette.hue,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    history::record(&conn, id, "update", Some(&before), Some("Custom cover"))?;
+    beq::sync(&conn)?;
+
+    let sql = format!("SELECT {} FROM books WHERE books.id = ?1", select_columns());
+    conn.query_row(&sql, params![id], row_to_book)
+        .map_err(|e| e.to_string())
+}
+
+/// Every cover the providers offer for a book: ISBN matches first, then the
+/// top title + author matches, each with its pixel dimensions. Images that
+/// can't be downloaded or decoded are left out.
+#[tauri::command]
+pub async fn get_cover_candidates(
+    state: State<'_, DbState>,
+    client: State<'_, Client>,
+    id: i64,
+) -> Result<Vec<CoverCandidate>, String> {
+    let (providers, isbn, title, author) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let (isbn, title, author): (Option<String>, String, String) = conn
+            .query_row(
+                "SELECT coalesce(nullif(isbn13, ''), nullif(isbn, '')), title, author FROM books WHERE id = ?1",
+                params![id],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .map_err(|e| format!("Book not found: {}", e))?;
+        (providers::load(&conn), isbn, title, author)
+    };
+
+    // (url, provider id, method) in display order
+    let mut found: Vec<(String, String, &str)> = Vec::new();
+    if let Some(ref isbn) = isbn {
+        for provider in &providers {
+            if let Ok(Some(url)) = providers::cover_by_isbn(&state, provider.as_ref(), &client, isbn).await {
+                found.push((url, provider.id().to_string(), "isbn"));
+            }
+        }
+        for record in providers::lookup_all(&state, &providers, &client, isbn).await.unwrap_or_default() {
+            if let Some(url) = record.cover_url {
+                found.push((url, record.source, "isbn"));
+            }
+        }
+    }
+    for provider in &providers {
+        let matches = providers::search(&state, provider.as_ref(), &client, &title, &author)
+            .await
+            .unwrap_or_default();
+        for url in matches.into_iter().filter_map(|m| m.cover_url).take(MAX_SEARCH_COVERS) {
+            found.push((url, provider.id().to_string(), "search"));
+        }
+    }
+
+    let throttle = Throttle::new("cover-download", None);
+    let mut seen = HashSet::new();
+    let mut candidates = Vec::new();
+    for (url, source, method) in found {
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        let Ok(resp) = throttle.send(client.get(&url)).await else { continue };
+        if !resp.status().is_success() {
+            continue;
+        }
+        let Ok(bytes) = resp.bytes().await else { continue };
+        // Skip blank and blocklisted placeholders
+        let Ok((width, height)) = cover_cache::inspect(&bytes) else { continue };
+        let blocked = {
+            let conn = state.conn.lock().map_err(|e| e.to_string())?;
+            cover_cache::is_blocked(&conn, &cover_cache::sha256_hex(&bytes))
+        };
+        if blocked {
+            continue;
+        }
+        candidates.push(CoverCandidate {
+            url,
+            source,
+            method: method.to_string(),
+            width,
+            height,
+        });
+    }
+    Ok(candidates)
+}
+
+/// Mark a book's current cover image as a placeholder. The image hash is
+/// blocklisted so no provider URL serving it is accepted again, and every
+/// book showing it loses its cover (so the re-fetch job picks them up).
+/// Returns the number of books cleared.
+#[tauri::command]
+pub fn block_cover(state: State<DbState>, id: i64, note: Option<String>) -> Result<i64, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let hash: Option<String> = conn
+        .query_row(
+            "SELECT coalesce(b.cover_hash, v.hash) FROM books b
+             LEFT JOIN cover_validations v ON v.url = b.cover_url WHERE b.id = ?1",
+            params![id],
+            |r| r.get(0),
+        )
+        .map_err(|e| format!("Book not found: {}", e))?;
+    let hash = hash.ok_or_else(|| "This cover hasn't been downloaded or checked yet".to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT OR REPLACE INTO cover_blocklist (hash, note) VALUES (?1, ?2)",
+        params![hash, note],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.execute(
+        "UPDATE cover_validations SET valid = 0, reason = 'Known placeholder image', checked_at = datetime('now')
+         WHERE hash = ?1",
+        params![hash],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let affected: Vec<i64> = {
+        let mut stmt = tx
+            .prepare(
+                "SELECT id FROM books WHERE cover_hash = ?1
+                 OR cover_url IN (SELECT url FROM cover_validations WHERE hash = ?1)",
             )
             .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![hash], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        ids
+    };
+    for book_id in &affected {
+        let before = history::snapshot(&tx, *book_id)?;
+        tx.execute(
+            "UPDATE books SET cover_url = NULL, cover_path = NULL, cover_hash = NULL, cover_cached_from = NULL,
+             cover_color = NULL, cover_accent = NULL, cover_hue = NULL, updated_at = datetime('now') WHERE id = ?1",
+            params![book_id],
+        )
+        .map_err(|e| e.to_string())?;
+        placeholder::refresh(&tx, &state.data_dir, *book_id)?;
+        history::record(&tx, *book_id, "update", before.as_ref(), Some("Placeholder cover removed"))?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(affected.len() as i64)
+}
+
+/// Hashes on the placeholder blocklist, newest first.
+#[tauri::command]
+pub fn get_cover_blocklist(state: State<DbState>) -> Result<Vec<String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT hash FROM cover_blocklist ORDER BY created_at DESC")
+        .map_err(|e| e.to_string())?;
+    let hashes = stmt
+        .query_map([], |r| r.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(hashes)
+}
+
+/// Take a hash off the blocklist. Covers cleared by `block_cover` stay
+/// cleared; URLs serving the image are re-validated next time they're seen.
+#[tauri::command]
+pub fn unblock_cover(state: State<DbState>, hash: String) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM cover_blocklist WHERE hash = ?1", params![hash])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Recorded validation result for a cover URL, if it has been checked.
+#[tauri::command]
+pub fn get_cover_validation(state: State<DbState>, url: String) -> Result<Option<CoverValidation>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    cover_cache::recorded(&conn, &url)
+}
+
+/// Generate placeholder covers for every book without artwork, and redraw
+/// existing generated covers (e.g. after a title change made elsewhere).
+/// Returns the number of books updated.
+#[tauri::command]
+pub fn generate_placeholder_covers(state: State<DbState>) -> Result<i64, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let ids: Vec<i64> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id FROM books WHERE deleted_at IS NULL AND coalesce(cover_url, '') = ''
+                 AND (cover_hash IS NULL OR cover_cached_from = ?1) ORDER BY id",
+            )
+            .map_err(|e| e.to_string())?;
+        let ids = stmt
+            .query_map(params![placeholder::GENERATED_SOURCE], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        ids
+    };
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut updated = 0;
+    for id in ids {
+        let before = history::snapshot(&tx, id)?;
+        if placeholder::refresh(&tx, &state.data_dir, id)? {
+            history::record(&tx, id, "update", before.as_ref(), Some("Placeholder cover"))?;
+            updated += 1;
         }
     }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+/// Extract colors for cached covers stored before colors were recorded.
+/// Generated placeholders get theirs when redrawn. Covers that can't be read
+/// are skipped and listed in the result.
+#[tauri::command]
+pub fn extract_cover_colors(state: State<DbState>) -> Result<CoverColorResult, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let hashes: Vec<String> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT cover_hash FROM books
+                 WHERE cover_hash IS NOT NULL AND cover_color IS NULL AND coalesce(cover_cached_from, '') != ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let hashes = stmt
+            .query_map(params![placeholder::GENERATED_SOURCE], |r| r.get(0))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        hashes
+    };
 
-    Ok(FixCoversResult { fixed, checked })
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut updated = 0;
+    let mut failures = Vec::new();
+    for hash in hashes {
+        let palette = match cover_cache::palette_of(&state.data_dir, &hash) {
+            Ok(palette) => palette,
+            Err(e) => {
+                failures.push(format!("{}: {}", hash, e));
+                continue;
+            }
+        };
+        updated += tx
+            .execute(
+                "UPDATE books SET cover_color = ?1, cover_accent = ?2, cover_hue = ?3
+                 WHERE cover_hash = ?4 AND cover_color IS NULL",
+                params![palette.dominant, palette.accent, palette.hue, hash],
+            )
+            .map_err(|e| e.to_string())? as i64;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(CoverColorResult { books_updated: updated, failures })
 }
@@ -0,0 +1,71 @@
+use crate::beq;
+use crate::commands::stats::today;
+use crate::db::DbState;
+use crate::goals;
+use crate::models::{Goal, GoalProgress};
+use rusqlite::params;
+use tauri::State;
+
+/// Goals and challenges with their progress, optionally only those for
+/// `year`.
+#[tauri::command]
+pub fn get_goals(state: State<DbState>, year: Option<i64>) -> Result<Vec<GoalProgress>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
+    let today = today(&conn)?;
+    goals::list(&conn, year)?
+        .iter()
+        .map(|goal| goals::progress(&conn, goal, today))
+        .collect()
+}
+
+/// Create a goal, or update it when `goal.id` is set.
+///
+/// `distinct_by` counts different authors, publishers, languages or shelves.
+/// Books have no country or origin field, so a challenge like "12 books from
+/// different countries" only works by shelving books as e.g. `country-france`
+/// and counting `shelf:country-`.
+#[tauri::command]
+pub fn save_goal(state: State<DbState>, goal: Goal) -> Result<GoalProgress, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut goal = goals::validate(&conn, &goal)?;
+    match goal.id {
+        Some(id) => {
+            let changed = conn
+                .execute(
+                    "UPDATE goals SET name = ?1, period = ?2, year = ?3, month = ?4, metric = ?5,
+                     target = ?6, filter = ?7, distinct_by = ?8 WHERE id = ?9",
+                    params![
+                        goal.name, goal.period, goal.year, goal.month, goal.metric,
+                        goal.target, goal.filter, goal.distinct_by, id,
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            if changed == 0 {
+                return Err(format!("Goal not found: {}", id));
+            }
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO goals (name, period, year, month, metric, target, filter, distinct_by)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    goal.name, goal.period, goal.year, goal.month, goal.metric,
+                    goal.target, goal.filter, goal.distinct_by,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            goal.id = Some(conn.last_insert_rowid());
+        }
+    }
+    beq::sync(&conn)?;
+    goals::progress(&conn, &goal, today(&conn)?)
+}
+
+#[tauri::command]
+pub fn delete_goal(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM goals WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
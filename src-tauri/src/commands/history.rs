@@ -0,0 +1,31 @@
+use crate::db::DbState;
+use crate::history;
+use crate::models::*;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_book_history(state: State<DbState>, book_id: i64) -> Result<Vec<HistoryEntry>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    history::timeline(&conn, book_id)
+}
+
+/// Restore every tracked field (and shelf membership) to how it was right
+/// after `revision`. The revert itself is recorded as a new revision.
+#[tauri::command]
+pub fn revert_book(state: State<DbState>, book_id: i64, revision: i64) -> Result<(), String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    history::revert_to(&tx, book_id, revision)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Undo the most recent change to any book. Returns the entry that was
+/// undone, or `None` when there is nothing left to undo.
+#[tauri::command]
+pub fn undo_last_change(state: State<DbState>) -> Result<Option<HistoryEntry>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let undone = history::undo_last(&tx)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(undone)
+}
@@ -1,27 +1,15 @@
+use crate::backup;
+use crate::book_types;
+use crate::dates;
 use crate::db::DbState;
+use crate::history;
+use crate::isbn;
 use crate::models::*;
-use rusqlite::params;
+use crate::placeholder;
+use rusqlite::{params, Connection};
 use std::collections::HashMap;
 use tauri::State;
 
-/// Extract a 4-digit year from a date string like "2024/05/15", "2024-05-15", "May 15, 2024", etc.
-fn extract_year(date_str: &str) -> Option<i64> {
-    // Find the first 4-digit number that looks like a year (1900-2099)
-    let mut i = 0;
-    let bytes = date_str.as_bytes();
-    while i + 3 < bytes.len() {
-        if bytes[i].is_ascii_digit() && bytes[i+1].is_ascii_digit() && bytes[i+2].is_ascii_digit() && bytes[i+3].is_ascii_digit() {
-            if let Ok(y) = date_str[i..i+4].parse::<i64>() {
-                if (1900..=2099).contains(&y) {
-                    return Some(y);
-                }
-            }
-        }
-        i += 1;
-    }
-    None
-}
-
 /// Known aliases: maps common CSV header variations → our canonical field names
 fn default_aliases() -> HashMap<String, String> {
     let pairs = vec![
@@ -46,6 +34,7 @@ fn default_aliases() -> HashMap<String, String> {
         ("Published", "year_published"),
         ("Edition Published", "edition_published"),
         ("Date Read", "date_read"),
+        ("Date Started", "date_started"),
         ("Year Read", "year_read"),
         ("Date Added", "date_added"),
         ("Exclusive Shelf", "exclusive_shelf"),
@@ -117,6 +106,8 @@ pub fn import_csv(
     column_map: Option<HashMap<String, String>>,
 ) -> Result<ImportResult, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    backup::create_backup(&conn, &state.data_dir, "pre-import")
+        .map_err(|e| format!("Aborted: could not back up before import: {}", e))?;
     let mut reader = csv::ReaderBuilder::new()
         .has_headers(true)
         .flexible(true)
@@ -140,9 +131,13 @@ pub fn import_csv(
             .and_then(|csv_header| headers.iter().position(|h| h.trim() == csv_header))
     };
 
+    let types = book_types::list(&conn)?;
+
     let mut imported: i64 = 0;
     let mut total: i64 = 0;
     let mut skipped: Vec<String> = Vec::new();
+    let mut also_in_trash: Vec<String> = Vec::new();
+    let mut invalid_isbns: Vec<String> = Vec::new();
 
     for result in reader.records() {
         total += 1;
@@ -159,68 +154,46 @@ pub fn import_csv(
                 .to_string()
         };
 
-        let clean_isbn = |raw: String| -> Option<String> {
-            let cleaned = raw.replace(['=', '"'], "").trim().to_string();
-            if cleaned.is_empty() {
-                None
-            } else {
-                Some(cleaned)
-            }
-        };
-
         let title = get("title");
         let author = get("author");
         if title.is_empty() && author.is_empty() {
             continue;
         }
 
-        let isbn = clean_isbn(get("isbn"));
-        let isbn13 = clean_isbn(get("isbn13"));
+        // Bad ISBNs are dropped (and reported) rather than failing the row
+        let (isbns, rejected) = isbn::resolve_lenient(Some(&get("isbn")), Some(&get("isbn13")));
+        let isbn::Isbns { isbn, isbn13 } = isbns;
 
-        // Dedup check: isbn13 → isbn → goodreads_id → title+author
+        // Dedup against live books only; a copy in the trash doesn't block
+        // the import but is reported so the user knows it's there
         let goodreads_id: Option<i64> = get("goodreads_id").parse().ok();
-        let is_dup = (|| {
-            if let Some(ref id) = isbn13 {
-                let count: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM books WHERE isbn13 = ?1", params![id], |r| r.get(0),
-                ).unwrap_or(0);
-                if count > 0 { return true; }
-            }
-            if let Some(ref id) = isbn {
-                let count: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM books WHERE isbn = ?1", params![id], |r| r.get(0),
-                ).unwrap_or(0);
-                if count > 0 { return true; }
-            }
-            if let Some(id) = goodreads_id {
-                let count: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM books WHERE goodreads_id = ?1", params![id], |r| r.get(0),
-                ).unwrap_or(0);
-                if count > 0 { return true; }
-            }
-            if !title.is_empty() && !author.is_empty() {
-                let count: i64 = conn.query_row(
-                    "SELECT COUNT(*) FROM books WHERE LOWER(title) = LOWER(?1) AND LOWER(author) = LOWER(?2)",
-                    params![title, author], |r| r.get(0),
-                ).unwrap_or(0);
-                if count > 0 { return true; }
-            }
-            false
-        })();
+        let is_dup = matches_existing(&conn, "deleted_at IS NULL", &isbn13, &isbn, goodreads_id, &title, &author);
+        let in_trash = !is_dup
+            && matches_existing(&conn, "deleted_at IS NOT NULL", &isbn13, &isbn, goodreads_id, &title, &author);
 
+        let label = if author.is_empty() {
+            title.clone()
+        } else {
+            format!("{} by {}", title, author)
+        };
         if is_dup {
-            let label = if author.is_empty() {
-                title.clone()
-            } else {
-                format!("{} by {}", title, author)
-            };
             skipped.push(label);
             continue;
         }
 
+        if !rejected.is_empty() {
+            invalid_isbns.push(format!("{}: {}", title, rejected.join(", ")));
+        }
+
         let pages: Option<i64> = get("pages").parse().ok();
-        let book_type_raw = get("book_type");
-        let book_type = if book_type_raw.is_empty() { "traditional".to_string() } else { book_type_raw };
+        // "Format"/"Type" values (or failing that, the binding) are matched
+        // against configured types; anything unrecognised is the default type
+        let book_type = book_types::match_value(&types, &get("book_type"))
+            .or_else(|| book_types::match_value(&types, &get("binding")))
+            .unwrap_or_else(|| book_types::DEFAULT_TYPE.to_string());
+        let binding = Some(get("binding"))
+            .filter(|b| !b.is_empty())
+            .or_else(|| types.iter().find(|t| t.name == book_type).and_then(|t| t.default_binding.clone()));
         let cover_url = isbn13
             .as_ref()
             .or(isbn.as_ref())
@@ -230,8 +203,9 @@ pub fn import_csv(
         conn.execute(
             "INSERT INTO books (goodreads_id, title, author, author_sort, additional_authors, isbn, isbn13,
              my_rating, average_rating, publisher, binding, pages, edition_published, year_published,
-             date_read, year_read, date_added, exclusive_shelf, my_review, read_count, owned_copies, cover_url, book_type)
-             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21,?22,?23)",
+             date_read, year_read, date_added, exclusive_shelf, my_review, read_count, owned_copies, cover_url, book_type,
+             date_started)
+             VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12,?13,?14,?15,?16,?17,?18,?19,?20,?21,?22,?23,?24)",
             params![
                 get("goodreads_id").parse::<i64>().ok(),
                 title,
@@ -243,7 +217,7 @@ pub fn import_csv(
                 get("my_rating").parse::<i64>().unwrap_or(0),
                 get("average_rating").parse::<f64>().ok(),
                 get("publisher"),
-                get("binding"),
+                binding,
                 pages,
                 get("edition_published").parse::<i64>().ok(),
                 get("year_published").parse::<i64>().ok(),
@@ -258,7 +232,7 @@ pub fn import_csv(
                         Some(y)
                     } else {
                         let dr = get("date_read");
-                        extract_year(&dr)
+                        dates::parse_year(&dr)
                     }
                 },
                 {
@@ -285,6 +259,10 @@ pub fn import_csv(
                 get("owned_copies").parse::<i64>().unwrap_or(0),
                 cover_url,
                 book_type,
+                {
+                    let v = get("date_started");
+                    if v.is_empty() { None } else { Some(v) }
+                },
             ],
         )
         .map_err(|e| e.to_string())?;
@@ -318,8 +296,40 @@ pub fn import_csv(
             }
         }
 
+        placeholder::refresh(&conn, &state.data_dir, book_id)?;
+        history::record(&conn, book_id, "create", None, Some("Imported from CSV"))?;
         imported += 1;
+        if in_trash {
+            also_in_trash.push(label);
+        }
     }
 
-    Ok(ImportResult { imported, total, skipped })
+    Ok(ImportResult { imported, total, skipped, also_in_trash, invalid_isbns })
+}
+
+/// Whether a book matching `condition` has the same isbn13, isbn,
+/// goodreads_id or (case-insensitive) title and author.
+fn matches_existing(
+    conn: &Connection,
+    condition: &str,
+    isbn13: &Option<String>,
+    isbn: &Option<String>,
+    goodreads_id: Option<i64>,
+    title: &str,
+    author: &str,
+) -> bool {
+    let count = |clause: &str, params: &[&dyn rusqlite::ToSql]| -> i64 {
+        conn.query_row(
+            &format!("SELECT COUNT(*) FROM books WHERE {} AND {}", condition, clause),
+            params,
+            |r| r.get(0),
+        )
+        .unwrap_or(0)
+    };
+    isbn13.as_ref().is_some_and(|id| count("isbn13 = ?1", &[id]) > 0)
+        || isbn.as_ref().is_some_and(|id| count("isbn = ?1", &[id]) > 0)
+        || goodreads_id.is_some_and(|id| count("goodreads_id = ?1", &[&id]) > 0)
+        || (!title.is_empty()
+            && !author.is_empty()
+            && count("LOWER(title) = LOWER(?1) AND LOWER(author) = LOWER(?2)", &[&title, &author]) > 0)
 }
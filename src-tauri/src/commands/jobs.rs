@@ -0,0 +1,13 @@
+use crate::jobs::JobRegistry;
+use tauri::State;
+
+/// Request cancellation of a background job. Returns false if it wasn't running.
+#[tauri::command]
+pub fn cancel_job(jobs: State<JobRegistry>, job: String) -> bool {
+    jobs.cancel(&job)
+}
+
+#[tauri::command]
+pub fn get_running_jobs(jobs: State<JobRegistry>) -> Vec<String> {
+    jobs.running()
+}
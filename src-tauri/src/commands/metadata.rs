@@ -1,25 +1,359 @@
-use crate::models::OpenLibraryBookData;
+use crate::book_types::{self, DEFAULT_TYPE};
+use crate::dates;
+use crate::db::DbState;
+use crate::isbn;
+use crate::models::*;
+use crate::providers;
+use reqwest::Client;
 use std::collections::HashMap;
+use tauri::State;
 
+/// Look up an ISBN with every configured provider and merge the results into
+/// a ready-to-save draft. For each field the highest-priority provider that
+/// has a value wins; `sources` records which one that was.
 #[tauri::command]
-pub async fn lookup_isbn(isbn: String) -> Result<Option<OpenLibraryBookData>, String> {
-    let url = format!(
-        "https://openlibrary.org/api/books?bibkeys=ISBN:{}&format=json&jscmd=data",
-        isbn
-    );
-
-    let client = reqwest::Client::new();
-    let resp = client
-        .get(&url)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let data: HashMap<String, OpenLibraryBookData> = resp
-        .json()
-        .await
-        .map_err(|e| e.to_string())?;
-
-    let key = format!("ISBN:{}", isbn);
-    Ok(data.get(&key).cloned())
+pub async fn lookup_isbn(
+    state: State<'_, DbState>,
+    client: State<'_, Client>,
+    isbn: String,
+) -> Result<Option<IsbnLookup>, String> {
+    let (providers, types) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        (providers::load(&conn), book_types::list(&conn)?)
+    };
+    let isbn = isbn::parse(&isbn)?.ok_or_else(|| "ISBN is required".to_string())?;
+    let records = providers::lookup_all(&state, &providers, &client, &isbn).await?;
+    Ok(merge_records(&isbn, &records, &types))
+}
+
+/// Forget every cached provider response so the next lookups hit the network.
+/// Returns the number of entries removed.
+#[tauri::command]
+pub fn clear_lookup_cache(state: State<DbState>) -> Result<usize, String> {
+    providers::cache::clear(&state)
+}
+
+/// Provider configs in priority order.
+#[tauri::command]
+pub fn get_metadata_providers(state: State<DbState>) -> Result<Vec<ProviderConfig>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    Ok(providers::load_configs(&conn))
+}
+
+/// Replace the provider list; its order becomes the lookup priority.
+#[tauri::command]
+pub fn set_metadata_providers(state: State<DbState>, configs: Vec<ProviderConfig>) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    providers::save_configs(&conn, &configs)
+}
+
+/// Merge provider records (already in priority order) into a `NewBook` draft.
+/// `types` are the configured book types the suggested type is chosen from.
+pub(crate) fn merge_records(isbn: &str, records: &[BookMetadata], types: &[BookType]) -> Option<IsbnLookup> {
+    if records.is_empty() {
+        return None;
+    }
+    let mut sources: HashMap<String, String> = HashMap::new();
+
+    let title = pick(records, &mut sources, "title", |r| non_empty(&r.title));
+    let authors = pick(records, &mut sources, "author", |r| {
+        Some(r.authors.clone()).filter(|a| !a.is_empty())
+    })
+    .unwrap_or_default();
+    let author = authors.first().cloned();
+    let additional_authors = Some(authors.iter().skip(1).cloned().collect::<Vec<_>>().join(", "))
+        .filter(|a| !a.is_empty());
+    if let Some(src) = sources.get("author").cloned() {
+        sources.insert("authorSort".to_string(), src.clone());
+        if additional_authors.is_some() {
+            sources.insert("additionalAuthors".to_string(), src);
+        }
+    }
+
+    let publisher = pick(records, &mut sources, "publisher", |r| r.publishers.first().cloned());
+    let pages = pick(records, &mut sources, "pages", |r| r.pages.filter(|p| *p > 0));
+    let year_published = pick(records, &mut sources, "yearPublished", |r| {
+        r.publish_date.as_deref().and_then(dates::parse_year)
+    });
+    let binding = pick(records, &mut sources, "binding", |r| non_empty(&r.binding));
+    let subjects = pick(records, &mut sources, "subjects", |r| {
+        Some(r.subjects.clone()).filter(|s| !s.is_empty())
+    });
+    let description = pick(records, &mut sources, "description", |r| non_empty(&r.description));
+    let language = pick(records, &mut sources, "language", |r| non_empty(&r.language));
+    let series = pick(records, &mut sources, "series", |r| non_empty(&r.series));
+    let open_library_key = pick(records, &mut sources, "openLibraryKey", |r| non_empty(&r.open_library_key));
+    let cover_url = pick(records, &mut sources, "coverUrl", |r| non_empty(&r.cover_url));
+
+    // Fall back to the ISBN that was looked up for whichever form it is
+    let digits = isbn::normalize(isbn);
+    let mut isbn10 = pick(records, &mut sources, "isbn", |r| {
+        r.isbn10.as_deref().map(isbn::normalize).filter(|v| isbn::is_valid_isbn10(v))
+    });
+    let mut isbn13 = pick(records, &mut sources, "isbn13", |r| {
+        r.isbn13.as_deref().map(isbn::normalize).filter(|v| isbn::is_valid_isbn13(v))
+    });
+    if isbn10.is_none() && isbn::is_valid_isbn10(&digits) {
+        isbn10 = Some(digits.clone());
+        sources.insert("isbn".to_string(), "input".to_string());
+    }
+    if isbn13.is_none() && isbn::is_valid_isbn13(&digits) {
+        isbn13 = Some(digits);
+        sources.insert("isbn13".to_string(), "input".to_string());
+    }
+    // Then derive whichever half is still missing from the other
+    if isbn13.is_none() {
+        isbn13 = isbn10.as_deref().and_then(isbn::isbn10_to_13);
+        if isbn13.is_some() {
+            sources.insert("isbn13".to_string(), "derived".to_string());
+        }
+    }
+    if isbn10.is_none() {
+        isbn10 = isbn13.as_deref().and_then(isbn::isbn13_to_10);
+        if isbn10.is_some() {
+            sources.insert("isbn".to_string(), "derived".to_string());
+        }
+    }
+
+    let book_type = suggest_book_type(records, types);
+    sources.insert("bookType".to_string(), "suggested".to_string());
+
+    let draft = NewBook {
+        goodreads_id: None,
+        title: title.unwrap_or_default(),
+        author_sort: author.as_deref().map(author_sort),
+        author: author.unwrap_or_default(),
+        additional_authors,
+        isbn: isbn10,
+        isbn13,
+        my_rating: None,
+        average_rating: None,
+        publisher,
+        binding,
+        pages,
+        edition_published: None,
+        year_published,
+        date_read: None,
+        year_read: None,
+        date_added: None,
+        exclusive_shelf: None,
+        my_review: None,
+        read_count: None,
+        owned_copies: None,
+        cover_url,
+        shelf_names: None,
+        book_type: Some(book_type),
+        open_library_key,
+        subjects,
+        description,
+        language,
+        series,
+        date_started: None,
+    };
+
+    Some(IsbnLookup { draft, sources })
+}
+
+/// First value any record has for a field, noting which provider it came from.
+fn pick<T>(
+    records: &[BookMetadata],
+    sources: &mut HashMap<String, String>,
+    field: &str,
+    get: impl Fn(&BookMetadata) -> Option<T>,
+) -> Option<T> {
+    let (source, value) = records.iter().find_map(|r| get(r).map(|v| (&r.source, v)))?;
+    sources.insert(field.to_string(), source.clone());
+    Some(value)
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.as_ref().map(|v| v.trim()).filter(|v| !v.is_empty()).map(str::to_string)
+}
+
+/// Lower-case surname particles kept with the surname ("Le Guin", "van Gogh")
+const SURNAME_PARTICLES: &[&str] = &[
+    "al", "bin", "da", "de", "del", "della", "der", "des", "di", "du", "ibn", "la", "le", "st.", "ten", "ter", "van",
+    "von",
+];
+/// Generational suffixes moved after the given names
+const NAME_SUFFIXES: &[&str] = &["jr", "jr.", "sr", "sr.", "ii", "iii", "iv"];
+
+/// "Ursula K. Le Guin" → "Le Guin, Ursula K.", "Martin Luther King Jr." →
+/// "King, Martin Luther, Jr.". Names already in "Last, First" form are left alone.
+fn author_sort(name: &str) -> String {
+    let name = name.trim();
+    if name.contains(',') {
+        return name.to_string();
+    }
+    let mut words: Vec<&str> = name.split_whitespace().collect();
+    let suffix = match words.last() {
+        Some(last) if words.len() > 2 && NAME_SUFFIXES.contains(&last.to_lowercase().as_str()) => words.pop(),
+        _ => None,
+    };
+    if words.len() < 2 {
+        return name.to_string();
+    }
+    // At least one given name always stays in front
+    let mut split = words.len() - 1;
+    while split > 1 && SURNAME_PARTICLES.contains(&words[split - 1].to_lowercase().as_str()) {
+        split -= 1;
+    }
+    let sorted = format!("{}, {}", words[split..].join(" "), words[..split].join(" "));
+    match suffix {
+        Some(suffix) => format!("{}, {}", sorted, suffix),
+        None => sorted,
+    }
+}
+
+/// Guess the book type by matching subjects and bindings across all records
+/// against the configured types (see `book_types::match_value`). Subjects
+/// are also tried part by part, so "Comics & Graphic Novels / General"
+/// matches a "comics" alias. Any other type wins over the default one.
+fn suggest_book_type(records: &[BookMetadata], types: &[BookType]) -> String {
+    let matched: Vec<String> = records
+        .iter()
+        .flat_map(|r| r.subjects.iter().chain(r.binding.iter()))
+        .flat_map(|value| std::iter::once(value.as_str()).chain(value.split(['/', '&', ',', ';'])))
+        .filter_map(|value| book_types::match_value(types, value))
+        .collect();
+    matched
+        .iter()
+        .find(|name| *name != DEFAULT_TYPE)
+        .or(matched.first())
+        .cloned()
+        .unwrap_or_else(|| DEFAULT_TYPE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(source: &str) -> BookMetadata {
+        BookMetadata { source: source.to_string(), ..Default::default() }
+    }
+
+    fn book_type(name: &str, aliases: &[&str]) -> BookType {
+        BookType {
+            name: name.to_string(),
+            label: name.replace('_', " "),
+            beq_baseline: None,
+            default_binding: None,
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+            cover_style: None,
+            book_count: 0,
+        }
+    }
+
+    fn types() -> Vec<BookType> {
+        vec![
+            book_type("traditional", &["novel", "paperback", "hardcover"]),
+            book_type("graphic_novel", &["graphic novels", "comics", "manga"]),
+            book_type("audiobook", &["audio cd"]),
+        ]
+    }
+
+    #[test]
+    fn merge_prefers_earlier_providers_per_field() {
+        let records = vec![
+            BookMetadata {
+                title: Some("Dune".to_string()),
+                authors: vec!["Frank Herbert".to_string(), "Brian Herbert".to_string()],
+                isbn13: Some("978-0-441-01359-3".to_string()),
+                description: Some("  ".to_string()),
+                ..record("googleBooks")
+            },
+            BookMetadata {
+                title: Some("Dune (Deluxe Edition)".to_string()),
+                authors: vec!["F. Herbert".to_string()],
+                publishers: vec!["Ace Books".to_string(), "Chilton".to_string()],
+                pages: Some(617),
+                publish_date: Some("c1965".to_string()),
+                binding: Some("Paperback".to_string()),
+                isbn10: Some("0441013597".to_string()),
+                description: Some("Set on Arrakis.".to_string()),
+                ..record("openLibrary")
+            },
+        ];
+        let IsbnLookup { draft, sources } = merge_records("9780441013593", &records, &types()).unwrap();
+
+        assert_eq!(draft.title, "Dune");
+        assert_eq!(draft.author, "Frank Herbert");
+        assert_eq!(draft.author_sort.as_deref(), Some("Herbert, Frank"));
+        assert_eq!(draft.additional_authors.as_deref(), Some("Brian Herbert"));
+        assert_eq!(draft.publisher.as_deref(), Some("Ace Books"));
+        assert_eq!(draft.pages, Some(617));
+        assert_eq!(draft.year_published, Some(1965));
+        assert_eq!(draft.binding.as_deref(), Some("Paperback"));
+        // Blank values don't count, so the next provider's description wins
+        assert_eq!(draft.description.as_deref(), Some("Set on Arrakis."));
+        assert_eq!(draft.isbn.as_deref(), Some("0441013597"));
+        assert_eq!(draft.isbn13.as_deref(), Some("9780441013593"));
+
+        let source = |field: &str| sources.get(field).map(String::as_str);
+        assert_eq!(source("title"), Some("googleBooks"));
+        assert_eq!(source("author"), Some("googleBooks"));
+        assert_eq!(source("authorSort"), Some("googleBooks"));
+        assert_eq!(source("additionalAuthors"), Some("googleBooks"));
+        assert_eq!(source("isbn13"), Some("googleBooks"));
+        assert_eq!(source("publisher"), Some("openLibrary"));
+        assert_eq!(source("pages"), Some("openLibrary"));
+        assert_eq!(source("yearPublished"), Some("openLibrary"));
+        assert_eq!(source("description"), Some("openLibrary"));
+        assert_eq!(source("isbn"), Some("openLibrary"));
+        assert_eq!(source("series"), None);
+        assert_eq!(source("bookType"), Some("suggested"));
+    }
+
+    #[test]
+    fn suggests_configured_types_by_alias() {
+        let with = |subjects: &[&str], binding: Option<&str>| BookMetadata {
+            subjects: subjects.iter().map(|s| s.to_string()).collect(),
+            binding: binding.map(str::to_string),
+            ..record("googleBooks")
+        };
+        let cases = [
+            (with(&["Comics & Graphic Novels / General"], Some("Paperback")), "graphic_novel"),
+            (with(&["Fiction"], Some("Audio CD")), "audiobook"),
+            (with(&["Manga"], None), "graphic_novel"),
+            (with(&["Science fiction"], Some("Hardcover")), "traditional"),
+            // Nothing configured matches
+            (with(&["Comic strips"], None), "traditional"),
+        ];
+        for (record, expected) in cases {
+            assert_eq!(suggest_book_type(std::slice::from_ref(&record), &types()), expected, "{:?}", record.subjects);
+        }
+    }
+
+    #[test]
+    fn author_sort_keeps_particles_with_the_surname() {
+        let cases = [
+            ("Frank Herbert", "Herbert, Frank"),
+            ("Ursula K. Le Guin", "Le Guin, Ursula K."),
+            ("Vincent van Gogh", "van Gogh, Vincent"),
+            ("Martin Luther King Jr.", "King, Martin Luther, Jr."),
+            ("John le Carré", "le Carré, John"),
+            ("Herbert, Frank", "Herbert, Frank"),
+            ("Plato", "Plato"),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(author_sort(name), expected, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn merge_falls_back_to_the_looked_up_isbn() {
+        let records = vec![BookMetadata {
+            title: Some("Dune".to_string()),
+            // Invalid checksums are ignored
+            isbn10: Some("0441013598".to_string()),
+            ..record("openLibrary")
+        }];
+        let IsbnLookup { draft, sources } = merge_records("0441013597", &records, &types()).unwrap();
+
+        assert_eq!(draft.isbn.as_deref(), Some("0441013597"));
+        assert_eq!(draft.isbn13.as_deref(), Some("9780441013593"));
+        assert_eq!(sources.get("isbn").map(String::as_str), Some("input"));
+        assert_eq!(sources.get("isbn13").map(String::as_str), Some("derived"));
+        assert!(merge_records("0441013597", &[], &types()).is_none());
+    }
 }
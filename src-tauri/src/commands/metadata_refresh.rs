@@ -0,0 +1,332 @@
+use crate::book_types;
+use crate::commands::books::apply_patch;
+use crate::commands::metadata::merge_records;
+use crate::db::DbState;
+use crate::history;
+use crate::jobs::JobRegistry;
+use crate::models::*;
+use crate::providers::{self, MetadataProvider};
+use reqwest::Client;
+use rusqlite::{params, Connection};
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+pub const JOB_NAME: &str = "metadata-refresh";
+const PROGRESS_EVENT: &str = "metadata-refresh-progress";
+
+/// Fields the refresh may fill: (camelCase name as in `NewBook`, column)
+const REFRESHABLE: &[(&str, &str)] = &[
+    ("publisher", "publisher"),
+    ("pages", "pages"),
+    ("yearPublished", "year_published"),
+    ("binding", "binding"),
+    ("description", "description"),
+    ("language", "language"),
+    ("series", "series"),
+    ("subjects", "subjects"),
+    ("coverUrl", "cover_url"),
+    ("openLibraryKey", "open_library_key"),
+    ("isbn", "isbn"),
+    ("isbn13", "isbn13"),
+];
+
+/// History notes written by metadata tooling rather than by the user
+const SYSTEM_NOTES: &[&str] = &["Metadata refresh", "Metadata review"];
+
+/// Start a background job that looks up every book with an ISBN that is
+/// missing any of `fields` (all refreshable fields when empty) and queues
+/// proposed values for review. Existing values are never touched, and fields
+/// the user deliberately cleared are skipped. Returns the number of books queued.
+///
+/// Emits `metadata-refresh-progress` after each book; cancel with
+/// `cancel_job("metadata-refresh")`.
+#[tauri::command]
+pub fn start_metadata_refresh(
+    app: AppHandle,
+    state: State<DbState>,
+    jobs: State<JobRegistry>,
+    fields: Vec<String>,
+) -> Result<i64, String> {
+    let targets: Vec<(&'static str, &'static str)> = if fields.is_empty() {
+        REFRESHABLE.to_vec()
+    } else {
+        fields
+            .iter()
+            .map(|f| {
+                REFRESHABLE
+                    .iter()
+                    .find(|(name, _)| name == f)
+                    .copied()
+                    .ok_or_else(|| format!("Field cannot be refreshed: {}", f))
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    let (providers, types, candidates) = {
+        let conn = state.conn.lock().map_err(|e| e.to_string())?;
+        let missing: Vec<String> = targets
+            .iter()
+            .map(|(_, col)| format!("({0} IS NULL OR {0} = '' OR {0} = 0)", col))
+            .collect();
+        let sql = format!(
+            "SELECT id, title, coalesce(nullif(isbn13, ''), isbn) FROM books
+             WHERE deleted_at IS NULL AND coalesce(nullif(isbn13, ''), isbn, '') != '' AND ({})
+             ORDER BY id",
+            missing.join(" OR ")
+        );
+        let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+        let rows: Vec<(i64, String, String)> = stmt
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        (providers::load(&conn), book_types::list(&conn)?, rows)
+    };
+
+    let cancel = jobs.start(JOB_NAME)?;
+    let total = candidates.len() as i64;
+    tauri::async_runtime::spawn(run(app, cancel, providers, types, candidates, targets));
+    Ok(total)
+}
+
+async fn run(
+    app: AppHandle,
+    cancel: Arc<AtomicBool>,
+    providers: Vec<Box<dyn MetadataProvider>>,
+    types: Vec<BookType>,
+    candidates: Vec<(i64, String, String)>,
+    targets: Vec<(&'static str, &'static str)>,
+) {
+    let total = candidates.len() as i64;
+    let mut processed = 0;
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut cancelled = false;
+    let state = app.state::<DbState>();
+    let client = app.state::<Client>();
+
+    for (id, title, isbn) in candidates {
+        if cancel.load(Ordering::Relaxed) {
+            cancelled = true;
+            break;
+        }
+
+        let outcome = match providers::lookup_all(&state, &providers, &client, &isbn).await {
+            Ok(records) => match merge_records(&isbn, &records, &types) {
+                Some(found) => match state.conn.lock() {
+                    Ok(conn) => queue_proposals(&conn, id, &found, &targets).map(|_| true),
+                    Err(e) => Err(e.to_string()),
+                },
+                None => Ok(false),
+            },
+            Err(e) => Err(e),
+        };
+        let error = match outcome {
+            Ok(stored) => {
+                succeeded += stored as i64;
+                None
+            }
+            Err(e) => {
+                failed += 1;
+                Some(e)
+            }
+        };
+
+        processed += 1;
+        let _ = app.emit(PROGRESS_EVENT, JobProgress {
+            job: JOB_NAME.to_string(),
+            processed,
+            total,
+            book_id: Some(id),
+            title: Some(title),
+            succeeded,
+            failed,
+            error,
+            done: false,
+            cancelled: false,
+        });
+    }
+
+    app.state::<JobRegistry>().finish(JOB_NAME);
+    let _ = app.emit(PROGRESS_EVENT, JobProgress {
+        job: JOB_NAME.to_string(),
+        processed,
+        total,
+        book_id: None,
+        title: None,
+        succeeded,
+        failed,
+        error: None,
+        done: true,
+        cancelled,
+    });
+}
+
+/// Store a proposal for every target field that is empty on the book and
+/// has a value in the lookup draft.
+fn queue_proposals(
+    conn: &Connection,
+    book_id: i64,
+    found: &IsbnLookup,
+    targets: &[(&str, &str)],
+) -> Result<(), String> {
+    let Some(current) = history::snapshot(conn, book_id)? else {
+        return Ok(());
+    };
+    let timeline = history::timeline(conn, book_id)?;
+    let draft = serde_json::to_value(&found.draft).map_err(|e| e.to_string())?;
+
+    for (field, column) in targets {
+        let Some(proposed) = draft.get(*field).filter(|v| !is_empty(v)) else {
+            continue;
+        };
+        if !current.get(*column).is_none_or(is_empty) || user_cleared(&timeline, column) {
+            continue;
+        }
+        let source = found.sources.get(*field).cloned().unwrap_or_default();
+        conn.execute(
+            "INSERT INTO metadata_proposals (book_id, field, proposed, source) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(book_id, field) DO UPDATE SET
+                proposed = excluded.proposed, source = excluded.source, created_at = datetime('now')",
+            params![book_id, field, proposed.to_string(), source],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Whether the most recent change to `column` was the user clearing it.
+fn user_cleared(timeline: &[HistoryEntry], column: &str) -> bool {
+    // Timeline is newest first
+    timeline
+        .iter()
+        .find_map(|entry| {
+            let change = entry.changes.iter().find(|c| c.field == column)?;
+            let by_user = entry.action != "create"
+                && !entry.note.as_deref().is_some_and(|n| SYSTEM_NOTES.contains(&n));
+            Some(by_user && is_empty(&change.new))
+        })
+        .unwrap_or(false)
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.trim().is_empty() || s == "[]",
+        Value::Array(a) => a.is_empty(),
+        Value::Number(n) => n.as_f64() == Some(0.0),
+        _ => false,
+    }
+}
+
+/// Pending proposals, grouped by book in title order.
+#[tauri::command]
+pub fn list_metadata_proposals(state: State<DbState>) -> Result<Vec<MetadataProposal>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT p.id, p.book_id, b.title, b.author, p.field, p.proposed, p.source, p.created_at
+             FROM metadata_proposals p JOIN books b ON b.id = p.book_id
+             WHERE b.deleted_at IS NULL
+             ORDER BY b.title, b.id, p.field",
+        )
+        .map_err(|e| e.to_string())?;
+    let proposals: Vec<MetadataProposal> = stmt
+        .query_map([], |row| {
+            let proposed: String = row.get(5)?;
+            Ok(MetadataProposal {
+                id: row.get(0)?,
+                book_id: row.get(1)?,
+                title: row.get(2)?,
+                author: row.get(3)?,
+                field: row.get(4)?,
+                proposed: serde_json::from_str(&proposed).unwrap_or(Value::Null),
+                source: row.get(6)?,
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(proposals)
+}
+
+/// Apply a book's pending proposals (only `fields`, if given). Fields that
+/// were filled in since the proposal was made are left as they are.
+/// Returns the fields that were written.
+#[tauri::command]
+pub fn accept_metadata_proposals(
+    state: State<DbState>,
+    book_id: i64,
+    fields: Option<Vec<String>>,
+) -> Result<Vec<String>, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let pending = pending_for(&tx, book_id, fields.as_deref())?;
+    let current = history::snapshot(&tx, book_id)?.ok_or_else(|| "Book not found".to_string())?;
+
+    let mut patch_json = serde_json::Map::new();
+    for (field, proposed) in &pending {
+        let Some((_, column)) = REFRESHABLE.iter().find(|(name, _)| name == field) else {
+            continue;
+        };
+        if current.get(*column).is_none_or(is_empty) {
+            patch_json.insert(field.clone(), proposed.clone());
+        }
+    }
+    let applied: Vec<String> = patch_json.keys().cloned().collect();
+
+    if !applied.is_empty() {
+        let patch: BookPatch = serde_json::from_value(Value::Object(patch_json)).map_err(|e| e.to_string())?;
+        apply_patch(&tx, book_id, &patch)?;
+        history::record(&tx, book_id, "update", Some(&current), Some("Metadata review"))?;
+    }
+    delete_pending(&tx, book_id, &pending)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(applied)
+}
+
+/// Discard a book's pending proposals (only `fields`, if given).
+#[tauri::command]
+pub fn reject_metadata_proposals(
+    state: State<DbState>,
+    book_id: i64,
+    fields: Option<Vec<String>>,
+) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let pending = pending_for(&conn, book_id, fields.as_deref())?;
+    delete_pending(&conn, book_id, &pending)
+}
+
+fn pending_for(
+    conn: &Connection,
+    book_id: i64,
+    fields: Option<&[String]>,
+) -> Result<Vec<(String, Value)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT field, proposed FROM metadata_proposals WHERE book_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let pending: Vec<(String, Value)> = stmt
+        .query_map(params![book_id], |r| Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .filter(|(field, _)| fields.is_none_or(|f| f.contains(field)))
+        .map(|(field, json)| (field, serde_json::from_str(&json).unwrap_or(Value::Null)))
+        .collect();
+    Ok(pending)
+}
+
+fn delete_pending(conn: &Connection, book_id: i64, pending: &[(String, Value)]) -> Result<(), String> {
+    for (field, _) in pending {
+        conn.execute(
+            "DELETE FROM metadata_proposals WHERE book_id = ?1 AND field = ?2",
+            params![book_id, field],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
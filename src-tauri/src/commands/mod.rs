@@ -1,6 +1,16 @@
+pub mod backups;
+pub mod book_types;
 pub mod books;
+pub mod bulk;
 pub mod covers;
+pub mod goals;
+pub mod history;
 pub mod import;
+pub mod jobs;
 pub mod metadata;
+pub mod metadata_refresh;
+pub mod settings;
 pub mod shelves;
 pub mod stats;
+pub mod trash;
+pub mod year_review;
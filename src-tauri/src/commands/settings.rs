@@ -0,0 +1,77 @@
+use crate::beq::{self, Mode};
+use crate::db::{self, DbState};
+use crate::models::BeqConfig;
+use std::collections::HashMap;
+use tauri::State;
+
+#[tauri::command]
+pub fn get_settings(state: State<DbState>) -> Result<HashMap<String, String>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare("SELECT key, value FROM settings")
+        .map_err(|e| e.to_string())?;
+    let settings: HashMap<String, String> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(settings)
+}
+
+/// Store a plain setting. BEq settings are rejected: they go through
+/// `set_beq_config`, which validates them and keeps stored BEq values fresh.
+#[tauri::command]
+pub fn update_setting(state: State<DbState>, key: String, value: String) -> Result<(), String> {
+    if key.starts_with(beq::SETTINGS_PREFIX) {
+        return Err(format!("{} is a BEq setting; use set_beq_config", key));
+    }
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    db::set_setting(&conn, &key, &value)
+}
+
+fn beq_config(conn: &rusqlite::Connection) -> Result<BeqConfig, String> {
+    let (snapshot, snapshot_at) = beq::snapshot(conn);
+    Ok(BeqConfig {
+        mode: beq::mode(conn).as_str().to_string(),
+        baselines: beq::baselines(conn),
+        snapshot,
+        snapshot_at,
+        live_averages: beq::live_averages(conn)?,
+        default_baseline: beq::DEFAULT_BASELINE,
+    })
+}
+
+#[tauri::command]
+pub fn get_beq_config(state: State<DbState>) -> Result<BeqConfig, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq_config(&conn)
+}
+
+/// Choose how BEq is computed: `live`, `snapshot` or `fixed`. Switching to
+/// snapshot mode freezes the current averages unless a snapshot exists;
+/// `baselines` replaces the per-type page counts used in fixed mode.
+#[tauri::command]
+pub fn set_beq_config(
+    state: State<DbState>,
+    mode: String,
+    baselines: Option<HashMap<String, f64>>,
+) -> Result<BeqConfig, String> {
+    let mode = Mode::parse(&mode)?;
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    if let Some(ref baselines) = baselines {
+        beq::set_baselines(&conn, baselines)?;
+    }
+    if mode == Mode::Snapshot && beq::snapshot(&conn).1.is_none() {
+        beq::take_snapshot(&conn)?;
+    }
+    beq::set_mode(&conn, mode)?;
+    beq_config(&conn)
+}
+
+/// Re-freeze the snapshot averages from the current library.
+#[tauri::command]
+pub fn snapshot_beq_averages(state: State<DbState>) -> Result<BeqConfig, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::take_snapshot(&conn)?;
+    beq_config(&conn)
+}
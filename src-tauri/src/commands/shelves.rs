@@ -1,6 +1,7 @@
 use crate::db::DbState;
+use crate::history;
 use crate::models::*;
-use rusqlite::params;
+use rusqlite::{params, Connection};
 use tauri::State;
 
 #[tauri::command]
@@ -8,9 +9,10 @@ pub fn get_shelves(state: State<DbState>) -> Result<Vec<ShelfWithCount>, String>
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
     let mut stmt = conn
         .prepare(
-            "SELECT s.id, s.name, COUNT(bs.book_id) as count
+            "SELECT s.id, s.name, COUNT(b.id) as count
              FROM shelves s
              LEFT JOIN book_shelves bs ON s.id = bs.shelf_id
+             LEFT JOIN books b ON b.id = bs.book_id AND b.deleted_at IS NULL
              GROUP BY s.id
              ORDER BY s.name ASC",
         )
@@ -47,15 +49,44 @@ pub fn create_shelf(state: State<DbState>, name: String) -> Result<ShelfWithCoun
 #[tauri::command]
 pub fn rename_shelf(state: State<DbState>, id: i64, name: String) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let before = member_snapshots(&conn, id)?;
     conn.execute("UPDATE shelves SET name = ?1 WHERE id = ?2", params![name, id])
         .map_err(|e| e.to_string())?;
-    Ok(())
+    record_members(&conn, &before, "shelves")
 }
 
 #[tauri::command]
 pub fn delete_shelf(state: State<DbState>, id: i64) -> Result<(), String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let before = member_snapshots(&conn, id)?;
     conn.execute("DELETE FROM shelves WHERE id = ?1", params![id])
         .map_err(|e| e.to_string())?;
+    record_members(&conn, &before, "shelves")
+}
+
+/// History snapshots of every book on a shelf, taken before the shelf changes.
+fn member_snapshots(conn: &Connection, shelf_id: i64) -> Result<Vec<(i64, history::Snapshot)>, String> {
+    let mut stmt = conn
+        .prepare("SELECT book_id FROM book_shelves WHERE shelf_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let book_ids: Vec<i64> = stmt
+        .query_map(params![shelf_id], |r| r.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut snapshots = Vec::with_capacity(book_ids.len());
+    for book_id in book_ids {
+        if let Some(snap) = history::snapshot(conn, book_id)? {
+            snapshots.push((book_id, snap));
+        }
+    }
+    Ok(snapshots)
+}
+
+fn record_members(conn: &Connection, before: &[(i64, history::Snapshot)], action: &str) -> Result<(), String> {
+    for (book_id, snap) in before {
+        history::record(conn, *book_id, action, Some(snap), None)?;
+    }
     Ok(())
 }
@@ -1,87 +1,48 @@
+use crate::beq;
+use crate::book_types;
 use crate::db::DbState;
 use crate::models::*;
-use rusqlite::params;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
 use tauri::State;
 
-/// SQL for computing a single book's BEq using per-type average
-const BEQ_EXPR: &str = "CASE WHEN books.pages > 0 THEN books.pages * 1.0 / NULLIF((SELECT AVG(b2.pages) FROM books b2 WHERE b2.book_type = books.book_type AND b2.exclusive_shelf = 'read' AND b2.pages > 0), 0) ELSE 0 END";
-
 #[tauri::command]
 pub fn get_stats(state: State<DbState>) -> Result<Stats, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
 
     let total_books: i64 = conn
-        .query_row("SELECT count(*) FROM books", [], |r| r.get(0))
+        .query_row("SELECT count(*) FROM books WHERE deleted_at IS NULL", [], |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
     let total_read: i64 = conn
-        .query_row("SELECT count(*) FROM books WHERE exclusive_shelf = 'read'", [], |r| r.get(0))
+        .query_row("SELECT count(*) FROM books WHERE deleted_at IS NULL AND exclusive_shelf = 'read'", [], |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
     let total_beq: f64 = conn
         .query_row(
-            &format!("SELECT coalesce(sum({}), 0) FROM books WHERE exclusive_shelf = 'read'", BEQ_EXPR),
-            [], |r| r.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    let total_beq_traditional: f64 = conn
-        .query_row(
-            &format!("SELECT coalesce(sum({}), 0) FROM books WHERE exclusive_shelf = 'read' AND book_type = 'traditional'", BEQ_EXPR),
-            [], |r| r.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    let total_beq_graphic_novel: f64 = conn
-        .query_row(
-            &format!("SELECT coalesce(sum({}), 0) FROM books WHERE exclusive_shelf = 'read' AND book_type = 'graphic_novel'", BEQ_EXPR),
-            [], |r| r.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    let avg_pages_traditional: f64 = conn
-        .query_row(
-            "SELECT coalesce(avg(pages), 0) FROM books WHERE book_type = 'traditional' AND exclusive_shelf = 'read' AND pages > 0",
-            [], |r| r.get(0),
-        )
-        .map_err(|e| e.to_string())?;
-
-    let avg_pages_graphic_novel: f64 = conn
-        .query_row(
-            "SELECT coalesce(avg(pages), 0) FROM books WHERE book_type = 'graphic_novel' AND exclusive_shelf = 'read' AND pages > 0",
+            "SELECT coalesce(sum(beq), 0) FROM books WHERE deleted_at IS NULL AND exclusive_shelf = 'read'",
             [], |r| r.get(0),
         )
         .map_err(|e| e.to_string())?;
 
     let avg_rating: f64 = conn
-        .query_row("SELECT coalesce(avg(my_rating), 0) FROM books WHERE exclusive_shelf = 'read' AND my_rating > 0", [], |r| r.get(0))
+        .query_row("SELECT coalesce(avg(my_rating), 0) FROM books WHERE deleted_at IS NULL AND exclusive_shelf = 'read' AND my_rating > 0", [], |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
-    let current_year = {
-        let secs = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        // Approximate current year
-        (1970 + secs / 31_557_600) as i64
-    };
+    let (current_year, _, _) = today(&conn)?;
+    let this_year = read_in(current_year, None);
 
     let books_this_year: i64 = conn
-        .query_row("SELECT count(*) FROM books WHERE year_read = ?1", params![current_year], |r| r.get(0))
+        .query_row(&format!("SELECT count(*) FROM books WHERE {}", this_year), [], |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
     let beq_this_year: f64 = conn
-        .query_row(
-            &format!("SELECT coalesce(sum({}), 0) FROM books WHERE year_read = ?1", BEQ_EXPR),
-            params![current_year], |r| r.get(0),
-        )
+        .query_row(&format!("SELECT coalesce(sum(beq), 0) FROM books WHERE {}", this_year), [], |r| r.get(0))
         .map_err(|e| e.to_string())?;
 
     let mut stmt = conn
-        .prepare(&format!(
-            "SELECT year_read, count(*), coalesce(sum({}), 0) FROM books WHERE year_read IS NOT NULL GROUP BY year_read ORDER BY year_read DESC",
-            BEQ_EXPR
-        ))
+        .prepare("SELECT year_read, count(*), coalesce(sum(beq), 0) FROM books WHERE deleted_at IS NULL AND year_read IS NOT NULL GROUP BY year_read ORDER BY year_read DESC")
         .map_err(|e| e.to_string())?;
     let by_year: Vec<YearStat> = stmt
         .query_map([], |row| {
@@ -96,7 +57,7 @@ pub fn get_stats(state: State<DbState>) -> Result<Stats, String> {
         .collect();
 
     let mut stmt = conn
-        .prepare("SELECT my_rating, count(*) FROM books WHERE my_rating > 0 GROUP BY my_rating ORDER BY my_rating ASC")
+        .prepare("SELECT my_rating, count(*) FROM books WHERE deleted_at IS NULL AND my_rating > 0 GROUP BY my_rating ORDER BY my_rating ASC")
         .map_err(|e| e.to_string())?;
     let rating_dist: Vec<RatingCount> = stmt
         .query_map([], |row| {
@@ -109,29 +70,706 @@ pub fn get_stats(state: State<DbState>) -> Result<Stats, String> {
         .filter_map(|r| r.ok())
         .collect();
 
-    let round2 = |v: f64| (v * 100.0).round() / 100.0;
+    // Per-type averages are kept in `book_type_stats` by triggers
+    let averages = beq::live_averages(&conn)?;
+    let divisors = beq::divisors(&conn)?;
+    let mode = beq::mode(&conn);
+    let mut stmt = conn
+        .prepare(
+            "SELECT t.name, t.label, count(b.id), coalesce(sum(b.beq), 0)
+             FROM book_types t
+             LEFT JOIN books b ON b.book_type = t.name AND b.deleted_at IS NULL AND b.exclusive_shelf = 'read'
+             GROUP BY t.name ORDER BY t.rowid",
+        )
+        .map_err(|e| e.to_string())?;
+    let by_type: Vec<TypeStat> = stmt
+        .query_map([], |row| {
+            let book_type: String = row.get(0)?;
+            let beq_divisor = match divisors.get(&book_type) {
+                Some(pages) => Some(round2(*pages)),
+                None if mode == beq::Mode::Live => None,
+                None => Some(beq::DEFAULT_BASELINE),
+            };
+            Ok(TypeStat {
+                label: row.get(1)?,
+                books_read: row.get(2)?,
+                beq: round2(row.get(3)?),
+                avg_pages: round2(averages.get(&book_type).copied().unwrap_or(0.0)),
+                beq_divisor,
+                book_type,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
 
     Ok(Stats {
         total_books,
         total_read,
         total_beq: round2(total_beq),
-        total_beq_traditional: round2(total_beq_traditional),
-        total_beq_graphic_novel: round2(total_beq_graphic_novel),
-        avg_pages_traditional: round2(avg_pages_traditional),
-        avg_pages_graphic_novel: round2(avg_pages_graphic_novel),
         avg_rating: round2(avg_rating),
         books_this_year,
         beq_this_year: round2(beq_this_year),
         by_year,
         rating_dist,
+        beq_mode: mode.as_str().to_string(),
+        by_type,
+    })
+}
+
+const MONTH_LABELS: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+const WEEKDAY_LABELS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Books, pages and BEq read in `year` (default: this year) per month, ISO
+/// week (of the ISO week-numbering year) or weekday, from full read dates.
+/// Books count toward `year` by `year_read`, as in every other stat; those
+/// whose read date falls outside the year are reported as `undated`.
+/// Months and weeks also get trailing averages over `window` buckets
+/// (default 3 months or 4 weeks).
+#[tauri::command]
+pub fn get_reading_series(
+    state: State<DbState>,
+    bucket: String,
+    year: Option<i64>,
+    window: Option<i64>,
+) -> Result<ReadingSeries, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
+    let year = match year {
+        Some(year) => year,
+        None => today(&conn)?.0,
+    };
+    reading_series(&conn, bucket, year, window)
+}
+
+/// `get_reading_series` for a given year; call `beq::sync` first.
+pub(crate) fn reading_series(
+    conn: &Connection,
+    bucket: String,
+    year: i64,
+    window: Option<i64>,
+) -> Result<ReadingSeries, String> {
+    let read_date = "date(replace(books.date_read, '/', '-'))";
+    // (bucket number, year the bucket belongs to, number of buckets, default window)
+    let (number, year_of, count, default_window) = match bucket.as_str() {
+        "month" => ("%m", "%Y", 12, Some(3)),
+        "week" => {
+            let weeks: i64 = conn
+                .query_row(
+                    "SELECT CAST(strftime('%V', printf('%04d-12-28', ?1)) AS INTEGER)",
+                    params![year],
+                    |r| r.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            ("%V", "%G", weeks, Some(4))
+        }
+        "weekday" => ("%u", "%Y", 7, None),
+        other => return Err(format!("Unknown series bucket: {}", other)),
+    };
+    let window = match (default_window, window) {
+        (None, _) => None,
+        (Some(_), Some(w)) if w < 1 => return Err("Rolling window must be at least 1".to_string()),
+        (Some(default), w) => Some(w.unwrap_or(default)),
+    };
+
+    let in_year = format!("coalesce(CAST(strftime('{}', {}) AS INTEGER), 0) = ?1", year_of, read_date);
+    let mut totals = vec![(0i64, 0i64, 0f64); count as usize];
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT CAST(strftime('{number}', {d}) AS INTEGER), count(*), coalesce(sum(books.pages), 0), coalesce(sum(books.beq), 0)
+             FROM books
+             WHERE {read_in} AND {in_year}
+             GROUP BY 1",
+            number = number,
+            d = read_date,
+            read_in = read_in(year, None),
+            in_year = in_year,
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![year], |r| Ok((r.get::<_, i64>(0)?, r.get(1)?, r.get(2)?, r.get(3)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok());
+    for (n, books, pages, beq) in rows {
+        if let Some(slot) = totals.get_mut((n - 1) as usize) {
+            *slot = (books, pages, beq);
+        }
+    }
+
+    let points = (0..totals.len())
+        .map(|i| {
+            let (books, pages, beq) = totals[i];
+            let n = i as i64 + 1;
+            let (key, label) = match bucket.as_str() {
+                "month" => (format!("{}-{:02}", year, n), MONTH_LABELS[i].to_string()),
+                "week" => (format!("{}-W{:02}", year, n), format!("W{:02}", n)),
+                _ => (n.to_string(), WEEKDAY_LABELS[i].to_string()),
+            };
+            let trailing = window.map(|w| &totals[(i + 1).saturating_sub(w as usize)..=i]);
+            let average = |f: fn(&(i64, i64, f64)) -> f64| {
+                trailing.map(|slice| round2(slice.iter().map(f).sum::<f64>() / slice.len() as f64))
+            };
+            SeriesPoint {
+                key,
+                label,
+                books,
+                pages,
+                beq: round2(beq),
+                rolling_books: average(|t| t.0 as f64),
+                rolling_pages: average(|t| t.1 as f64),
+                rolling_beq: average(|t| t.2),
+            }
+        })
+        .collect();
+
+    let undated: i64 = conn
+        .query_row(
+            &format!("SELECT count(*) FROM books WHERE {} AND NOT {}", read_in(year, None), in_year),
+            params![year],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+
+    Ok(ReadingSeries {
+        bucket,
+        year,
+        window,
+        points,
+        undated,
+    })
+}
+
+/// Lower bounds of the page-length histogram ranges
+const PAGE_RANGES: [i64; 8] = [0, 100, 200, 300, 400, 500, 750, 1000];
+
+/// Read books (in `year`, when given) grouped by author, publisher, shelf,
+/// book type, publication decade and page length. Authors and publishers
+/// are the top `limit` (default 10) by `sort_by`: `books` (default),
+/// `pages` or `beq`.
+#[tauri::command]
+pub fn get_breakdowns(
+    state: State<DbState>,
+    year: Option<i64>,
+    sort_by: Option<String>,
+    limit: Option<i64>,
+) -> Result<Breakdowns, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
+    breakdowns(&conn, year, sort_by, limit)
+}
+
+/// `get_breakdowns` without the lock; call `beq::sync` first.
+pub(crate) fn breakdowns(
+    conn: &Connection,
+    year: Option<i64>,
+    sort_by: Option<String>,
+    limit: Option<i64>,
+) -> Result<Breakdowns, String> {
+    let sort_by = sort_by.unwrap_or_else(|| "books".to_string());
+    let order = match sort_by.as_str() {
+        "books" => "3 DESC, 4 DESC, 2",
+        "pages" => "4 DESC, 3 DESC, 2",
+        "beq" => "5 DESC, 3 DESC, 2",
+        other => return Err(format!("Unknown breakdown sort: {}", other)),
+    };
+    let limit = limit.unwrap_or(10).max(1);
+    let read = match year {
+        Some(year) => format!("{} AND books.exclusive_shelf = 'read'", read_in(year, None)),
+        None => "books.deleted_at IS NULL AND books.exclusive_shelf = 'read'".to_string(),
+    };
+
+    let authors = breakdown(conn, "trim(books.author)", "trim(books.author)", "", &read, order, Some(limit))?;
+    let publishers = breakdown(conn, "trim(books.publisher)", "trim(books.publisher)", "", &read, order, Some(limit))?;
+    let shelves = breakdown(
+        conn,
+        "s.name",
+        "s.name",
+        "JOIN book_shelves bs ON bs.book_id = books.id JOIN shelves s ON s.id = bs.shelf_id",
+        &read,
+        "3 DESC, 2",
+        None,
+    )?;
+    let book_types = breakdown(
+        conn,
+        "books.book_type",
+        "coalesce(t.label, books.book_type)",
+        "LEFT JOIN book_types t ON t.name = books.book_type",
+        &read,
+        "3 DESC, 2",
+        None,
+    )?;
+
+    let mut decades = Vec::new();
+    let found = breakdown(
+        conn,
+        "CAST(books.year_published / 10 AS INTEGER) * 10",
+        "''",
+        "",
+        &format!("{} AND books.year_published > 0", read),
+        "min(books.year_published)",
+        None,
+    )?;
+    let decade_of = |row: &BreakdownRow| row.key.parse::<i64>().unwrap_or_default();
+    if let (Some(first), Some(last)) = (found.first().map(decade_of), found.last().map(decade_of)) {
+        for decade in (first..=last).step_by(10) {
+            let row = found.iter().find(|r| decade_of(r) == decade);
+            decades.push(BreakdownRow {
+                label: format!("{}s", decade),
+                ..row.cloned().unwrap_or_else(|| empty_row(decade.to_string()))
+            });
+        }
+    }
+
+    let bucket = PAGE_RANGES
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, from)| format!("WHEN books.pages >= {} THEN {}", from, i))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let found = breakdown(
+        conn,
+        &format!("CASE {} END", bucket),
+        "''",
+        "",
+        &format!("{} AND books.pages > 0", read),
+        "1",
+        None,
+    )?;
+    let page_lengths = PAGE_RANGES
+        .iter()
+        .enumerate()
+        .map(|(i, from)| {
+            let row = found.iter().find(|r| r.key == i.to_string());
+            BreakdownRow {
+                key: from.to_string(),
+                label: page_range_label(i),
+                ..row.cloned().unwrap_or_else(|| empty_row(String::new()))
+            }
+        })
+        .collect();
+
+    Ok(Breakdowns {
+        year,
+        sort_by,
+        authors,
+        publishers,
+        shelves,
+        book_types,
+        decades,
+        page_lengths,
+    })
+}
+
+/// `<100`, `100–199` … `1000+` for the `PAGE_RANGES` entry at `i`.
+fn page_range_label(i: usize) -> String {
+    let from = PAGE_RANGES[i];
+    match PAGE_RANGES.get(i + 1) {
+        Some(to) if from == 0 => format!("<{}", to),
+        Some(to) => format!("{}–{}", from, to - 1),
+        None => format!("{}+", from),
+    }
+}
+
+/// Group the books matching `condition` by `key`, skipping empty keys.
+fn breakdown(
+    conn: &Connection,
+    key: &str,
+    label: &str,
+    joins: &str,
+    condition: &str,
+    order: &str,
+    limit: Option<i64>,
+) -> Result<Vec<BreakdownRow>, String> {
+    let sql = format!(
+        "SELECT CAST({key} AS TEXT), {label}, count(*), coalesce(sum(books.pages), 0), coalesce(sum(books.beq), 0),
+                avg(CASE WHEN books.my_rating > 0 THEN books.my_rating END)
+         FROM books {joins}
+         WHERE {condition} AND coalesce({key}, '') <> ''
+         GROUP BY 1 ORDER BY {order} LIMIT {limit}",
+        limit = limit.unwrap_or(-1),
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(BreakdownRow {
+                key: row.get(0)?,
+                label: row.get(1)?,
+                books: row.get(2)?,
+                pages: row.get(3)?,
+                beq: round2(row.get(4)?),
+                avg_rating: row.get::<_, Option<f64>>(5)?.map(round2),
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
+}
+
+fn empty_row(key: String) -> BreakdownRow {
+    BreakdownRow {
+        label: key.clone(),
+        key,
+        books: 0,
+        pages: 0,
+        beq: 0.0,
+        avg_rating: None,
+    }
+}
+
+/// Reads listed as fastest and slowest
+const PACE_EXTREMES: usize = 5;
+
+/// Reading streaks and speed. A day counts toward a streak when a book was
+/// finished that day or was being read (between `date_started` and the read
+/// date, or today for currently-reading books). Speeds use finished books
+/// with both dates; pages per day by type is total pages over total days.
+#[tauri::command]
+pub fn get_reading_pace(state: State<DbState>) -> Result<ReadingPace, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let started = "date(replace(books.date_started, '/', '-'))";
+    let read = "date(replace(books.date_read, '/', '-'))";
+
+    // Reading spans as unix day numbers, inclusive
+    let today: i64 = conn
+        .query_row("SELECT unixepoch(date('now', 'localtime')) / 86400", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT unixepoch(CASE WHEN {s} <= {r} THEN {s} ELSE {r} END) / 86400, unixepoch({r}) / 86400
+             FROM books WHERE books.deleted_at IS NULL AND {r} IS NOT NULL
+             UNION ALL
+             SELECT unixepoch({s}) / 86400, ?1 FROM books
+             WHERE books.deleted_at IS NULL AND books.exclusive_shelf = 'currently-reading' AND {s} <= date('now', 'localtime')
+             ORDER BY 1",
+            s = started,
+            r = read,
+        ))
+        .map_err(|e| e.to_string())?;
+    let spans: Vec<(i64, i64)> = stmt
+        .query_map(params![today], |r| Ok((r.get(0)?, r.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Merge overlapping or back-to-back spans into runs of reading days
+    let mut runs: Vec<(i64, i64)> = Vec::new();
+    for (from, to) in spans {
+        match runs.last_mut() {
+            Some(run) if from <= run.1 + 1 => run.1 = run.1.max(to),
+            _ => runs.push((from, to)),
+        }
+    }
+    let length = |(from, to): &(i64, i64)| to - from + 1;
+    let current_streak = runs
+        .iter()
+        .find(|(from, to)| *from <= today && *to >= today - 1)
+        .map(|run| run.1.min(today) - run.0 + 1)
+        .unwrap_or(0);
+    let longest = runs.iter().max_by_key(|run| length(run));
+    let day_to_date = |day: i64| -> Result<String, String> {
+        conn.query_row("SELECT date(?1 * 86400, 'unixepoch')", params![day], |r| r.get(0))
+            .map_err(|e| e.to_string())
+    };
+    let (longest_streak_start, longest_streak_end) = match longest {
+        Some(&(from, to)) => (Some(day_to_date(from)?), Some(day_to_date(to)?)),
+        None => (None, None),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT books.id, books.title, books.author, books.book_type, coalesce(books.pages, 0), {s}, {r},
+                    CAST(julianday({r}) - julianday({s}) AS INTEGER) + 1
+             FROM books
+             WHERE books.deleted_at IS NULL AND books.exclusive_shelf = 'read' AND {s} <= {r}",
+            s = started,
+            r = read,
+        ))
+        .map_err(|e| e.to_string())?;
+    let timed: Vec<TimedRead> = stmt
+        .query_map([], |row| {
+            let pages: i64 = row.get(4)?;
+            let days: i64 = row.get(7)?;
+            Ok(TimedRead {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                author: row.get(2)?,
+                book_type: row.get(3)?,
+                pages,
+                date_started: row.get(5)?,
+                date_read: row.get(6)?,
+                days,
+                pages_per_day: pages as f64 / days as f64,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let avg_days_to_finish = (!timed.is_empty())
+        .then(|| round2(timed.iter().map(|t| t.days as f64).sum::<f64>() / timed.len() as f64));
+
+    let mut by_type = Vec::new();
+    for book_type in book_types::list(&conn)? {
+        let reads: Vec<&TimedRead> = timed
+            .iter()
+            .filter(|t| t.book_type == book_type.name && t.pages > 0)
+            .collect();
+        if reads.is_empty() {
+            continue;
+        }
+        let days: i64 = reads.iter().map(|t| t.days).sum();
+        let pages: i64 = reads.iter().map(|t| t.pages).sum();
+        by_type.push(TypePace {
+            book_type: book_type.name,
+            label: book_type.label,
+            books: reads.len() as i64,
+            avg_days: round2(days as f64 / reads.len() as f64),
+            pages_per_day: round2(pages as f64 / days as f64),
+        });
+    }
+
+    let timed_books = timed.len() as i64;
+    let mut with_pages: Vec<TimedRead> = timed
+        .into_iter()
+        .filter(|t| t.pages > 0)
+        .map(|t| TimedRead { pages_per_day: round2(t.pages_per_day), ..t })
+        .collect();
+    with_pages.sort_by(|a, b| b.pages_per_day.total_cmp(&a.pages_per_day));
+    let fastest = with_pages.iter().take(PACE_EXTREMES).cloned().collect();
+    let slowest = with_pages.iter().rev().take(PACE_EXTREMES).cloned().collect();
+
+    Ok(ReadingPace {
+        current_streak,
+        longest_streak: longest.map(length).unwrap_or(0),
+        longest_streak_start,
+        longest_streak_end,
+        avg_days_to_finish,
+        timed_books,
+        by_type,
+        fastest,
+        slowest,
+    })
+}
+
+/// Default number of books in each of `liked_more` / `liked_less`
+const RATING_GAP_LIMIT: i64 = 10;
+
+struct RatedBook {
+    id: i64,
+    title: String,
+    author: String,
+    my_rating: f64,
+    average_rating: Option<f64>,
+    pages: Option<i64>,
+    year_published: Option<i64>,
+    book_type: String,
+}
+
+impl RatedBook {
+    /// Community average, when one was imported or looked up
+    fn community(&self) -> Option<f64> {
+        self.average_rating.filter(|a| *a > 0.0)
+    }
+}
+
+/// How the user's own 1–5 ratings compare with the community average, and
+/// how they relate to page count, publication year and book type. Books at
+/// least `threshold` stars (default 1) above or below the average are
+/// listed, up to `limit` (default 10) each, biggest gap first.
+#[tauri::command]
+pub fn get_rating_analysis(
+    state: State<DbState>,
+    threshold: Option<f64>,
+    limit: Option<i64>,
+) -> Result<RatingAnalysis, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let threshold = threshold.unwrap_or(1.0);
+    if !(threshold.is_finite() && threshold > 0.0) {
+        return Err("Rating threshold must be a positive number".to_string());
+    }
+    let limit = limit.unwrap_or(RATING_GAP_LIMIT).max(1) as usize;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, author, my_rating, average_rating, pages, year_published, book_type FROM books
+             WHERE deleted_at IS NULL AND my_rating > 0",
+        )
+        .map_err(|e| e.to_string())?;
+    let rated: Vec<RatedBook> = stmt
+        .query_map([], |r| {
+            Ok(RatedBook {
+                id: r.get(0)?,
+                title: r.get(1)?,
+                author: r.get(2)?,
+                my_rating: r.get::<_, i64>(3)? as f64,
+                average_rating: r.get(4)?,
+                pages: r.get(5)?,
+                year_published: r.get(6)?,
+                book_type: r.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let compared: Vec<(&RatedBook, f64)> = rated.iter().filter_map(|b| Some((b, b.community()?))).collect();
+    let differences: Vec<f64> = compared.iter().map(|(b, avg)| b.my_rating - avg).collect();
+    let mean_abs: Vec<f64> = differences.iter().map(|d| d.abs()).collect();
+
+    let mut gaps: Vec<RatingGap> = compared
+        .iter()
+        .map(|(b, avg)| RatingGap {
+            id: b.id,
+            title: b.title.clone(),
+            author: b.author.clone(),
+            my_rating: b.my_rating as i64,
+            average_rating: *avg,
+            difference: round2(b.my_rating - avg),
+        })
+        .collect();
+    gaps.sort_by(|a, b| b.difference.total_cmp(&a.difference));
+    let liked_more = gaps.iter().take_while(|g| g.difference >= threshold).take(limit).cloned().collect();
+    let liked_less = gaps.iter().rev().take_while(|g| g.difference <= -threshold).take(limit).cloned().collect();
+
+    let pages = rating_correlation(
+        &rated,
+        |b| b.pages.filter(|p| *p > 0),
+        |pages| {
+            let i = PAGE_RANGES.iter().rposition(|from| pages >= *from).unwrap_or_default();
+            (PAGE_RANGES[i], page_range_label(i))
+        },
+    );
+    let year_published = rating_correlation(
+        &rated,
+        |b| b.year_published.filter(|y| *y > 0),
+        |year| (year / 10 * 10, format!("{}s", year / 10 * 10)),
+    );
+
+    let mut by_type = Vec::new();
+    for book_type in book_types::list(&conn)? {
+        let books: Vec<&RatedBook> = rated.iter().filter(|b| b.book_type == book_type.name).collect();
+        if !books.is_empty() {
+            by_type.push(rating_group(book_type.name, book_type.label, &books));
+        }
+    }
+
+    Ok(RatingAnalysis {
+        rated_books: rated.len() as i64,
+        compared_books: compared.len() as i64,
+        threshold,
+        mean_deviation: mean(&differences).map(round2),
+        mean_abs_deviation: mean(&mean_abs).map(round2),
+        community_correlation: pearson(compared.iter().map(|(b, avg)| (b.my_rating, *avg))).map(round2),
+        liked_more,
+        liked_less,
+        pages,
+        year_published,
+        by_type,
     })
 }
 
+/// Correlate ratings with `value` (books without one are left out), and
+/// average them per `bucket` (start, label) in ascending order.
+fn rating_correlation(
+    rated: &[RatedBook],
+    value: impl Fn(&RatedBook) -> Option<i64>,
+    bucket: impl Fn(i64) -> (i64, String),
+) -> RatingCorrelation {
+    let books: Vec<(&RatedBook, i64)> = rated.iter().filter_map(|b| Some((b, value(b)?))).collect();
+
+    let mut grouped: BTreeMap<i64, (String, Vec<&RatedBook>)> = BTreeMap::new();
+    for (book, v) in &books {
+        let (start, label) = bucket(*v);
+        grouped.entry(start).or_insert_with(|| (label, Vec::new())).1.push(book);
+    }
+
+    RatingCorrelation {
+        books: books.len() as i64,
+        my_correlation: pearson(books.iter().map(|(b, v)| (*v as f64, b.my_rating))).map(round2),
+        community_correlation: pearson(books.iter().filter_map(|(b, v)| Some((*v as f64, b.community()?))))
+            .map(round2),
+        buckets: grouped
+            .into_iter()
+            .map(|(start, (label, books))| rating_group(start.to_string(), label, &books))
+            .collect(),
+    }
+}
+
+fn rating_group(key: String, label: String, books: &[&RatedBook]) -> RatingGroup {
+    let mine: Vec<f64> = books.iter().map(|b| b.my_rating).collect();
+    let community: Vec<f64> = books.iter().filter_map(|b| b.community()).collect();
+    let differences: Vec<f64> = books.iter().filter_map(|b| Some(b.my_rating - b.community()?)).collect();
+    RatingGroup {
+        key,
+        label,
+        books: books.len() as i64,
+        avg_my_rating: mean(&mine).map(round2).unwrap_or_default(),
+        avg_community: mean(&community).map(round2),
+        mean_deviation: mean(&differences).map(round2),
+    }
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+/// Pearson correlation coefficient, or `None` for fewer than three pairs or
+/// when either side doesn't vary.
+fn pearson(pairs: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let pairs: Vec<(f64, f64)> = pairs.collect();
+    if pairs.len() < 3 {
+        return None;
+    }
+    let n = pairs.len() as f64;
+    let mean_x = pairs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = pairs.iter().map(|p| p.1).sum::<f64>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in &pairs {
+        sxy += (x - mean_x) * (y - mean_y);
+        sxx += (x - mean_x).powi(2);
+        syy += (y - mean_y).powi(2);
+    }
+    (sxx > 0.0 && syy > 0.0).then(|| sxy / (sxx * syy).sqrt())
+}
+
+/// Round to two decimal places for display.
+pub(crate) fn round2(v: f64) -> f64 {
+    (v * 100.0).round() / 100.0
+}
+
+/// Local date as (year, month, day).
+pub(crate) fn today(conn: &Connection) -> Result<(i64, i64, i64), String> {
+    conn.query_row(
+        "SELECT CAST(strftime('%Y', 'now', 'localtime') AS INTEGER),
+                CAST(strftime('%m', 'now', 'localtime') AS INTEGER),
+                CAST(strftime('%d', 'now', 'localtime') AS INTEGER)",
+        [],
+        |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// SQL condition for books read in `year` (and `month`, when given), by
+/// `year_read` and the month of `date_read` (`YYYY/MM/DD` or `YYYY-MM-DD`).
+pub(crate) fn read_in(year: i64, month: Option<i64>) -> String {
+    let mut condition = format!("books.deleted_at IS NULL AND books.year_read = {}", year);
+    if let Some(month) = month {
+        condition.push_str(&format!(
+            " AND books.date_read GLOB '[0-9][0-9][0-9][0-9][-/][0-9][0-9]*' \
+             AND CAST(substr(books.date_read, 6, 2) AS INTEGER) = {}",
+            month
+        ));
+    }
+    condition
+}
+
 #[tauri::command]
 pub fn get_shelf_counts(state: State<DbState>) -> Result<Vec<ShelfCount>, String> {
     let conn = state.conn.lock().map_err(|e| e.to_string())?;
     let mut stmt = conn
-        .prepare("SELECT exclusive_shelf, count(*) FROM books GROUP BY exclusive_shelf")
+        .prepare("SELECT exclusive_shelf, count(*) FROM books WHERE deleted_at IS NULL GROUP BY exclusive_shelf")
         .map_err(|e| e.to_string())?;
     let counts: Vec<ShelfCount> = stmt
         .query_map([], |row| {
@@ -0,0 +1,91 @@
+use crate::backup;
+use crate::beq;
+use crate::commands::books::{row_to_book, select_columns};
+use crate::db::{get_setting_i64, DbState};
+use crate::history;
+use crate::models::*;
+use rusqlite::{params, Connection};
+use tauri::State;
+
+/// Days a book stays in the trash unless `trash_retention_days` is set
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+/// Setting holding the last failed startup purge
+pub const LAST_ERROR_KEY: &str = "last_purge_error";
+
+/// Books in the trash, most recently deleted first.
+#[tauri::command]
+pub fn list_trash(state: State<DbState>) -> Result<Vec<Book>, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
+    let sql = format!(
+        "SELECT {} FROM books WHERE books.deleted_at IS NOT NULL ORDER BY books.deleted_at DESC",
+        select_columns()
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let books: Vec<Book> = stmt
+        .query_map([], row_to_book)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(books)
+}
+
+/// Take a book out of the trash. Shelf links were never removed, so the
+/// book reappears on every shelf it was on (minus shelves deleted since).
+#[tauri::command]
+pub fn restore_book(state: State<DbState>, id: i64) -> Result<(), String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let before = history::snapshot(&conn, id)?;
+    let changed = conn
+        .execute(
+            "UPDATE books SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![id],
+        )
+        .map_err(|e| e.to_string())?;
+    if changed == 0 {
+        return Err(format!("Book {} is not in the trash", id));
+    }
+    history::record(&conn, id, "restore", before.as_ref(), None)
+}
+
+/// Permanently delete trashed books older than `older_than_days`
+/// (defaults to the `trash_retention_days` setting; 0 empties the trash).
+/// Returns the number of books purged.
+#[tauri::command]
+pub fn purge_trash(state: State<DbState>, older_than_days: Option<i64>) -> Result<i64, String> {
+    let mut conn = state.conn.lock().map_err(|e| e.to_string())?;
+    let days = older_than_days
+        .unwrap_or_else(|| get_setting_i64(&conn, "trash_retention_days", DEFAULT_RETENTION_DAYS));
+    purge_expired(&mut conn, &state.data_dir, days)
+}
+
+/// Hard-delete trashed books deleted more than `days` ago, backing up first
+/// when there is anything to remove. Their change history goes with them.
+pub fn purge_expired(conn: &mut Connection, data_dir: &std::path::Path, days: i64) -> Result<i64, String> {
+    let cutoff = format!("-{} days", days.max(0));
+    let expired: i64 = conn
+        .query_row(
+            "SELECT count(*) FROM books WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)",
+            params![cutoff],
+            |r| r.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if expired == 0 {
+        return Ok(0);
+    }
+
+    backup::create_backup(conn, data_dir, "pre-purge")
+        .map_err(|e| format!("Aborted: could not back up before purging: {}", e))?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let purged = tx
+        .execute(
+            "DELETE FROM books WHERE deleted_at IS NOT NULL AND deleted_at <= datetime('now', ?1)",
+            params![cutoff],
+        )
+        .map_err(|e| e.to_string())?;
+    // Also clears history left behind by purges before this was done here
+    tx.execute("DELETE FROM book_history WHERE book_id NOT IN (SELECT id FROM books)", [])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(purged as i64)
+}
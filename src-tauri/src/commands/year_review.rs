@@ -0,0 +1,31 @@
+use crate::beq;
+use crate::commands::stats::today;
+use crate::db::DbState;
+use crate::models::{YearReview, YearReviewExport};
+use crate::year_review;
+use tauri::State;
+
+/// The "Year in Books" report for `year` (default: this year).
+#[tauri::command]
+pub fn get_year_review(state: State<DbState>, year: Option<i64>) -> Result<YearReview, String> {
+    let conn = state.conn.lock().map_err(|e| e.to_string())?;
+    beq::sync(&conn)?;
+    let year = match year {
+        Some(year) => year,
+        None => today(&conn)?.0,
+    };
+    year_review::build(&conn, year)
+}
+
+/// Build the report and save it as JSON and standalone HTML under
+/// `reports/` in the app data dir.
+#[tauri::command]
+pub fn export_year_review(state: State<DbState>, year: Option<i64>) -> Result<YearReviewExport, String> {
+    let review = get_year_review(state.clone(), year)?;
+    let (json_path, html_path) = year_review::export(&state.data_dir, &review)?;
+    Ok(YearReviewExport {
+        json_path: json_path.to_string_lossy().to_string(),
+        html_path: html_path.to_string_lossy().to_string(),
+        review,
+    })
+}
@@ -0,0 +1,403 @@
+use crate::db::DbState;
+use crate::models::{CoverPruneResult, CoverValidation};
+use crate::palette::{self, Palette};
+use crate::providers::http::Throttle;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageFormat};
+use reqwest::Client;
+use rusqlite::{params, Connection, OptionalExtension};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// URI scheme the webview loads cached covers from:
+/// `bookcover://localhost/<hash>/<size>` (`http://bookcover.localhost/...` on Windows)
+pub const PROTOCOL: &str = "bookcover";
+
+/// Thumbnail variants as (name, max width, max height)
+const THUMBNAILS: &[(&str, u32, u32)] = &[("small", 120, 180), ("medium", 320, 480)];
+const THUMBNAIL_QUALITY: u8 = 85;
+/// Uploaded covers larger than this are scaled down before storing
+const MAX_UPLOAD_DIMENSIONS: (u32, u32) = (1600, 2400);
+const MAX_UPLOAD_BYTES: usize = 20 * 1024 * 1024;
+/// `cover_cached_from` value for covers the user uploaded
+pub const UPLOAD_SOURCE: &str = "upload";
+/// Smallest width/height accepted as real cover art
+const MIN_DIMENSION: u32 = 20;
+/// Extensions an original may be stored under, per `ImageFormat`
+const ORIGINAL_EXTENSIONS: &[&str] = &["jpg", "png", "gif", "webp"];
+
+/// A cover written to the cache
+pub struct StoredCover {
+    /// Path relative to the data dir, e.g. `covers/<hash>.jpg`
+    pub path: String,
+    pub hash: String,
+    pub palette: Palette,
+}
+
+pub fn cover_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("covers")
+}
+
+/// Decode `bytes`, store the original under its SHA-256 plus the thumbnail
+/// variants, and extract its colors. Files are content-addressed, so books
+/// sharing artwork share one copy.
+pub fn store_bytes(data_dir: &Path, bytes: &[u8]) -> Result<StoredCover, String> {
+    let format = image::guess_format(bytes).map_err(|e| format!("Not an image: {}", e))?;
+    let ext = match format {
+        ImageFormat::Jpeg => "jpg",
+        ImageFormat::Png => "png",
+        ImageFormat::Gif => "gif",
+        ImageFormat::WebP => "webp",
+        other => return Err(format!("Unsupported image format: {:?}", other)),
+    };
+    let img = image::load_from_memory_with_format(bytes, format).map_err(|e| format!("Invalid image: {}", e))?;
+
+    let hash = sha256_hex(bytes);
+    let dir = cover_dir(data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cover dir: {}", e))?;
+
+    let file_name = format!("{}.{}", hash, ext);
+    let original = dir.join(&file_name);
+    if !original.exists() {
+        std::fs::write(&original, bytes).map_err(|e| format!("Failed to save cover: {}", e))?;
+    }
+    write_thumbnails(&dir, &hash, &img)?;
+
+    Ok(StoredCover {
+        path: format!("covers/{}", file_name),
+        hash,
+        palette: palette::extract(&img),
+    })
+}
+
+/// Validate a user-supplied image, scale it down if it's oversized, and store
+/// it like a downloaded cover.
+pub fn store_upload(data_dir: &Path, bytes: &[u8]) -> Result<StoredCover, String> {
+    if bytes.len() > MAX_UPLOAD_BYTES {
+        return Err(format!("Image is larger than {} MB", MAX_UPLOAD_BYTES / 1024 / 1024));
+    }
+    let img = image::load_from_memory(bytes).map_err(|e| format!("Invalid image: {}", e))?;
+    let (max_w, max_h) = MAX_UPLOAD_DIMENSIONS;
+    if img.width() <= max_w && img.height() <= max_h {
+        return store_bytes(data_dir, bytes);
+    }
+
+    let resized = img.resize(max_w, max_h, image::imageops::FilterType::Lanczos3).to_rgb8();
+    let mut out = Vec::new();
+    JpegEncoder::new_with_quality(&mut out, 90)
+        .encode_image(&resized)
+        .map_err(|e| format!("Failed to encode cover: {}", e))?;
+    store_bytes(data_dir, &out)
+}
+
+/// Pixel dimensions read from the image header, without a full decode.
+pub fn dimensions(bytes: &[u8]) -> Result<(u32, u32), String> {
+    image::ImageReader::new(std::io::Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| e.to_string())?
+        .into_dimensions()
+        .map_err(|e| format!("Not an image: {}", e))
+}
+
+/// Store a generated SVG cover. Vector art scales freely, so no thumbnails
+/// are made; every size resolves to the one file.
+pub fn store_svg(data_dir: &Path, svg: &str) -> Result<(String, String), String> {
+    let hash = sha256_hex(svg.as_bytes());
+    let dir = cover_dir(data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create cover dir: {}", e))?;
+    let file_name = format!("{}.svg", hash);
+    let path = dir.join(&file_name);
+    if !path.exists() {
+        std::fs::write(&path, svg).map_err(|e| format!("Failed to save cover: {}", e))?;
+    }
+    Ok((format!("covers/{}", file_name), hash))
+}
+
+/// Colors of an already cached cover, read from its small thumbnail. Used to
+/// fill in covers cached before colors were extracted.
+pub fn palette_of(data_dir: &Path, hash: &str) -> Result<Palette, String> {
+    let dir = cover_dir(data_dir);
+    let thumbnail = dir.join(format!("{}-small.jpg", hash));
+    let path = if thumbnail.is_file() {
+        thumbnail
+    } else {
+        ORIGINAL_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", hash, ext)))
+            .find(|p| p.is_file())
+            .ok_or_else(|| format!("Cover {} is not cached", hash))?
+    };
+    let img = image::open(&path).map_err(|e| format!("Invalid image: {}", e))?;
+    Ok(palette::extract(&img))
+}
+
+fn write_thumbnails(dir: &Path, hash: &str, img: &DynamicImage) -> Result<(), String> {
+    for (name, width, height) in THUMBNAILS {
+        let path = dir.join(format!("{}-{}.jpg", hash, name));
+        if path.exists() {
+            continue;
+        }
+        let thumb = img.thumbnail(*width, *height).to_rgb8();
+        let mut out = Vec::new();
+        JpegEncoder::new_with_quality(&mut out, THUMBNAIL_QUALITY)
+            .encode_image(&thumb)
+            .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+        std::fs::write(&path, out).map_err(|e| format!("Failed to save thumbnail: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Download `url` and cache it as the book's cover. The row is only updated
+/// if its `cover_url` is still `url`, so a cover changed meanwhile wins.
+pub async fn cache_from_url(db: &DbState, client: &Client, book_id: i64, url: &str) -> Result<String, String> {
+    let throttle = Throttle::new("cover-download", None);
+    let resp = throttle.send(client.get(url)).await?;
+    if !resp.status().is_success() {
+        return Err(format!("Cover download failed: HTTP {}", resp.status()));
+    }
+    let bytes = resp.bytes().await.map_err(|e| e.to_string())?;
+    let digest = sha256_hex(&bytes);
+    if is_blocked(&*db.conn.lock().map_err(|e| e.to_string())?, &digest) {
+        return Err("Cover is a known placeholder image".to_string());
+    }
+
+    let stored = store_bytes(&db.data_dir, &bytes)?;
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE books SET cover_path = ?1, cover_hash = ?2, cover_cached_from = ?3,
+            cover_color = ?5, cover_accent = ?6, cover_hue = ?7
+         WHERE id = ?4 AND cover_url = ?3",
+        params![
+            stored.path,
+            stored.hash,
+            url,
+            book_id,
+            stored.palette.dominant,
+            stored.palette.accent,
+            stored.palette.hue,
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(stored.hash)
+}
+
+/// Books whose remote cover hasn't been cached yet, or changed since it was.
+pub fn uncached(conn: &Connection) -> Result<Vec<(i64, String, String)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, title, cover_url FROM books
+             WHERE deleted_at IS NULL AND coalesce(cover_url, '') LIKE 'http%'
+               AND (cover_hash IS NULL OR coalesce(cover_cached_from, '') != cover_url)
+             ORDER BY id",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(rows)
+}
+
+/// Resolve a protocol path (`<hash>/<size>`) to a file in the cover dir.
+/// `size` is `small`, `medium` or `original`; anything else is rejected.
+pub fn resolve(data_dir: &Path, request_path: &str) -> Option<PathBuf> {
+    let mut parts = request_path.trim_matches('/').split('/');
+    let hash = parts.next()?;
+    let size = parts.next().unwrap_or("medium");
+    if parts.next().is_some() || hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let dir = cover_dir(data_dir);
+    let svg = dir.join(format!("{}.svg", hash));
+    if svg.is_file() {
+        return Some(svg);
+    }
+    let path = match size {
+        "original" => ORIGINAL_EXTENSIONS
+            .iter()
+            .map(|ext| dir.join(format!("{}.{}", hash, ext)))
+            .find(|p| p.is_file())?,
+        s if THUMBNAILS.iter().any(|(name, _, _)| *name == s) => dir.join(format!("{}-{}.jpg", hash, s)),
+        _ => return None,
+    };
+    path.is_file().then_some(path)
+}
+
+/// MIME type for a cached file, sniffed from its contents.
+pub fn content_type(bytes: &[u8]) -> &'static str {
+    if bytes.starts_with(b"<svg") {
+        return "image/svg+xml";
+    }
+    match image::guess_format(bytes) {
+        Ok(ImageFormat::Png) => "image/png",
+        Ok(ImageFormat::Gif) => "image/gif",
+        Ok(ImageFormat::WebP) => "image/webp",
+        _ => "image/jpeg",
+    }
+}
+
+/// Delete cached files no book (including trashed ones) refers to.
+pub fn prune(conn: &Connection, data_dir: &Path) -> Result<CoverPruneResult, String> {
+    let mut stmt = conn
+        .prepare("SELECT DISTINCT cover_hash FROM books WHERE cover_hash IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let referenced: HashSet<String> = stmt
+        .query_map([], |r| r.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut result = CoverPruneResult { files_removed: 0, bytes_freed: 0 };
+    let dir = cover_dir(data_dir);
+    if !dir.exists() {
+        return Ok(result);
+    }
+    for entry in std::fs::read_dir(&dir).map_err(|e| e.to_string())?.filter_map(|e| e.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        // `<hash>.<ext>` or `<hash>-<size>.jpg`
+        let hash: String = name.chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+        if referenced.contains(&hash) {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        if std::fs::remove_file(entry.path()).is_ok() {
+            result.files_removed += 1;
+            result.bytes_freed += size;
+        }
+    }
+    Ok(result)
+}
+
+/// Decode the image header and check it is a supported format with
+/// plausible dimensions. Returns `(width, height)`.
+pub fn inspect(bytes: &[u8]) -> Result<(u32, u32), String> {
+    let format = image::guess_format(bytes).map_err(|_| "Not an image".to_string())?;
+    if !matches!(format, ImageFormat::Jpeg | ImageFormat::Png | ImageFormat::Gif | ImageFormat::WebP) {
+        return Err(format!("Unsupported image format: {:?}", format));
+    }
+    let (width, height) = dimensions(bytes)?;
+    if width < MIN_DIMENSION || height < MIN_DIMENSION {
+        return Err(format!("Image too small ({}x{})", width, height));
+    }
+    Ok((width, height))
+}
+
+/// Validate a cover URL, reusing the recorded result when the URL was
+/// already found valid. Invalid results are re-checked on the next call,
+/// since a provider may have added the artwork since. Only images that were
+/// downloaded are recorded: a 404 comes back invalid without a row, and
+/// request failures are returned as errors.
+pub async fn check_url(db: &DbState, client: &Client, url: &str) -> Result<CoverValidation, String> {
+    {
+        let conn = db.conn.lock().map_err(|e| e.to_string())?;
+        if let Some(known) = recorded(&conn, url)?.filter(|v| v.valid) {
+            return Ok(known);
+        }
+    }
+
+    let throttle = Throttle::new("cover-check", None);
+    let fetched = crate::providers::fetch_image(client, &throttle, url).await?;
+
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let Some(bytes) = fetched else {
+        let checked_at = conn
+            .query_row("SELECT datetime('now')", [], |r| r.get(0))
+            .map_err(|e| e.to_string())?;
+        return Ok(CoverValidation {
+            url: url.to_string(),
+            valid: false,
+            width: None,
+            height: None,
+            hash: None,
+            reason: Some("Image not found".to_string()),
+            checked_at,
+        });
+    };
+    let hash = sha256_hex(&bytes);
+    let outcome = if is_blocked(&conn, &hash) {
+        Err("Known placeholder image".to_string())
+    } else {
+        inspect(&bytes)
+    };
+    let (width, height) = outcome.as_ref().ok().copied().unzip();
+    conn.execute(
+        "INSERT INTO cover_validations (url, valid, width, height, hash, reason) VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(url) DO UPDATE SET valid = excluded.valid, width = excluded.width, height = excluded.height,
+            hash = excluded.hash, reason = excluded.reason, checked_at = datetime('now')",
+        params![url, outcome.is_ok(), width, height, hash, outcome.as_ref().err()],
+    )
+    .map_err(|e| e.to_string())?;
+    recorded(&conn, url)?.ok_or_else(|| "Validation was not recorded".to_string())
+}
+
+pub fn recorded(conn: &Connection, url: &str) -> Result<Option<CoverValidation>, String> {
+    conn.query_row(
+        "SELECT url, valid, width, height, hash, reason, checked_at FROM cover_validations WHERE url = ?1",
+        params![url],
+        |r| {
+            Ok(CoverValidation {
+                url: r.get(0)?,
+                valid: r.get(1)?,
+                width: r.get(2)?,
+                height: r.get(3)?,
+                hash: r.get(4)?,
+                reason: r.get(5)?,
+                checked_at: r.get(6)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Whether `hash` is on the placeholder blocklist.
+pub fn is_blocked(conn: &Connection, hash: &str) -> bool {
+    conn.query_row("SELECT 1 FROM cover_blocklist WHERE hash = ?1", params![hash], |_| Ok(()))
+        .optional()
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::apply_schema;
+    use crate::providers::test_server::serve;
+    use std::sync::Mutex;
+
+    fn recorded_count(db: &DbState) -> i64 {
+        let conn = db.conn.lock().unwrap();
+        conn.query_row("SELECT count(*) FROM cover_validations", [], |r| r.get(0)).unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_downloaded_images_are_recorded() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_schema(&conn).unwrap();
+        let db = DbState { conn: Mutex::new(conn), data_dir: std::env::temp_dir() };
+        let client = Client::new();
+
+        let (forbidden, _) = serve(403, b"").await;
+        assert!(check_url(&db, &client, &format!("{}/cover.jpg", forbidden)).await.is_err());
+        assert_eq!(recorded_count(&db), 0);
+
+        let (missing, _) = serve(404, b"").await;
+        let result = check_url(&db, &client, &format!("{}/cover.jpg", missing)).await.unwrap();
+        assert!(!result.valid);
+        assert_eq!(recorded_count(&db), 0);
+
+        let (blank, _) = serve(200, b"<html>No cover</html>").await;
+        let url = format!("{}/cover.jpg", blank);
+        let result = check_url(&db, &client, &url).await.unwrap();
+        assert_eq!(result.reason.as_deref(), Some("Not an image"));
+        assert!(recorded(&db.conn.lock().unwrap(), &url).unwrap().is_some_and(|v| !v.valid));
+    }
+}
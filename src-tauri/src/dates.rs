@@ -0,0 +1,33 @@
+/// Pull a year out of free-form dates like "2024/05/15", "May 15, 2024",
+/// "March 1985" or "c1999". Only standalone 4-digit runs count, so ISBN-like
+/// numbers and timestamps are ignored.
+pub fn parse_year(date: &str) -> Option<i64> {
+    date.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| run.len() == 4)
+        .filter_map(|run| run.parse::<i64>().ok())
+        .find(|y| (1000..=2099).contains(y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_year;
+
+    #[test]
+    fn finds_standalone_years() {
+        let cases = [
+            ("2024/05/15", Some(2024)),
+            ("2024-05-15", Some(2024)),
+            ("May 15, 2024", Some(2024)),
+            ("March 1985", Some(1985)),
+            ("c1999", Some(1999)),
+            ("1605", Some(1605)),
+            ("9780441013593", None),
+            ("0999", None),
+            ("2100", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_year(input), expected, "{:?}", input);
+        }
+    }
+}
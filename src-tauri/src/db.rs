@@ -1,9 +1,12 @@
-use rusqlite::Connection;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::PathBuf;
 use std::sync::Mutex;
 use tauri::{AppHandle, Manager};
 
 pub struct DbState {
     pub conn: Mutex<Connection>,
+    /// App data directory holding `books.db` and its `backups/` folder
+    pub data_dir: PathBuf,
 }
 
 pub fn init_db(app: &AppHandle) -> Result<DbState, String> {
@@ -21,14 +24,61 @@ pub fn init_db(app: &AppHandle) -> Result<DbState, String> {
     conn.execute_batch("PRAGMA foreign_keys = ON;").ok();
     conn.execute_batch("PRAGMA busy_timeout = 5000;").ok();
 
-    create_tables(&conn)?;
-    run_migrations(&conn)?;
+    apply_schema(&conn)?;
 
     Ok(DbState {
         conn: Mutex::new(conn),
+        data_dir: app_dir,
     })
 }
 
+/// Create missing tables and bring an existing database up to the current schema.
+pub fn apply_schema(conn: &Connection) -> Result<(), String> {
+    create_tables(conn)?;
+    run_migrations(conn)
+}
+
+/// Read a value from the `settings` table.
+pub fn get_setting(conn: &Connection, key: &str) -> Option<String> {
+    conn.query_row("SELECT value FROM settings WHERE key = ?1", params![key], |r| r.get(0))
+        .optional()
+        .ok()
+        .flatten()
+}
+
+/// Read a numeric setting, falling back to `default` when unset or unparsable.
+pub fn get_setting_i64(conn: &Connection, key: &str, default: i64) -> i64 {
+    get_setting(conn, key)
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(default)
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Keep the outcome of an unattended task (startup or scheduled backup, trash
+/// purge) under `key`: a failure is stored with its time so the settings page
+/// can show it, and the next success clears it. Storing is best effort since
+/// there is nowhere left to report a failure to write the setting.
+pub fn record_background_result<T>(conn: &Connection, key: &str, result: &Result<T, String>) {
+    let _ = match result {
+        Ok(_) => conn.execute("DELETE FROM settings WHERE key = ?1", params![key]),
+        Err(e) => conn.execute(
+            "INSERT INTO settings (key, value)
+             VALUES (?1, strftime('%Y-%m-%d %H:%M', 'now', 'localtime') || ': ' || ?2)
+             ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            params![key, e],
+        ),
+    };
+}
+
 fn create_tables(conn: &Connection) -> Result<(), String> {
     conn.execute_batch(
         "
@@ -74,11 +124,90 @@ fn create_tables(conn: &Connection) -> Result<(), String> {
             PRIMARY KEY (book_id, shelf_id)
         );
 
+        CREATE TABLE IF NOT EXISTS book_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            book_id INTEGER NOT NULL,
+            revision INTEGER NOT NULL,
+            action TEXT NOT NULL,
+            changes TEXT NOT NULL,
+            note TEXT,
+            undone INTEGER NOT NULL DEFAULT 0,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS metadata_proposals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            book_id INTEGER NOT NULL REFERENCES books(id) ON DELETE CASCADE,
+            field TEXT NOT NULL,
+            proposed TEXT NOT NULL,
+            source TEXT NOT NULL,
+            created_at TEXT DEFAULT (datetime('now')),
+            UNIQUE (book_id, field)
+        );
+
+        CREATE TABLE IF NOT EXISTS lookup_cache (
+            provider TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            query TEXT NOT NULL,
+            response TEXT,
+            fetched_at TEXT NOT NULL DEFAULT (datetime('now')),
+            expires_at TEXT NOT NULL,
+            PRIMARY KEY (provider, kind, query)
+        );
+
+        CREATE TABLE IF NOT EXISTS cover_validations (
+            url TEXT PRIMARY KEY,
+            valid INTEGER NOT NULL,
+            width INTEGER,
+            height INTEGER,
+            hash TEXT,
+            reason TEXT,
+            checked_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS cover_blocklist (
+            hash TEXT PRIMARY KEY,
+            note TEXT,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS book_types (
+            name TEXT PRIMARY KEY,
+            label TEXT NOT NULL,
+            beq_baseline REAL,
+            default_binding TEXT,
+            aliases TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        INSERT OR IGNORE INTO book_types (name, label, beq_baseline, aliases) VALUES
+            ('traditional', 'Traditional', 348, '[\"book\", \"novel\", \"paperback\", \"hardcover\", \"ebook\", \"kindle edition\", \"mass market paperback\"]'),
+            ('graphic_novel', 'Graphic Novel', NULL, '[\"graphic novel\", \"graphic novels\", \"comic\", \"comics\", \"comic book\", \"manga\", \"bande dessinée\"]');
+
+        CREATE TABLE IF NOT EXISTS goals (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL,
+            period TEXT NOT NULL,
+            year INTEGER NOT NULL,
+            month INTEGER,
+            metric TEXT NOT NULL,
+            target REAL NOT NULL,
+            filter TEXT,
+            distinct_by TEXT,
+            created_at TEXT DEFAULT (datetime('now'))
+        );
+
+        CREATE TABLE IF NOT EXISTS settings (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        );
+
         CREATE INDEX IF NOT EXISTS idx_books_title ON books(title);
         CREATE INDEX IF NOT EXISTS idx_books_author_sort ON books(author_sort);
         CREATE INDEX IF NOT EXISTS idx_books_exclusive_shelf ON books(exclusive_shelf);
         CREATE INDEX IF NOT EXISTS idx_books_isbn ON books(isbn);
         CREATE INDEX IF NOT EXISTS idx_books_isbn13 ON books(isbn13);
+        CREATE INDEX IF NOT EXISTS idx_book_history_book ON book_history(book_id, revision);
         ",
     )
     .map_err(|e| format!("Failed to create tables: {}", e))
@@ -86,15 +215,182 @@ fn create_tables(conn: &Connection) -> Result<(), String> {
 
 fn run_migrations(conn: &Connection) -> Result<(), String> {
     // Migration 1: Add book_type column
-    let has_book_type: bool = conn
-        .prepare("SELECT COUNT(*) FROM pragma_table_info('books') WHERE name = 'book_type'")
-        .and_then(|mut s| s.query_row([], |r| r.get::<_, i64>(0)))
-        .unwrap_or(0) > 0;
-
-    if !has_book_type {
+    if !column_exists(conn, "books", "book_type") {
         conn.execute_batch("ALTER TABLE books ADD COLUMN book_type TEXT NOT NULL DEFAULT 'traditional'")
             .map_err(|e| format!("Migration failed (book_type): {}", e))?;
     }
 
+    // Migration 2: Soft delete — trashed books keep their row and shelf links
+    if !column_exists(conn, "books", "deleted_at") {
+        conn.execute_batch(
+            "ALTER TABLE books ADD COLUMN deleted_at TEXT;
+             CREATE INDEX IF NOT EXISTS idx_books_deleted_at ON books(deleted_at);",
+        )
+        .map_err(|e| format!("Migration failed (deleted_at): {}", e))?;
+    }
+
+    // Migration 3: Descriptive metadata filled from ISBN lookups
+    for (column, ddl) in [
+        ("subjects", "ALTER TABLE books ADD COLUMN subjects TEXT"),
+        ("description", "ALTER TABLE books ADD COLUMN description TEXT"),
+        ("language", "ALTER TABLE books ADD COLUMN language TEXT"),
+        ("series", "ALTER TABLE books ADD COLUMN series TEXT"),
+    ] {
+        if !column_exists(conn, "books", column) {
+            conn.execute_batch(ddl)
+                .map_err(|e| format!("Migration failed ({}): {}", column, e))?;
+        }
+    }
+
+    // Migration 4: Locally cached cover art (see cover_cache.rs)
+    for (column, ddl) in [
+        ("cover_path", "ALTER TABLE books ADD COLUMN cover_path TEXT"),
+        ("cover_hash", "ALTER TABLE books ADD COLUMN cover_hash TEXT"),
+        ("cover_cached_from", "ALTER TABLE books ADD COLUMN cover_cached_from TEXT"),
+    ] {
+        if !column_exists(conn, "books", column) {
+            conn.execute_batch(ddl)
+                .map_err(|e| format!("Migration failed ({}): {}", column, e))?;
+        }
+    }
+
+    // Migration 5: Cover colors for bookshelf views (see palette.rs)
+    for (column, ddl) in [
+        ("cover_color", "ALTER TABLE books ADD COLUMN cover_color TEXT"),
+        ("cover_accent", "ALTER TABLE books ADD COLUMN cover_accent TEXT"),
+        ("cover_hue", "ALTER TABLE books ADD COLUMN cover_hue INTEGER"),
+    ] {
+        if !column_exists(conn, "books", column) {
+            conn.execute_batch(ddl)
+                .map_err(|e| format!("Migration failed ({}): {}", column, e))?;
+        }
+    }
+
+    // Migration 6: Stored BEq. Triggers keep per-type read counts and page
+    // totals current and flag the type stale; beq::sync recomputes the
+    // `beq` column of stale types before it is read.
+    let had_type_stats = table_exists(conn, "book_type_stats");
+    conn.execute_batch(&format!(
+        "CREATE TABLE IF NOT EXISTS book_type_stats (
+            book_type TEXT PRIMARY KEY,
+            read_count INTEGER NOT NULL DEFAULT 0,
+            total_pages INTEGER NOT NULL DEFAULT 0,
+            stale INTEGER NOT NULL DEFAULT 1
+        );
+        CREATE INDEX IF NOT EXISTS idx_books_beq ON books(beq);
+        CREATE INDEX IF NOT EXISTS idx_books_book_type ON books(book_type);
+
+        CREATE TRIGGER IF NOT EXISTS books_type_stats_insert AFTER INSERT ON books BEGIN
+            INSERT OR IGNORE INTO book_type_stats (book_type) VALUES (NEW.book_type);
+            UPDATE book_type_stats SET read_count = read_count + {new_read}, total_pages = total_pages + {new_pages}, stale = 1
+            WHERE book_type = NEW.book_type;
+        END;
+        CREATE TRIGGER IF NOT EXISTS books_type_stats_delete AFTER DELETE ON books BEGIN
+            UPDATE book_type_stats SET read_count = read_count - {old_read}, total_pages = total_pages - {old_pages}, stale = 1
+            WHERE book_type = OLD.book_type;
+        END;
+        CREATE TRIGGER IF NOT EXISTS books_type_stats_update
+        AFTER UPDATE OF pages, exclusive_shelf, deleted_at, book_type ON books BEGIN
+            UPDATE book_type_stats SET read_count = read_count - {old_read}, total_pages = total_pages - {old_pages}, stale = 1
+            WHERE book_type = OLD.book_type;
+            INSERT OR IGNORE INTO book_type_stats (book_type) VALUES (NEW.book_type);
+            UPDATE book_type_stats SET read_count = read_count + {new_read}, total_pages = total_pages + {new_pages}, stale = 1
+            WHERE book_type = NEW.book_type;
+        END;",
+        new_read = counts_toward_average("NEW", "1"),
+        new_pages = counts_toward_average("NEW", "NEW.pages"),
+        old_read = counts_toward_average("OLD", "1"),
+        old_pages = counts_toward_average("OLD", "OLD.pages"),
+    ))
+    .map_err(|e| format!("Migration failed (book_type_stats): {}", e))?;
+    if !had_type_stats {
+        conn.execute_batch(&format!(
+            "INSERT INTO book_type_stats (book_type, read_count, total_pages)
+             SELECT book_type, sum({read}), sum({pages}) FROM books GROUP BY book_type",
+            read = counts_toward_average("books", "1"),
+            pages = counts_toward_average("books", "books.pages"),
+        ))
+        .map_err(|e| format!("Migration failed (book_type_stats): {}", e))?;
+    }
+
+    // Migration 7: Every type in use is a configured type
+    conn.execute_batch(
+        "INSERT OR IGNORE INTO book_types (name, label) SELECT DISTINCT book_type, book_type FROM books",
+    )
+    .map_err(|e| format!("Migration failed (book_types): {}", e))?;
+
+    // Migration 8: When reading started, for pace and streak stats. Moving a
+    // book to currently-reading stamps today unless a date is already set.
+    if !column_exists(conn, "books", "date_started") {
+        conn.execute_batch("ALTER TABLE books ADD COLUMN date_started TEXT")
+            .map_err(|e| format!("Migration failed (date_started): {}", e))?;
+    }
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS books_date_started_insert AFTER INSERT ON books
+         WHEN NEW.exclusive_shelf = 'currently-reading' AND NEW.date_started IS NULL
+         BEGIN
+             UPDATE books SET date_started = date('now', 'localtime') WHERE id = NEW.id;
+         END;
+         CREATE TRIGGER IF NOT EXISTS books_date_started_update AFTER UPDATE OF exclusive_shelf ON books
+         WHEN NEW.exclusive_shelf = 'currently-reading' AND OLD.exclusive_shelf IS NOT 'currently-reading'
+             AND NEW.date_started IS NULL
+         BEGIN
+             UPDATE books SET date_started = date('now', 'localtime') WHERE id = NEW.id;
+         END;",
+    )
+    .map_err(|e| format!("Migration failed (date_started): {}", e))?;
+
+    // Migration 9: Per-book revision counter for optimistic concurrency.
+    // Every write that stamps `updated_at` bumps it, so edits within the same
+    // second (which `updated_at` can't tell apart) still conflict.
+    if !column_exists(conn, "books", "revision") {
+        conn.execute_batch("ALTER TABLE books ADD COLUMN revision INTEGER NOT NULL DEFAULT 0")
+            .map_err(|e| format!("Migration failed (revision): {}", e))?;
+    }
+    conn.execute_batch(
+        "CREATE TRIGGER IF NOT EXISTS books_revision_update AFTER UPDATE OF updated_at ON books
+         BEGIN
+             UPDATE books SET revision = OLD.revision + 1 WHERE id = NEW.id;
+         END;",
+    )
+    .map_err(|e| format!("Migration failed (revision): {}", e))?;
+
+    // Migration 10: Per-type look of generated placeholder covers (see
+    // placeholder.rs). Graphic novels keep the bold style they always had.
+    if !column_exists(conn, "book_types", "cover_style") {
+        conn.execute_batch(
+            "ALTER TABLE book_types ADD COLUMN cover_style TEXT;
+             UPDATE book_types SET cover_style = 'bold' WHERE name = 'graphic_novel';",
+        )
+        .map_err(|e| format!("Migration failed (cover_style): {}", e))?;
+    }
+
     Ok(())
 }
+
+/// `value` when `row` is a read, non-trashed book with a page count (what
+/// the per-type average is taken over), else 0.
+fn counts_toward_average(row: &str, value: &str) -> String {
+    format!(
+        "(CASE WHEN {row}.exclusive_shelf = 'read' AND coalesce({row}.pages, 0) > 0 AND {row}.deleted_at IS NULL \
+         THEN {value} ELSE 0 END)"
+    )
+}
+
+fn table_exists(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |r| r.get::<_, i64>(0),
+    )
+    .unwrap_or(0) > 0
+}
+
+fn column_exists(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?1) WHERE name = ?2",
+        params![table, column],
+        |r| r.get::<_, i64>(0),
+    )
+    .unwrap_or(0) > 0
+}
@@ -0,0 +1,257 @@
+use crate::book_types;
+use crate::commands::stats::{read_in, round2};
+use crate::models::{Goal, GoalBook, GoalProgress};
+use rusqlite::{params, params_from_iter, Connection};
+use std::collections::HashSet;
+
+const PERIODS: &[&str] = &["year", "month"];
+const METRICS: &[&str] = &["books", "pages", "beq"];
+const DISTINCT_FIELDS: &[&str] = &["author", "publisher", "language", "shelf"];
+/// Separates shelf names in the per-book `group_concat`
+const SHELF_SEP: char = '\u{1f}';
+
+/// Check a goal before it is stored, returning it with its text fields
+/// trimmed and empty options cleared.
+pub fn validate(conn: &Connection, goal: &Goal) -> Result<Goal, String> {
+    let mut goal = goal.clone();
+    goal.name = goal.name.trim().to_string();
+    if goal.name.is_empty() {
+        return Err("Goal name can't be empty".to_string());
+    }
+    if !PERIODS.contains(&goal.period.as_str()) {
+        return Err(format!("Unknown goal period: {}", goal.period));
+    }
+    goal.month = match (goal.period.as_str(), goal.month) {
+        ("month", Some(m)) if (1..=12).contains(&m) => Some(m),
+        ("month", _) => return Err("Monthly goals need a month from 1 to 12".to_string()),
+        _ => None,
+    };
+    if !METRICS.contains(&goal.metric.as_str()) {
+        return Err(format!("Unknown goal metric: {}", goal.metric));
+    }
+    if !(goal.target.is_finite() && goal.target > 0.0) {
+        return Err("Goal target must be a positive number".to_string());
+    }
+
+    goal.filter = goal.filter.map(|f| f.trim().to_string()).filter(|f| !f.is_empty());
+    if let Some(ref filter) = goal.filter {
+        for (key, value) in parse_filter(filter)? {
+            if key == "type" {
+                book_types::validate(conn, &value)?;
+            }
+        }
+    }
+
+    goal.distinct_by = goal.distinct_by.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    if let Some(ref field) = goal.distinct_by {
+        let base = field.split(':').next().unwrap_or_default();
+        if !DISTINCT_FIELDS.contains(&base) || (base != "shelf" && field.contains(':')) {
+            return Err(format!(
+                "Can't count distinct '{}': use author, publisher, language, shelf or shelf:<prefix>",
+                field
+            ));
+        }
+        if goal.metric != "books" {
+            return Err("Distinct-value challenges count books".to_string());
+        }
+    }
+    Ok(goal)
+}
+
+/// Split `key:value, key:value` into lower-case keys and trimmed values.
+fn parse_filter(filter: &str) -> Result<Vec<(String, String)>, String> {
+    filter
+        .split(',')
+        .map(str::trim)
+        .filter(|term| !term.is_empty())
+        .map(|term| {
+            let (key, value) = term
+                .split_once(':')
+                .ok_or_else(|| format!("Filter term '{}' should look like key:value", term))?;
+            let key = key.trim().to_lowercase();
+            let value = value.trim().to_string();
+            if !matches!(key.as_str(), "shelf" | "type" | "author" | "publisher" | "language") {
+                return Err(format!("Unknown filter '{}'", key));
+            }
+            if value.is_empty() {
+                return Err(format!("Filter '{}' needs a value", key));
+            }
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Every stored goal, newest period first.
+pub fn list(conn: &Connection, year: Option<i64>) -> Result<Vec<Goal>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, name, period, year, month, metric, target, filter, distinct_by FROM goals
+             WHERE ?1 IS NULL OR year = ?1
+             ORDER BY year DESC, month IS NOT NULL, month DESC, id",
+        )
+        .map_err(|e| e.to_string())?;
+    let goals = stmt
+        .query_map(params![year], |r| {
+            Ok(Goal {
+                id: r.get(0)?,
+                name: r.get(1)?,
+                period: r.get(2)?,
+                year: r.get(3)?,
+                month: r.get(4)?,
+                metric: r.get(5)?,
+                target: r.get(6)?,
+                filter: r.get(7)?,
+                distinct_by: r.get(8)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(goals)
+}
+
+/// Progress of `goal` as of `today` (year, month, day). Call `beq::sync`
+/// first so BEq goals see current values.
+pub fn progress(conn: &Connection, goal: &Goal, today: (i64, i64, i64)) -> Result<GoalProgress, String> {
+    let mut conditions = vec![read_in(goal.year, goal.month)];
+    let mut values: Vec<String> = Vec::new();
+    for (key, value) in parse_filter(goal.filter.as_deref().unwrap_or(""))? {
+        values.push(value);
+        let n = values.len();
+        conditions.push(match key.as_str() {
+            "shelf" => format!(
+                "EXISTS (SELECT 1 FROM book_shelves bs JOIN shelves s ON s.id = bs.shelf_id
+                 WHERE bs.book_id = books.id AND lower(s.name) = lower(?{}))",
+                n
+            ),
+            "type" => format!("books.book_type = ?{}", n),
+            "language" => format!("lower(books.language) = lower(?{})", n),
+            // Author and publisher match anywhere in the name
+            _ => format!("books.{} LIKE '%' || ?{} || '%'", key, n),
+        });
+    }
+
+    let distinct = goal.distinct_by.as_deref();
+    let distinct_expr = match distinct {
+        Some(field) if field.starts_with("shelf") => format!(
+            "(SELECT group_concat(s.name, char({})) FROM book_shelves bs JOIN shelves s ON s.id = bs.shelf_id
+              WHERE bs.book_id = books.id)",
+            SHELF_SEP as u32
+        ),
+        Some(field) => format!("books.{}", field),
+        None => "NULL".to_string(),
+    };
+    let sql = format!(
+        "SELECT books.id, books.title, books.author, books.date_read, books.pages, ROUND(books.beq, 2), {}
+         FROM books WHERE {}
+         ORDER BY books.date_read IS NULL, replace(books.date_read, '-', '/'), books.id",
+        distinct_expr,
+        conditions.join(" AND ")
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows: Vec<(GoalBook, Option<String>)> = stmt
+        .query_map(params_from_iter(values.iter()), |r| {
+            Ok((
+                GoalBook {
+                    id: r.get(0)?,
+                    title: r.get(1)?,
+                    author: r.get(2)?,
+                    date_read: r.get(3)?,
+                    pages: r.get(4)?,
+                    beq: r.get(5)?,
+                    counted_as: None,
+                },
+                r.get(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let books: Vec<GoalBook> = match distinct {
+        // Each book counts for the first value it adds to the set
+        Some(field) => {
+            let prefix = field.strip_prefix("shelf:").map(str::to_lowercase);
+            let mut seen = HashSet::new();
+            rows.into_iter()
+                .filter_map(|(mut book, raw)| {
+                    let raw = raw.unwrap_or_default();
+                    let candidates: Vec<&str> = if field.starts_with("shelf") {
+                        raw.split(SHELF_SEP).collect()
+                    } else {
+                        vec![raw.as_str()]
+                    };
+                    let value = candidates.into_iter().map(str::trim).find(|v| {
+                        let key = v.to_lowercase();
+                        !v.is_empty()
+                            && prefix.as_deref().is_none_or(|p| key.starts_with(p))
+                            && !seen.contains(&key)
+                    })?;
+                    seen.insert(value.to_lowercase());
+                    book.counted_as = Some(value.to_string());
+                    Some(book)
+                })
+                .collect()
+        }
+        None => rows.into_iter().map(|(book, _)| book).collect(),
+    };
+
+    let current = match goal.metric.as_str() {
+        "pages" => books.iter().filter_map(|b| b.pages).sum::<i64>() as f64,
+        "beq" => books.iter().filter_map(|b| b.beq).sum(),
+        _ => books.len() as f64,
+    };
+    let elapsed = elapsed_fraction(goal, today);
+    let expected = goal.target * elapsed;
+    let pace = if current >= goal.target {
+        "complete"
+    } else if elapsed == 0.0 {
+        "upcoming"
+    } else if elapsed >= 1.0 {
+        "missed"
+    } else if current >= expected {
+        "ahead"
+    } else {
+        "behind"
+    };
+
+    Ok(GoalProgress {
+        goal: goal.clone(),
+        current: round2(current),
+        percent: round2((current / goal.target * 100.0).min(100.0)),
+        expected: round2(expected),
+        projected: round2(if elapsed > 0.0 { current / elapsed } else { 0.0 }),
+        pace: pace.to_string(),
+        books,
+    })
+}
+
+/// Share of the goal's period that has passed by the end of `today`:
+/// 0 before it starts, 1 once it is over.
+fn elapsed_fraction(goal: &Goal, (year, month, day): (i64, i64, i64)) -> f64 {
+    match goal.month {
+        Some(goal_month) => match (goal.year, goal_month).cmp(&(year, month)) {
+            std::cmp::Ordering::Less => 1.0,
+            std::cmp::Ordering::Greater => 0.0,
+            std::cmp::Ordering::Equal => day as f64 / days_in_month(year, month) as f64,
+        },
+        None => match goal.year.cmp(&year) {
+            std::cmp::Ordering::Less => 1.0,
+            std::cmp::Ordering::Greater => 0.0,
+            std::cmp::Ordering::Equal => {
+                let day_of_year: i64 = (1..month).map(|m| days_in_month(year, m)).sum::<i64>() + day;
+                let days: i64 = (1..=12).map(|m| days_in_month(year, m)).sum();
+                day_of_year as f64 / days as f64
+            }
+        },
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
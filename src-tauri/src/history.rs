@@ -0,0 +1,299 @@
+use crate::commands::books::link_shelves;
+use crate::models::{FieldChange, HistoryEntry};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+
+/// Book columns whose changes are recorded. `shelves` is tracked separately
+/// as a pseudo-field holding the sorted list of shelf names.
+pub const TRACKED_FIELDS: &[&str] = &[
+    "goodreads_id", "title", "author", "author_sort", "additional_authors",
+    "isbn", "isbn13", "my_rating", "average_rating", "publisher", "binding",
+    "pages", "edition_published", "year_published", "date_read", "year_read",
+    "date_added", "exclusive_shelf", "my_review", "read_count", "owned_copies",
+    "cover_url", "open_library_key", "book_type", "deleted_at",
+    "subjects", "description", "language", "series",
+    "cover_path", "cover_hash", "cover_cached_from", "cover_color", "cover_accent", "cover_hue",
+    "date_started",
+];
+
+const SHELVES_FIELD: &str = "shelves";
+
+/// Field name → value for one book at a point in time.
+pub type Snapshot = Map<String, Value>;
+
+/// Read the current tracked state of a book, or `None` if the row is gone.
+pub fn snapshot(conn: &Connection, book_id: i64) -> Result<Option<Snapshot>, String> {
+    let sql = format!("SELECT {} FROM books WHERE id = ?1", TRACKED_FIELDS.join(", "));
+    let row: Option<Vec<SqlValue>> = conn
+        .query_row(&sql, params![book_id], |row| {
+            (0..TRACKED_FIELDS.len()).map(|i| row.get::<_, SqlValue>(i)).collect()
+        })
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(values) = row else {
+        return Ok(None);
+    };
+
+    let mut snap: Snapshot = TRACKED_FIELDS
+        .iter()
+        .zip(values)
+        .map(|(field, value)| (field.to_string(), sql_to_json(value)))
+        .collect();
+
+    let mut stmt = conn
+        .prepare("SELECT s.name FROM book_shelves bs JOIN shelves s ON bs.shelf_id = s.id WHERE bs.book_id = ?1 ORDER BY s.name")
+        .map_err(|e| e.to_string())?;
+    let shelves: Vec<Value> = stmt
+        .query_map(params![book_id], |r| r.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .map(Value::String)
+        .collect();
+    snap.insert(SHELVES_FIELD.to_string(), Value::Array(shelves));
+
+    Ok(Some(snap))
+}
+
+/// Diff `before` against the book's current state and store the result as a
+/// new revision. Nothing is written when no tracked field changed.
+pub fn record(
+    conn: &Connection,
+    book_id: i64,
+    action: &str,
+    before: Option<&Snapshot>,
+    note: Option<&str>,
+) -> Result<(), String> {
+    let after = snapshot(conn, book_id)?.unwrap_or_default();
+    let empty = Snapshot::new();
+    let before = before.unwrap_or(&empty);
+
+    let changes: Vec<FieldChange> = TRACKED_FIELDS
+        .iter()
+        .copied()
+        .chain(std::iter::once(SHELVES_FIELD))
+        .filter_map(|field| {
+            let old = before.get(field).cloned().unwrap_or(Value::Null);
+            let new = after.get(field).cloned().unwrap_or(Value::Null);
+            (old != new).then(|| FieldChange {
+                field: field.to_string(),
+                old,
+                new,
+            })
+        })
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let changes_json = serde_json::to_string(&changes).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO book_history (book_id, revision, action, changes, note)
+         VALUES (?1, (SELECT coalesce(max(revision), 0) + 1 FROM book_history WHERE book_id = ?1), ?2, ?3, ?4)",
+        params![book_id, action, changes_json, note],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Timeline for one book, newest revision first.
+pub fn timeline(conn: &Connection, book_id: i64) -> Result<Vec<HistoryEntry>, String> {
+    let mut stmt = conn
+        .prepare(&format!(
+            "{} WHERE book_id = ?1 ORDER BY revision DESC",
+            ENTRY_SELECT
+        ))
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<HistoryEntry> = stmt
+        .query_map(params![book_id], row_to_entry)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(entries)
+}
+
+/// Put a book back into the state it had right after `revision`.
+pub fn revert_to(conn: &Connection, book_id: i64, revision: i64) -> Result<(), String> {
+    let before = snapshot(conn, book_id)?
+        .ok_or_else(|| format!("Book {} no longer exists", book_id))?;
+
+    // Unwind every later revision from the current state, newest first
+    let entries = timeline(conn, book_id)?;
+    if !entries.iter().any(|e| e.revision == revision) {
+        return Err(format!("Book {} has no revision {}", book_id, revision));
+    }
+    let mut state = before.clone();
+    for entry in entries.iter().filter(|e| e.revision > revision) {
+        for change in &entry.changes {
+            state.insert(change.field.clone(), change.old.clone());
+        }
+    }
+
+    apply(conn, book_id, &state)?;
+    record(
+        conn,
+        book_id,
+        "revert",
+        Some(&before),
+        Some(&format!("Reverted to revision {}", revision)),
+    )
+}
+
+/// Undo the most recent change across all books that hasn't been undone yet.
+/// Repeated calls keep stepping further back. Entries for books that have
+/// since been purged are skipped. Returns the undone entry.
+pub fn undo_last(conn: &Connection) -> Result<Option<HistoryEntry>, String> {
+    let entry = conn
+        .query_row(
+            &format!(
+                "{} WHERE undone = 0 AND action != 'undo' AND book_id IN (SELECT id FROM books)
+                 ORDER BY id DESC LIMIT 1",
+                ENTRY_SELECT
+            ),
+            [],
+            row_to_entry,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+
+    let before = snapshot(conn, entry.book_id)?
+        .ok_or_else(|| format!("Book {} no longer exists", entry.book_id))?;
+
+    if entry.action == "create" {
+        // Creating has no prior state to return to; send the book to the trash
+        conn.execute(
+            "UPDATE books SET deleted_at = datetime('now') WHERE id = ?1",
+            params![entry.book_id],
+        )
+        .map_err(|e| e.to_string())?;
+    } else {
+        let state: Snapshot = entry
+            .changes
+            .iter()
+            .map(|c| (c.field.clone(), c.old.clone()))
+            .collect();
+        apply(conn, entry.book_id, &state)?;
+    }
+
+    conn.execute("UPDATE book_history SET undone = 1 WHERE id = ?1", params![entry.id])
+        .map_err(|e| e.to_string())?;
+    record(
+        conn,
+        entry.book_id,
+        "undo",
+        Some(&before),
+        Some(&format!("Undid revision {}", entry.revision)),
+    )?;
+
+    Ok(Some(entry))
+}
+
+/// Write the given field values (and shelf list, if present) onto a book.
+fn apply(conn: &Connection, book_id: i64, state: &Snapshot) -> Result<(), String> {
+    let fields: Vec<&str> = TRACKED_FIELDS
+        .iter()
+        .copied()
+        .filter(|f| state.contains_key(*f))
+        .collect();
+
+    if !fields.is_empty() {
+        let assignments: Vec<String> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| format!("{} = ?{}", f, i + 1))
+            .collect();
+        let sql = format!(
+            "UPDATE books SET {}, updated_at = datetime('now') WHERE id = ?{}",
+            assignments.join(", "),
+            fields.len() + 1
+        );
+        let mut values: Vec<SqlValue> = fields.iter().map(|f| json_to_sql(&state[*f])).collect();
+        values.push(SqlValue::Integer(book_id));
+        conn.execute(&sql, params_from_iter(values))
+            .map_err(|e| e.to_string())?;
+    }
+
+    if let Some(Value::Array(shelves)) = state.get(SHELVES_FIELD) {
+        let names: Vec<String> = shelves
+            .iter()
+            .filter_map(|v| v.as_str().map(str::to_string))
+            .collect();
+        conn.execute("DELETE FROM book_shelves WHERE book_id = ?1", params![book_id])
+            .map_err(|e| e.to_string())?;
+        link_shelves(conn, book_id, &names)?;
+    }
+
+    Ok(())
+}
+
+const ENTRY_SELECT: &str =
+    "SELECT id, book_id, revision, action, changes, note, undone, created_at FROM book_history";
+
+fn row_to_entry(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let changes: String = row.get(4)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        book_id: row.get(1)?,
+        revision: row.get(2)?,
+        action: row.get(3)?,
+        changes: serde_json::from_str(&changes).unwrap_or_default(),
+        note: row.get(5)?,
+        undone: row.get::<_, i64>(6)? != 0,
+        created_at: row.get(7)?,
+    })
+}
+
+fn sql_to_json(value: SqlValue) -> Value {
+    match value {
+        SqlValue::Null | SqlValue::Blob(_) => Value::Null,
+        SqlValue::Integer(i) => Value::from(i),
+        SqlValue::Real(f) => Value::from(f),
+        SqlValue::Text(s) => Value::String(s),
+    }
+}
+
+fn json_to_sql(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => n
+            .as_i64()
+            .map(SqlValue::Integer)
+            .unwrap_or_else(|| SqlValue::Real(n.as_f64().unwrap_or_default())),
+        Value::String(s) => SqlValue::Text(s.clone()),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::apply_schema;
+
+    #[test]
+    fn revert_clears_fields_set_after_the_target() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_schema(&conn).unwrap();
+        conn.execute("INSERT INTO books (title, author) VALUES ('Dune', 'Frank Herbert')", []).unwrap();
+        let id = conn.last_insert_rowid();
+        record(&conn, id, "create", None, None).unwrap();
+
+        let before = snapshot(&conn, id).unwrap();
+        conn.execute(
+            "UPDATE books SET title = 'Dune (Deluxe)', series = 'Dune Chronicles', date_started = '2024-01-02' WHERE id = ?1",
+            params![id],
+        )
+        .unwrap();
+        record(&conn, id, "update", before.as_ref(), None).unwrap();
+
+        revert_to(&conn, id, 1).unwrap();
+        let state = snapshot(&conn, id).unwrap().unwrap();
+        assert_eq!(state["title"], "Dune");
+        assert_eq!(state["series"], Value::Null);
+        assert_eq!(state["date_started"], Value::Null);
+        assert_eq!(timeline(&conn, id).unwrap()[0].action, "revert");
+    }
+}
@@ -0,0 +1,269 @@
+/// A book's ISBN pair after normalization, with the missing half derived
+/// from the other where possible.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Isbns {
+    pub isbn: Option<String>,
+    pub isbn13: Option<String>,
+}
+
+/// Strip hyphens, spaces, Goodreads' `="..."` wrapping and anything else
+/// that isn't a digit, upper-casing an `x` check digit.
+pub fn normalize(raw: &str) -> String {
+    raw.chars()
+        .filter(|c| c.is_ascii_digit() || *c == 'x' || *c == 'X')
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+pub fn is_valid_isbn10(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || !bytes[..9].iter().all(u8::is_ascii_digit) {
+        return false;
+    }
+    let check = match bytes[9] {
+        b'X' => 10,
+        b if b.is_ascii_digit() => (b - b'0') as u32,
+        _ => return false,
+    };
+    let sum: u32 = bytes[..9]
+        .iter()
+        .enumerate()
+        .map(|(i, b)| (10 - i as u32) * (b - b'0') as u32)
+        .sum();
+    (sum + check).is_multiple_of(11)
+}
+
+pub fn is_valid_isbn13(s: &str) -> bool {
+    let bytes = s.as_bytes();
+    if bytes.len() != 13 || !bytes.iter().all(u8::is_ascii_digit) {
+        return false;
+    }
+    if !(s.starts_with("978") || s.starts_with("979")) {
+        return false;
+    }
+    isbn13_check_digit(&s[..12]) == bytes[12] - b'0'
+}
+
+/// Convert a valid ISBN-10 to its `978` ISBN-13.
+pub fn isbn10_to_13(isbn10: &str) -> Option<String> {
+    if !is_valid_isbn10(isbn10) {
+        return None;
+    }
+    let body = format!("978{}", &isbn10[..9]);
+    let check = isbn13_check_digit(&body);
+    Some(format!("{}{}", body, check))
+}
+
+/// Convert a valid ISBN-13 back to ISBN-10. Only `978` numbers have one.
+pub fn isbn13_to_10(isbn13: &str) -> Option<String> {
+    if !is_valid_isbn13(isbn13) || !isbn13.starts_with("978") {
+        return None;
+    }
+    let body = &isbn13[3..12];
+    let sum: u32 = body
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| (10 - i as u32) * (b - b'0') as u32)
+        .sum();
+    let check = match (11 - sum % 11) % 11 {
+        10 => 'X',
+        d => char::from(b'0' + d as u8),
+    };
+    Some(format!("{}{}", body, check))
+}
+
+/// Normalize and validate one ISBN of either length. Blank input is `None`.
+pub fn parse(raw: &str) -> Result<Option<String>, String> {
+    let value = normalize(raw);
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let valid = match value.len() {
+        10 => is_valid_isbn10(&value),
+        13 => is_valid_isbn13(&value),
+        _ => false,
+    };
+    if valid {
+        Ok(Some(value))
+    } else {
+        Err(format!("Invalid ISBN: {}", raw.trim()))
+    }
+}
+
+/// Normalize an ISBN/ISBN-13 pair as entered. Values are sorted into the
+/// right slot by length (an ISBN-13 typed into the ISBN field is accepted),
+/// checksums are verified, an ISBN-10 and ISBN-13 must name the same book,
+/// and a missing half is derived from the other.
+pub fn resolve(isbn: Option<&str>, isbn13: Option<&str>) -> Result<Isbns, String> {
+    let mut ten: Option<String> = None;
+    let mut thirteen: Option<String> = None;
+
+    for raw in [isbn, isbn13].into_iter().flatten() {
+        let Some(value) = parse(raw)? else {
+            continue;
+        };
+        let slot = if value.len() == 10 { &mut ten } else { &mut thirteen };
+        match slot {
+            Some(existing) if *existing != value => {
+                return Err(format!("Conflicting ISBNs: {} and {}", existing, value));
+            }
+            _ => *slot = Some(value),
+        }
+    }
+
+    // Both halves given: they must be the same book
+    if let (Some(ten), Some(thirteen)) = (&ten, &thirteen) {
+        if isbn10_to_13(ten).as_ref() != Some(thirteen) {
+            return Err(format!("Conflicting ISBNs: {} and {}", ten, thirteen));
+        }
+    }
+
+    if thirteen.is_none() {
+        thirteen = ten.as_deref().and_then(isbn10_to_13);
+    }
+    if ten.is_none() {
+        ten = thirteen.as_deref().and_then(isbn13_to_10);
+    }
+
+    Ok(Isbns { isbn: ten, isbn13: thirteen })
+}
+
+/// Like `resolve`, but drops values that fail validation instead of
+/// erroring. Returns the cleaned pair and the raw values that were dropped.
+pub fn resolve_lenient(isbn: Option<&str>, isbn13: Option<&str>) -> (Isbns, Vec<String>) {
+    if let Ok(resolved) = resolve(isbn, isbn13) {
+        return (resolved, Vec::new());
+    }
+
+    let mut rejected = Vec::new();
+    let mut keep = |raw: Option<&str>| -> Option<String> {
+        let raw = raw?;
+        match parse(raw) {
+            Ok(value) => value,
+            Err(_) => {
+                rejected.push(raw.trim().to_string());
+                None
+            }
+        }
+    };
+    let first = keep(isbn);
+    let second = keep(isbn13);
+
+    // Still conflicting after dropping invalid values: prefer the ISBN-13 column
+    let resolved = match resolve(first.as_deref(), second.as_deref()) {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            rejected.extend(first);
+            resolve(None, second.as_deref()).unwrap_or_default()
+        }
+    };
+    (resolved, rejected)
+}
+
+fn isbn13_check_digit(first12: &str) -> u8 {
+    let sum: u32 = first12
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum();
+    ((10 - sum % 10) % 10) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(isbn: Option<&str>, isbn13: Option<&str>) -> Isbns {
+        Isbns { isbn: isbn.map(str::to_string), isbn13: isbn13.map(str::to_string) }
+    }
+
+    #[test]
+    fn checksums() {
+        let cases = [
+            ("0306406152", true),
+            ("0306406153", false),
+            ("080442957X", true),
+            ("0804429579", false),
+            // Only the last character may be X
+            ("08044295X7", false),
+            ("9780306406157", true),
+            ("9780306406158", false),
+            ("9791032305690", true),
+            // Right checksum, but not a Bookland prefix
+            ("9770306406153", false),
+            ("030640615", false),
+            ("97803064061577", false),
+            ("", false),
+        ];
+        for (value, valid) in cases {
+            let actual = match value.len() {
+                10 => is_valid_isbn10(value),
+                _ => is_valid_isbn13(value),
+            };
+            assert_eq!(actual, valid, "{}", value);
+        }
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(isbn10_to_13("0306406152").as_deref(), Some("9780306406157"));
+        assert_eq!(isbn10_to_13("080442957X").as_deref(), Some("9780804429573"));
+        assert_eq!(isbn10_to_13("0306406153"), None);
+        assert_eq!(isbn13_to_10("9780306406157").as_deref(), Some("0306406152"));
+        assert_eq!(isbn13_to_10("9780804429573").as_deref(), Some("080442957X"));
+        // 979 numbers have no ISBN-10
+        assert_eq!(isbn13_to_10("9791032305690"), None);
+    }
+
+    #[test]
+    fn parse_normalizes() {
+        assert_eq!(parse("0-8044-2957-x"), Ok(Some("080442957X".to_string())));
+        assert_eq!(parse("=\"9780306406157\""), Ok(Some("9780306406157".to_string())));
+        assert_eq!(parse("  "), Ok(None));
+        assert!(parse("978-0-306-40615-8").is_err());
+    }
+
+    #[test]
+    fn resolve_strict() {
+        let cases = [
+            ((Some("0306406152"), None), pair(Some("0306406152"), Some("9780306406157"))),
+            ((None, Some("978-0-8044-2957-3")), pair(Some("080442957X"), Some("9780804429573"))),
+            // An ISBN-13 typed into the ISBN field goes to the right slot
+            ((Some("9791032305690"), None), pair(None, Some("9791032305690"))),
+            ((Some("0306406152"), Some("9780306406157")), pair(Some("0306406152"), Some("9780306406157"))),
+            ((Some(""), Some("")), pair(None, None)),
+        ];
+        for ((isbn, isbn13), expected) in cases {
+            assert_eq!(resolve(isbn, isbn13), Ok(expected), "{:?} {:?}", isbn, isbn13);
+        }
+        assert!(resolve(Some("0306406153"), None).is_err());
+        // A valid ISBN-10 and ISBN-13 for different books
+        assert!(resolve(Some("080442957X"), Some("9780306406157")).is_err());
+        assert!(resolve(Some("0306406152"), Some("9791032305690")).is_err());
+        assert_eq!(
+            resolve(Some("9780306406157"), Some("9791032305690")),
+            Err("Conflicting ISBNs: 9780306406157 and 9791032305690".to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_lenient_drops_bad_values() {
+        // Invalid ISBN column: keep the valid ISBN-13 and derive the 10
+        assert_eq!(
+            resolve_lenient(Some("0306406153"), Some("9780306406157")),
+            (pair(Some("0306406152"), Some("9780306406157")), vec!["0306406153".to_string()])
+        );
+        // Both valid but conflicting: the ISBN-13 column wins
+        assert_eq!(
+            resolve_lenient(Some("080442957X"), Some("9780306406157")),
+            (pair(Some("0306406152"), Some("9780306406157")), vec!["080442957X".to_string()])
+        );
+        assert_eq!(
+            resolve_lenient(Some("0306406X52"), Some("12345")),
+            (pair(None, None), vec!["0306406X52".to_string(), "12345".to_string()])
+        );
+        // Nothing ISBN-like at all counts as blank, not invalid
+        assert_eq!(resolve_lenient(Some("n/a"), None), (pair(None, None), vec![]));
+        assert_eq!(resolve_lenient(Some("0306406152"), None), (pair(Some("0306406152"), Some("9780306406157")), vec![]));
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Tracks long-running background jobs by name so they can be cancelled and
+/// so the same job isn't started twice.
+#[derive(Default)]
+pub struct JobRegistry {
+    running: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl JobRegistry {
+    /// Register a job and return its cancellation flag.
+    pub fn start(&self, job: &str) -> Result<Arc<AtomicBool>, String> {
+        let mut running = self.running.lock().map_err(|e| e.to_string())?;
+        if running.contains_key(job) {
+            return Err(format!("{} is already running", job));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        running.insert(job.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    /// Ask a running job to stop. Returns false if it wasn't running.
+    pub fn cancel(&self, job: &str) -> bool {
+        let Ok(running) = self.running.lock() else {
+            return false;
+        };
+        match running.get(job) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn finish(&self, job: &str) {
+        if let Ok(mut running) = self.running.lock() {
+            running.remove(job);
+        }
+    }
+
+    pub fn running(&self) -> Vec<String> {
+        self.running
+            .lock()
+            .map(|r| r.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
@@ -1,6 +1,19 @@
+mod backup;
+mod beq;
+mod book_types;
 mod commands;
+mod cover_cache;
+mod dates;
 mod db;
+mod goals;
+mod history;
+mod isbn;
+mod jobs;
 mod models;
+mod palette;
+mod placeholder;
+mod providers;
+mod year_review;
 
 use db::init_db;
 use tauri::Manager;
@@ -11,16 +24,44 @@ pub fn run() {
         .setup(|app| {
             let db_state = init_db(app.handle())
                 .expect("Failed to initialize database");
+            if let Ok(mut conn) = db_state.conn.lock() {
+                let backed_up = backup::create_backup(&conn, &db_state.data_dir, "startup");
+                db::record_background_result(&conn, backup::LAST_ERROR_KEY, &backed_up);
+                let days = db::get_setting_i64(&conn, "trash_retention_days", commands::trash::DEFAULT_RETENTION_DAYS);
+                let purged = commands::trash::purge_expired(&mut conn, &db_state.data_dir, days);
+                db::record_background_result(&conn, commands::trash::LAST_ERROR_KEY, &purged);
+            }
             app.manage(db_state);
+            app.manage(jobs::JobRegistry::default());
+            app.manage(providers::http_client().expect("Failed to build HTTP client"));
+            backup::spawn_scheduler(app.handle().clone());
             Ok(())
         })
+        .register_uri_scheme_protocol(cover_cache::PROTOCOL, |ctx, request| {
+            let state = ctx.app_handle().state::<db::DbState>();
+            let file = cover_cache::resolve(&state.data_dir, request.uri().path())
+                .and_then(|path| std::fs::read(path).ok());
+            let builder = tauri::http::Response::builder().header("Access-Control-Allow-Origin", "*");
+            match file {
+                Some(bytes) => builder
+                    .header("Content-Type", cover_cache::content_type(&bytes))
+                    .header("Cache-Control", "max-age=31536000, immutable")
+                    .body(bytes),
+                None => builder.status(404).body(Vec::new()),
+            }
+            .expect("Failed to build cover response")
+        })
         .invoke_handler(tauri::generate_handler![
             commands::books::get_books,
             commands::books::get_book,
             commands::books::create_book,
             commands::books::update_book,
+            commands::books::patch_book,
+            commands::books::fix_invalid_isbns,
             commands::books::delete_book,
             commands::books::clear_database,
+            commands::bulk::bulk_edit,
+            commands::bulk::bulk_refetch_metadata,
             commands::shelves::get_shelves,
             commands::shelves::create_shelf,
             commands::shelves::rename_shelf,
@@ -29,10 +70,57 @@ pub fn run() {
             commands::import::auto_detect_columns,
             commands::import::import_csv,
             commands::metadata::lookup_isbn,
+            commands::metadata::get_metadata_providers,
+            commands::metadata::set_metadata_providers,
+            commands::metadata::clear_lookup_cache,
+            commands::metadata_refresh::start_metadata_refresh,
+            commands::metadata_refresh::list_metadata_proposals,
+            commands::metadata_refresh::accept_metadata_proposals,
+            commands::metadata_refresh::reject_metadata_proposals,
+            commands::jobs::cancel_job,
+            commands::jobs::get_running_jobs,
             commands::covers::fix_missing_covers,
             commands::covers::lookup_cover,
+            commands::covers::cache_book_cover,
+            commands::covers::cache_covers,
+            commands::covers::prune_cover_cache,
+            commands::covers::set_custom_cover,
+            commands::covers::get_cover_candidates,
+            commands::covers::block_cover,
+            commands::covers::get_cover_blocklist,
+            commands::covers::unblock_cover,
+            commands::covers::get_cover_validation,
+            commands::covers::generate_placeholder_covers,
+            commands::covers::extract_cover_colors,
             commands::stats::get_stats,
             commands::stats::get_shelf_counts,
+            commands::stats::get_reading_series,
+            commands::stats::get_reading_pace,
+            commands::stats::get_breakdowns,
+            commands::stats::get_rating_analysis,
+            commands::year_review::get_year_review,
+            commands::year_review::export_year_review,
+            commands::goals::get_goals,
+            commands::goals::save_goal,
+            commands::goals::delete_goal,
+            commands::backups::list_backups,
+            commands::backups::create_backup,
+            commands::backups::verify_backup,
+            commands::backups::restore_backup,
+            commands::settings::get_settings,
+            commands::settings::update_setting,
+            commands::settings::get_beq_config,
+            commands::settings::set_beq_config,
+            commands::settings::snapshot_beq_averages,
+            commands::book_types::get_book_types,
+            commands::book_types::save_book_type,
+            commands::book_types::delete_book_type,
+            commands::trash::list_trash,
+            commands::trash::restore_book,
+            commands::trash::purge_trash,
+            commands::history::get_book_history,
+            commands::history::revert_book,
+            commands::history::undo_last_change,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
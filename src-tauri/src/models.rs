@@ -1,4 +1,5 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -31,6 +32,22 @@ pub struct Book {
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
     pub book_type: String,
+    pub deleted_at: Option<String>,
+    pub subjects: Vec<String>,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub series: Option<String>,
+    /// Cached copy of the cover, relative to the app data dir
+    pub cover_path: Option<String>,
+    /// SHA-256 of the cached cover; the key for the `bookcover://` protocol
+    pub cover_hash: Option<String>,
+    /// Dominant and accent colors of the cached cover, as `#rrggbb`
+    pub cover_color: Option<String>,
+    pub cover_accent: Option<String>,
+    /// Set when the book moves to currently-reading, unless already known
+    pub date_started: Option<String>,
+    /// Bumped on every write that touches `updated_at`
+    pub revision: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +100,121 @@ pub struct NewBook {
     pub cover_url: Option<String>,
     pub shelf_names: Option<Vec<String>>,
     pub book_type: Option<String>,
+    pub open_library_key: Option<String>,
+    pub subjects: Option<Vec<String>>,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub series: Option<String>,
+    pub date_started: Option<String>,
+}
+
+/// Partial update for `patch_book`. For nullable columns a missing key leaves
+/// the value alone, `null` clears it, and a value sets it. Non-nullable
+/// columns are only changed when a value is given.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookPatch {
+    #[serde(default, deserialize_with = "nullable")]
+    pub goodreads_id: Option<Option<i64>>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub author_sort: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub additional_authors: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub isbn: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub isbn13: Option<Option<String>>,
+    pub my_rating: Option<i64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub average_rating: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub publisher: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub binding: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub pages: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub edition_published: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub year_published: Option<Option<i64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub date_read: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub date_started: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub year_read: Option<Option<i64>>,
+    pub date_added: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub exclusive_shelf: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub my_review: Option<Option<String>>,
+    pub read_count: Option<i64>,
+    pub owned_copies: Option<i64>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub cover_url: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub open_library_key: Option<Option<String>>,
+    pub book_type: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub subjects: Option<Option<Vec<String>>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub language: Option<Option<String>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub series: Option<Option<String>>,
+    /// Shelf names to link, created if missing
+    pub add_shelves: Option<Vec<String>>,
+    /// Shelf names to unlink; other memberships are left alone
+    pub remove_shelves: Option<Vec<String>>,
+    /// When set, the patch is rejected unless the book's `revision` still matches
+    pub expected_revision: Option<i64>,
+}
+
+/// Distinguishes an explicit `null` (`Some(None)`) from a missing key (`None`).
+fn nullable<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// One operation applied to every book in a `bulk_edit` selection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BulkOperation {
+    SetExclusiveShelf { shelf: String },
+    AddShelves { shelves: Vec<String> },
+    RemoveShelves { shelves: Vec<String> },
+    SetBookType { book_type: String },
+    SetRating { rating: i64 },
+    SetOwnedCopies { copies: i64 },
+    Delete,
+}
+
+/// A book whose stored ISBNs are malformed, invalid or missing a derivable half.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsbnFix {
+    pub book_id: i64,
+    pub title: String,
+    pub old_isbn: Option<String>,
+    pub old_isbn13: Option<String>,
+    pub isbn: Option<String>,
+    pub isbn13: Option<String>,
+    /// Stored values that failed validation and are cleared by the fix
+    pub invalid: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkItemResult {
+    pub id: i64,
+    pub ok: bool,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +230,10 @@ pub struct ImportResult {
     pub imported: i64,
     pub total: i64,
     pub skipped: Vec<String>,
+    /// Imported rows matching a book in the trash, which is left there
+    pub also_in_trash: Vec<String>,
+    /// `"<title>: <raw value>"` for ISBNs dropped because they failed validation
+    pub invalid_isbns: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -106,15 +242,65 @@ pub struct Stats {
     pub total_books: i64,
     pub total_read: i64,
     pub total_beq: f64,
-    pub total_beq_traditional: f64,
-    pub total_beq_graphic_novel: f64,
-    pub avg_pages_traditional: f64,
-    pub avg_pages_graphic_novel: f64,
     pub avg_rating: f64,
     pub books_this_year: i64,
     pub beq_this_year: f64,
     pub by_year: Vec<YearStat>,
     pub rating_dist: Vec<RatingCount>,
+    /// `live`, `snapshot` or `fixed` (see beq.rs)
+    pub beq_mode: String,
+    /// One entry per configured book type
+    pub by_type: Vec<TypeStat>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeStat {
+    pub book_type: String,
+    pub label: String,
+    pub books_read: i64,
+    pub beq: f64,
+    pub avg_pages: f64,
+    /// Pages per BEq under `beq_mode`; `None` in live mode before any book
+    /// of this type is read
+    pub beq_divisor: Option<f64>,
+}
+
+/// A configured book type (see book_types.rs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookType {
+    pub name: String,
+    pub label: String,
+    /// Pages per BEq in fixed mode; `DEFAULT_BASELINE` when unset
+    pub beq_baseline: Option<f64>,
+    /// Binding given to new books of this type that have none
+    pub default_binding: Option<String>,
+    /// Imported "Format"/"Type" values that map to this type
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// Look of generated placeholder covers, one of `placeholder::STYLES`;
+    /// the first when unset
+    #[serde(default)]
+    pub cover_style: Option<String>,
+    #[serde(default)]
+    pub book_count: i64,
+}
+
+/// How BEq is computed, for the settings page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeqConfig {
+    pub mode: String,
+    /// Pages per BEq by book type, used in fixed mode
+    pub baselines: HashMap<String, f64>,
+    /// Averages frozen for snapshot mode
+    pub snapshot: HashMap<String, f64>,
+    pub snapshot_at: Option<String>,
+    /// Current averages of read books, used in live mode
+    pub live_averages: HashMap<String, f64>,
+    /// Used for types without a baseline
+    pub default_baseline: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -125,6 +311,113 @@ pub struct YearStat {
     pub beq: f64,
 }
 
+/// Reading volume over one year, bucketed by month, ISO week or weekday.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingSeries {
+    /// `month`, `week` or `weekday`
+    pub bucket: String,
+    pub year: i64,
+    /// Buckets averaged for the `rolling_*` values (trailing, including the
+    /// bucket itself); `None` for weekdays
+    pub window: Option<i64>,
+    /// Every bucket of the year in order, including empty ones
+    pub points: Vec<SeriesPoint>,
+    /// Books read in the year (by `year_read`) without a full read date in
+    /// that year, left out of `points`
+    pub undated: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeriesPoint {
+    /// `2024-03`, `2024-W09` or `1` (Monday) … `7`
+    pub key: String,
+    pub label: String,
+    pub books: i64,
+    pub pages: i64,
+    pub beq: f64,
+    pub rolling_books: Option<f64>,
+    pub rolling_pages: Option<f64>,
+    pub rolling_beq: Option<f64>,
+}
+
+/// Read books grouped several ways, for one year read or all time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breakdowns {
+    pub year: Option<i64>,
+    /// Top authors and publishers, ordered by `sort_by`
+    pub sort_by: String,
+    pub authors: Vec<BreakdownRow>,
+    pub publishers: Vec<BreakdownRow>,
+    pub shelves: Vec<BreakdownRow>,
+    /// Most read first
+    pub book_types: Vec<BreakdownRow>,
+    /// Every decade from the oldest to the newest, including empty ones
+    pub decades: Vec<BreakdownRow>,
+    /// Fixed page-count ranges, including empty ones
+    pub page_lengths: Vec<BreakdownRow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BreakdownRow {
+    /// Author, publisher, shelf or type name, decade start year, or the
+    /// lower page bound
+    pub key: String,
+    pub label: String,
+    pub books: i64,
+    pub pages: i64,
+    pub beq: f64,
+    /// Over rated books only
+    pub avg_rating: Option<f64>,
+}
+
+/// Streaks and reading speed, from read dates and `date_started`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadingPace {
+    /// Consecutive reading days up to today (or yesterday)
+    pub current_streak: i64,
+    pub longest_streak: i64,
+    pub longest_streak_start: Option<String>,
+    pub longest_streak_end: Option<String>,
+    /// Over finished books with a start date
+    pub avg_days_to_finish: Option<f64>,
+    pub timed_books: i64,
+    pub by_type: Vec<TypePace>,
+    /// Highest pages per day first
+    pub fastest: Vec<TimedRead>,
+    /// Lowest pages per day first
+    pub slowest: Vec<TimedRead>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypePace {
+    pub book_type: String,
+    pub label: String,
+    pub books: i64,
+    pub avg_days: f64,
+    pub pages_per_day: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimedRead {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub book_type: String,
+    pub pages: i64,
+    pub date_started: String,
+    pub date_read: String,
+    /// Calendar days, counting both the start and finish day
+    pub days: i64,
+    pub pages_per_day: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RatingCount {
@@ -139,30 +432,338 @@ pub struct ShelfCount {
     pub count: i64,
 }
 
+/// Result of `lookup_isbn`: a book draft merged from every provider that
+/// knew the ISBN, plus which provider supplied each field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IsbnLookup {
+    pub draft: NewBook,
+    /// camelCase field name → provider id
+    pub sources: std::collections::HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub reason: String,
+    pub created_at: String,
+    pub size_bytes: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct OpenLibraryBookData {
+pub struct BackupVerification {
+    pub file_name: String,
+    pub ok: bool,
+    pub messages: Vec<String>,
+    pub book_count: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub book_id: i64,
+    pub revision: i64,
+    pub action: String,
+    pub changes: Vec<FieldChange>,
+    pub note: Option<String>,
+    pub undone: bool,
+    pub created_at: Option<String>,
+}
+
+/// Book metadata normalized from any provider.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BookMetadata {
+    /// Id of the provider that returned this record
+    pub source: String,
     pub title: Option<String>,
-    pub authors: Option<Vec<OpenLibraryAuthor>>,
-    pub publishers: Option<Vec<OpenLibraryPublisher>>,
-    pub number_of_pages: Option<i64>,
+    pub authors: Vec<String>,
+    pub publishers: Vec<String>,
+    pub pages: Option<i64>,
     pub publish_date: Option<String>,
-    pub cover: Option<OpenLibraryCover>,
+    pub binding: Option<String>,
+    pub isbn10: Option<String>,
+    pub isbn13: Option<String>,
+    pub subjects: Vec<String>,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub series: Option<String>,
+    pub open_library_key: Option<String>,
+    pub cover_url: Option<String>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProviderKind {
+    OpenLibrary,
+    GoogleBooks,
+}
+
+/// One entry in the metadata provider priority list. Several entries may
+/// share a kind, e.g. a self-hosted Open Library mirror next to the real one.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenLibraryAuthor {
-    pub name: String,
+#[serde(rename_all = "camelCase")]
+pub struct ProviderConfig {
+    pub id: String,
+    pub kind: ProviderKind,
+    /// Overrides the API base URL (e.g. a local mock server in tests)
+    pub base_url: Option<String>,
+    /// Overrides the Open Library covers host
+    pub covers_base_url: Option<String>,
+    pub api_key: Option<String>,
+    pub enabled: bool,
+    /// Request budget for this provider; `None` uses the default
+    pub requests_per_minute: Option<u32>,
 }
 
+/// A cover image offered by a provider, for the user to choose from.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenLibraryPublisher {
+#[serde(rename_all = "camelCase")]
+pub struct CoverCandidate {
+    pub url: String,
+    /// Provider id the image came from
+    pub source: String,
+    /// `isbn` for an exact edition match, `search` for a title + author match
+    pub method: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Outcome of downloading and decoding a cover URL.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverValidation {
+    pub url: String,
+    pub valid: bool,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    /// SHA-256 of the image body, when it could be downloaded
+    pub hash: Option<String>,
+    pub reason: Option<String>,
+    pub checked_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverPruneResult {
+    pub files_removed: i64,
+    pub bytes_freed: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverColorResult {
+    pub books_updated: i64,
+    /// `<hash>: <reason>` for each cached cover whose colors couldn't be read
+    pub failures: Vec<String>,
+}
+
+/// Progress event emitted by background jobs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobProgress {
+    pub job: String,
+    pub processed: i64,
+    pub total: i64,
+    pub book_id: Option<i64>,
+    pub title: Option<String>,
+    /// Items the job changed or found something for so far
+    pub succeeded: i64,
+    /// Items that failed so far
+    pub failed: i64,
+    /// Why the item in this event failed (or, on the final event, why the
+    /// job couldn't save its state)
+    pub error: Option<String>,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+/// A value the metadata refresh job suggests for an empty field.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataProposal {
+    pub id: i64,
+    pub book_id: i64,
+    pub title: String,
+    pub author: String,
+    /// camelCase field name, as in `NewBook`
+    pub field: String,
+    pub proposed: serde_json::Value,
+    pub source: String,
+    pub created_at: Option<String>,
+}
+
+/// A reading goal or challenge (see goals.rs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Goal {
+    /// `None` when creating
+    pub id: Option<i64>,
     pub name: String,
+    /// `year` or `month`
+    pub period: String,
+    pub year: i64,
+    /// 1–12, for monthly goals
+    pub month: Option<i64>,
+    /// `books`, `pages` or `beq`
+    pub metric: String,
+    pub target: f64,
+    /// Comma-separated `key:value` terms a book must all match, e.g.
+    /// `shelf:classics, type:graphic_novel`
+    pub filter: Option<String>,
+    /// Count distinct values of `author`, `publisher`, `language`, `shelf`
+    /// or `shelf:<prefix>` instead of books. There is no country field;
+    /// country challenges use `shelf:<prefix>`.
+    pub distinct_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalProgress {
+    #[serde(flatten)]
+    pub goal: Goal,
+    pub current: f64,
+    pub percent: f64,
+    /// Where progress should be today for an even pace through the period
+    pub expected: f64,
+    /// `current` extrapolated to the end of the period
+    pub projected: f64,
+    /// `complete`, `ahead`, `behind`, `upcoming` or `missed`
+    pub pace: String,
+    /// Books counting toward the goal, oldest read first
+    pub books: Vec<GoalBook>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct OpenLibraryCover {
-    pub small: Option<String>,
-    pub medium: Option<String>,
-    pub large: Option<String>,
+#[serde(rename_all = "camelCase")]
+pub struct GoalBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub date_read: Option<String>,
+    pub pages: Option<i64>,
+    pub beq: Option<f64>,
+    /// The value a distinct-count challenge counted this book for
+    pub counted_as: Option<String>,
+}
+
+/// The user's ratings against the community average and other fields.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingAnalysis {
+    /// Books the user rated 1–5
+    pub rated_books: i64,
+    /// Rated books that also have a community average
+    pub compared_books: i64,
+    pub threshold: f64,
+    /// Mean of (my rating − community average); positive means more generous
+    pub mean_deviation: Option<f64>,
+    pub mean_abs_deviation: Option<f64>,
+    /// Pearson r between my rating and the community average
+    pub community_correlation: Option<f64>,
+    /// Biggest positive gap first
+    pub liked_more: Vec<RatingGap>,
+    /// Biggest negative gap first
+    pub liked_less: Vec<RatingGap>,
+    pub pages: RatingCorrelation,
+    pub year_published: RatingCorrelation,
+    pub by_type: Vec<RatingGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingGap {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub my_rating: i64,
+    pub average_rating: f64,
+    /// My rating minus the community average
+    pub difference: f64,
+}
+
+/// Ratings against a numeric field, as Pearson r and per-range averages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingCorrelation {
+    /// Rated books with a value for the field
+    pub books: i64,
+    pub my_correlation: Option<f64>,
+    pub community_correlation: Option<f64>,
+    pub buckets: Vec<RatingGroup>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatingGroup {
+    /// Book type name, or the range's lower bound
+    pub key: String,
+    pub label: String,
+    pub books: i64,
+    pub avg_my_rating: f64,
+    pub avg_community: Option<f64>,
+    pub mean_deviation: Option<f64>,
+}
+
+/// "Year in Books" report (see year_review.rs).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearReview {
+    pub year: i64,
+    pub generated_at: String,
+    pub totals: YearTotals,
+    pub previous: YearTotals,
+    pub first_book: Option<ReviewBook>,
+    pub last_book: Option<ReviewBook>,
+    pub longest_book: Option<ReviewBook>,
+    pub shortest_book: Option<ReviewBook>,
+    pub highest_rated: Option<ReviewBook>,
+    pub lowest_rated: Option<ReviewBook>,
+    pub most_read_author: Option<BreakdownRow>,
+    /// Month-by-month books, pages and BEq
+    pub months: ReadingSeries,
+    /// Yearly goals for `year`
+    pub goals: Vec<GoalProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearTotals {
+    pub year: i64,
+    pub books: i64,
+    pub pages: i64,
+    pub beq: f64,
+    pub avg_pages: Option<f64>,
+    pub avg_rating: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReviewBook {
+    pub id: i64,
+    pub title: String,
+    pub author: String,
+    pub pages: Option<i64>,
+    pub my_rating: i64,
+    pub date_read: Option<String>,
+}
+
+/// Where `export_year_review` wrote the report.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearReviewExport {
+    pub json_path: String,
+    pub html_path: String,
+    pub review: YearReview,
 }
@@ -0,0 +1,178 @@
+use image::DynamicImage;
+
+/// Longest side the image is scaled to before sampling
+const SAMPLE_SIZE: u32 = 64;
+/// Buckets smaller than this share of the sampled pixels can't be the accent
+const MIN_ACCENT_SHARE: f32 = 0.02;
+/// Minimum RGB distance between the dominant and accent colors
+const MIN_ACCENT_DISTANCE: f32 = 80.0;
+/// Below this saturation (or near black/white) a color has no hue
+const MIN_SATURATION: f32 = 0.2;
+
+/// Color families accepted by the `get_books` cover color filter, as
+/// (name, hue from, hue to). Ranges wrap at 360; `neutral` matches covers
+/// with no usable hue.
+pub const FAMILIES: &[(&str, i64, i64)] = &[
+    ("red", 345, 15),
+    ("orange", 15, 45),
+    ("yellow", 45, 70),
+    ("green", 70, 165),
+    ("teal", 165, 200),
+    ("blue", 200, 255),
+    ("purple", 255, 290),
+    ("pink", 290, 345),
+];
+pub const NEUTRAL: &str = "neutral";
+
+/// Colors stored on a book alongside its cached cover.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    /// Most common color, as `#rrggbb`
+    pub dominant: String,
+    /// A frequent, saturated color that stands apart from the dominant one
+    pub accent: String,
+    /// Hue of the dominant color in degrees, `None` for greys, black and white
+    pub hue: Option<i64>,
+}
+
+impl Palette {
+    fn new(dominant: [u8; 3], accent: [u8; 3]) -> Self {
+        Self {
+            dominant: hex(dominant),
+            accent: hex(accent),
+            hue: hue_of(dominant),
+        }
+    }
+
+    /// Palette for colors given as HSL (hue in degrees, saturation and
+    /// lightness in percent), used by generated covers.
+    pub fn from_hsl(dominant: (u32, u32, u32), accent: (u32, u32, u32)) -> Self {
+        Self::new(hsl_to_rgb(dominant), hsl_to_rgb(accent))
+    }
+}
+
+/// Extract the dominant and accent colors of an image. Pixels are grouped
+/// into coarse color buckets on a downscaled copy; the largest bucket gives
+/// the dominant color and the accent is the most frequent saturated bucket
+/// that differs visibly from it.
+pub fn extract(img: &DynamicImage) -> Palette {
+    let sample = img.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE).to_rgba8();
+
+    // 3 bits per channel: (pixel count, channel sums)
+    let mut buckets = vec![(0u32, [0u64; 3]); 512];
+    let mut counted = 0u32;
+    for pixel in sample.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let index = ((r as usize >> 5) << 6) | ((g as usize >> 5) << 3) | (b as usize >> 5);
+        let bucket = &mut buckets[index];
+        bucket.0 += 1;
+        bucket.1[0] += r as u64;
+        bucket.1[1] += g as u64;
+        bucket.1[2] += b as u64;
+        counted += 1;
+    }
+    if counted == 0 {
+        return Palette::new([0, 0, 0], [0, 0, 0]);
+    }
+
+    let mut colors: Vec<(u32, [u8; 3])> = buckets
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, sums)| {
+            let avg = |c: u64| (c / count as u64) as u8;
+            (count, [avg(sums[0]), avg(sums[1]), avg(sums[2])])
+        })
+        .collect();
+    colors.sort_by_key(|(count, _)| std::cmp::Reverse(*count));
+
+    let dominant = colors[0].1;
+    let min_count = (counted as f32 * MIN_ACCENT_SHARE).ceil() as u32;
+    let accent = colors
+        .iter()
+        .filter(|(count, color)| *count >= min_count && distance(*color, dominant) >= MIN_ACCENT_DISTANCE)
+        .max_by(|a, b| score(a).total_cmp(&score(b)))
+        .map(|(_, color)| *color)
+        .unwrap_or(dominant);
+
+    Palette::new(dominant, accent)
+}
+
+/// Accent preference: frequent and colorful beats frequent and grey.
+fn score((count, color): &(u32, [u8; 3])) -> f32 {
+    let (_, saturation, _) = rgb_to_hsl(*color);
+    *count as f32 * (0.25 + saturation)
+}
+
+fn distance(a: [u8; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(x, y)| (*x as f32 - *y as f32).powi(2))
+        .sum::<f32>()
+        .sqrt()
+}
+
+fn hue_of(color: [u8; 3]) -> Option<i64> {
+    let (hue, saturation, lightness) = rgb_to_hsl(color);
+    (saturation >= MIN_SATURATION && (0.08..=0.92).contains(&lightness)).then(|| hue.round() as i64 % 360)
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// `(hue degrees, saturation 0–1, lightness 0–1)`
+fn rgb_to_hsl([r, g, b]: [u8; 3]) -> (f32, f32, f32) {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == r {
+        60.0 * ((g - b) / delta).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / delta + 2.0)
+    } else {
+        60.0 * ((r - g) / delta + 4.0)
+    };
+    (hue, saturation, lightness)
+}
+
+fn hsl_to_rgb((hue, saturation, lightness): (u32, u32, u32)) -> [u8; 3] {
+    let s = saturation.min(100) as f32 / 100.0;
+    let l = lightness.min(100) as f32 / 100.0;
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h = (hue % 360) as f32 / 60.0;
+    let x = chroma * (1.0 - (h.rem_euclid(2.0) - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = l - chroma / 2.0;
+    let channel = |v: f32| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    [channel(r), channel(g), channel(b)]
+}
+
+/// SQL condition selecting books whose cover falls in a color family, or
+/// `None` for an unknown family name.
+pub fn family_condition(family: &str) -> Option<String> {
+    if family == NEUTRAL {
+        return Some("(books.cover_color IS NOT NULL AND books.cover_hue IS NULL)".to_string());
+    }
+    let (_, from, to) = FAMILIES.iter().find(|(name, _, _)| *name == family)?;
+    Some(if from < to {
+        format!("(books.cover_hue >= {} AND books.cover_hue < {})", from, to)
+    } else {
+        format!("(books.cover_hue >= {} OR books.cover_hue < {})", from, to)
+    })
+}
@@ -0,0 +1,207 @@
+use crate::cover_cache;
+use crate::palette::Palette;
+use rusqlite::{params, Connection};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// `cover_cached_from` value for generated placeholder covers
+pub const GENERATED_SOURCE: &str = "generated";
+
+const WIDTH: u32 = 400;
+const HEIGHT: u32 = 600;
+const TITLE_LINE_CHARS: usize = 16;
+const TITLE_MAX_LINES: usize = 5;
+const AUTHOR_LINE_CHARS: usize = 26;
+
+/// Cover styles a book type can pick (`book_types.cover_style`). The first
+/// is used when a type has none or an unknown one.
+pub const STYLES: &[&str] = &["classic", "bold"];
+
+/// Visual treatment of a cover style: (saturation %, lightness %, font stack, ornament)
+fn style(name: Option<&str>) -> (u32, u32, &'static str, Ornament) {
+    match name {
+        Some("bold") => (70, 45, "'Helvetica Neue', Arial, sans-serif", Ornament::Stripes),
+        _ => (35, 32, "Georgia, 'Times New Roman', serif", Ornament::Frame),
+    }
+}
+
+enum Ornament {
+    /// Inset double rule, like a cloth binding
+    Frame,
+    /// Diagonal bands across the top, comic-style
+    Stripes,
+}
+
+/// Render a cover as SVG: the title and author set over a background whose
+/// hue comes from a hash of the title, in the book type's cover style. The
+/// output only depends on the inputs, so regenerating an unchanged book is
+/// a no-op.
+pub fn render(title: &str, author: &str, cover_style: Option<&str>) -> String {
+    let (_, _, font, ornament) = style(cover_style);
+    let (background, accent) = colors(title, cover_style);
+    let background = format!("hsl({}, {}%, {}%)", background.0, background.1, background.2);
+    let accent = format!("hsl({}, {}%, {}%)", accent.0, accent.1, accent.2);
+
+    let title_lines = wrap(title, TITLE_LINE_CHARS, TITLE_MAX_LINES);
+    let longest = title_lines.iter().map(|l| l.chars().count()).max().unwrap_or(1).max(8);
+    // Shrink long lines so they fit the width
+    let title_size = (WIDTH as usize * 17 / 10 / longest).min(52);
+    let title_top = 170 - (title_lines.len() as i64 - 1) * title_size as i64 / 3;
+
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}"><rect width="{w}" height="{h}" fill="{background}"/>"#,
+        w = WIDTH,
+        h = HEIGHT,
+    );
+    match ornament {
+        Ornament::Frame => svg.push_str(&format!(
+            r#"<rect x="20" y="20" width="{}" height="{}" fill="none" stroke="{accent}" stroke-width="4"/><rect x="30" y="30" width="{}" height="{}" fill="none" stroke="{accent}" stroke-width="1.5"/>"#,
+            WIDTH - 40,
+            HEIGHT - 40,
+            WIDTH - 60,
+            HEIGHT - 60,
+        )),
+        Ornament::Stripes => {
+            for i in 0..4 {
+                let offset = i * 60;
+                svg.push_str(&format!(
+                    r#"<polygon points="{},0 {},0 0,{} 0,{}" fill="{accent}" opacity="{:.2}"/>"#,
+                    offset + 120,
+                    offset + 150,
+                    offset + 150,
+                    offset + 120,
+                    0.5 - i as f32 * 0.1,
+                ));
+            }
+        }
+    }
+
+    svg.push_str(&format!(
+        r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="bold" fill="white" text-anchor="middle">"#,
+        WIDTH / 2,
+        title_top,
+        font,
+        title_size,
+    ));
+    for (i, line) in title_lines.iter().enumerate() {
+        let dy = if i == 0 { 0 } else { title_size * 6 / 5 };
+        svg.push_str(&format!(r#"<tspan x="{}" dy="{}">{}</tspan>"#, WIDTH / 2, dy, escape(line)));
+    }
+    svg.push_str("</text>");
+
+    let author_line = wrap(author, AUTHOR_LINE_CHARS, 1).into_iter().next().unwrap_or_default();
+    svg.push_str(&format!(
+        r#"<text x="{}" y="{}" font-family="{}" font-size="24" fill="white" fill-opacity="0.85" text-anchor="middle">{}</text></svg>"#,
+        WIDTH / 2,
+        HEIGHT - 70,
+        font,
+        escape(&author_line),
+    ));
+    svg
+}
+
+/// Background and accent as HSL, the hue taken from a hash of the title.
+fn colors(title: &str, cover_style: Option<&str>) -> ((u32, u32, u32), (u32, u32, u32)) {
+    let digest = Sha256::digest(title.trim().to_lowercase().as_bytes());
+    let hue = u16::from_be_bytes([digest[0], digest[1]]) as u32 % 360;
+    let (saturation, lightness, _, _) = style(cover_style);
+    ((hue, saturation, lightness), ((hue + 30) % 360, saturation, lightness + 18))
+}
+
+/// Give a book a generated cover if it has no cover at all, or refresh the
+/// generated one after its title, author, type or the type's cover style
+/// changed. Real covers are never replaced, and a generated cover is dropped
+/// once the book gets a cover URL. Returns whether the row was updated.
+pub fn refresh(conn: &Connection, data_dir: &Path, book_id: i64) -> Result<bool, String> {
+    let (title, author, cover_style, cover_url, cover_hash, cached_from): (
+        String,
+        String,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+        Option<String>,
+    ) = conn
+        .query_row(
+            "SELECT b.title, b.author, t.cover_style, b.cover_url, b.cover_hash, b.cover_cached_from
+             FROM books b LEFT JOIN book_types t ON t.name = b.book_type WHERE b.id = ?1",
+            params![book_id],
+            |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?, r.get(4)?, r.get(5)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let generated = cached_from.as_deref() == Some(GENERATED_SOURCE);
+    if cover_url.as_deref().is_some_and(|u| !u.is_empty()) {
+        if !generated {
+            return Ok(false);
+        }
+        conn.execute(
+            "UPDATE books SET cover_path = NULL, cover_hash = NULL, cover_cached_from = NULL,
+                cover_color = NULL, cover_accent = NULL, cover_hue = NULL WHERE id = ?1",
+            params![book_id],
+        )
+        .map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
+    if cover_hash.is_some() && !generated {
+        return Ok(false);
+    }
+
+    let svg = render(&title, &author, cover_style.as_deref());
+    let (path, hash) = cover_cache::store_svg(data_dir, &svg)?;
+    if cover_hash.as_deref() == Some(hash.as_str()) {
+        return Ok(false);
+    }
+    let (background, accent) = colors(&title, cover_style.as_deref());
+    let palette = Palette::from_hsl(background, accent);
+    conn.execute(
+        "UPDATE books SET cover_path = ?1, cover_hash = ?2, cover_cached_from = ?3,
+            cover_color = ?5, cover_accent = ?6, cover_hue = ?7 WHERE id = ?4",
+        params![path, hash, GENERATED_SOURCE, book_id, palette.dominant, palette.accent, palette.hue],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Greedy word wrap to `width` characters, ending with an ellipsis when the
+/// text needs more than `max_lines`. Words longer than a line are split.
+fn wrap(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        while word.len() > width {
+            if !current.is_empty() {
+                lines.push(std::mem::take(&mut current));
+            }
+            lines.push(word.drain(..width).collect());
+        }
+        let word: String = word.into_iter().collect();
+        if current.is_empty() {
+            current = word;
+        } else if current.chars().count() + 1 + word.chars().count() <= width {
+            current.push(' ');
+            current.push_str(&word);
+        } else {
+            lines.push(std::mem::replace(&mut current, word));
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            let kept: String = last.chars().take(width.saturating_sub(1)).collect();
+            *last = format!("{}…", kept.trim_end());
+        }
+    }
+    lines
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,92 @@
+use crate::db::{get_setting_i64, DbState};
+use rusqlite::{params, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+
+/// Days a successful lookup is reused unless `lookup_cache_ttl_days` is set
+pub const DEFAULT_TTL_DAYS: i64 = 30;
+/// Hours a miss is remembered unless `lookup_cache_negative_ttl_hours` is set
+pub const DEFAULT_NEGATIVE_TTL_HOURS: i64 = 24;
+
+/// A stored response. `value` is `None` for a remembered miss.
+struct Entry<T> {
+    value: Option<T>,
+    fresh: bool,
+}
+
+/// Run `fetch` through the `lookup_cache` table, keyed by provider, kind of
+/// request and query. Fresh entries (hits and misses) are returned without
+/// touching the network. When the fetch fails, an expired hit is served
+/// instead so previously resolved lookups keep working offline.
+pub async fn cached<T, F>(db: &DbState, provider: &str, kind: &str, query: &str, fetch: F) -> Result<Option<T>, String>
+where
+    T: Serialize + DeserializeOwned,
+    F: Future<Output = Result<Option<T>, String>>,
+{
+    let query = query.trim().to_lowercase();
+    let stale = match read::<T>(db, provider, kind, &query) {
+        Some(entry) if entry.fresh => return Ok(entry.value),
+        Some(entry) => entry.value,
+        None => None,
+    };
+
+    match fetch.await {
+        Ok(value) => {
+            // The cache only saves a round trip; a failed write must not turn
+            // a successful lookup into an error, so the next call refetches
+            let _ = write(db, provider, kind, &query, value.as_ref());
+            Ok(value)
+        }
+        Err(e) => match stale {
+            Some(value) => Ok(Some(value)),
+            None => Err(e),
+        },
+    }
+}
+
+/// Drop every cached response. Returns the number of entries removed.
+pub fn clear(db: &DbState) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM lookup_cache", [])
+        .map_err(|e| e.to_string())
+}
+
+fn read<T: DeserializeOwned>(db: &DbState, provider: &str, kind: &str, query: &str) -> Option<Entry<T>> {
+    let conn = db.conn.lock().ok()?;
+    let (response, fresh): (Option<String>, bool) = conn
+        .query_row(
+            "SELECT response, expires_at > datetime('now') FROM lookup_cache
+             WHERE provider = ?1 AND kind = ?2 AND query = ?3",
+            params![provider, kind, query],
+            |r| Ok((r.get(0)?, r.get(1)?)),
+        )
+        .optional()
+        .ok()??;
+    let value = match response {
+        Some(json) => Some(serde_json::from_str(&json).ok()?),
+        None => None,
+    };
+    Some(Entry { value, fresh })
+}
+
+fn write<T: Serialize>(db: &DbState, provider: &str, kind: &str, query: &str, value: Option<&T>) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    let lifetime = match value {
+        Some(_) => format!("+{} days", get_setting_i64(&conn, "lookup_cache_ttl_days", DEFAULT_TTL_DAYS)),
+        None => format!(
+            "+{} hours",
+            get_setting_i64(&conn, "lookup_cache_negative_ttl_hours", DEFAULT_NEGATIVE_TTL_HOURS)
+        ),
+    };
+    let response = value.map(serde_json::to_string).transpose().map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO lookup_cache (provider, kind, query, response, expires_at)
+         VALUES (?1, ?2, ?3, ?4, datetime('now', ?5))
+         ON CONFLICT(provider, kind, query) DO UPDATE SET
+            response = excluded.response, fetched_at = datetime('now'), expires_at = excluded.expires_at",
+        params![provider, kind, query, response, lifetime],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
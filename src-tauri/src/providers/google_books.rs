@@ -0,0 +1,270 @@
+use super::http::Throttle;
+use super::{base, BoxFuture, MetadataProvider};
+use crate::models::{BookMetadata, ProviderConfig};
+use reqwest::Client;
+use serde::Deserialize;
+
+const DEFAULT_BASE_URL: &str = "https://www.googleapis.com/books/v1";
+
+/// Google Books volumes API.
+pub struct GoogleBooks {
+    id: String,
+    base_url: String,
+    api_key: Option<String>,
+    throttle: Throttle,
+}
+
+#[derive(Debug, Deserialize)]
+struct VolumesResponse {
+    items: Option<Vec<Volume>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Volume {
+    volume_info: Option<VolumeInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VolumeInfo {
+    title: Option<String>,
+    subtitle: Option<String>,
+    authors: Option<Vec<String>>,
+    publisher: Option<String>,
+    published_date: Option<String>,
+    description: Option<String>,
+    industry_identifiers: Option<Vec<IndustryIdentifier>>,
+    page_count: Option<i64>,
+    categories: Option<Vec<String>>,
+    language: Option<String>,
+    image_links: Option<ImageLinks>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndustryIdentifier {
+    #[serde(rename = "type")]
+    kind: String,
+    identifier: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageLinks {
+    thumbnail: Option<String>,
+}
+
+impl GoogleBooks {
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            id: config.id.clone(),
+            base_url: base(&config.base_url, DEFAULT_BASE_URL),
+            api_key: config.api_key.clone().filter(|k| !k.trim().is_empty()),
+            throttle: Throttle::new(&config.id, config.requests_per_minute),
+        }
+    }
+
+    async fn volumes(&self, client: &Client, query: &str, max_results: u32) -> Result<Vec<BookMetadata>, String> {
+        let mut url = format!(
+            "{}/volumes?q={}&maxResults={}",
+            self.base_url,
+            urlencoding::encode(query),
+            max_results
+        );
+        if let Some(ref key) = self.api_key {
+            url.push_str(&format!("&key={}", urlencoding::encode(key)));
+        }
+        let resp: VolumesResponse = self
+            .throttle
+            .send(client.get(&url))
+            .await?
+            .json()
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(resp
+            .items
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|v| v.volume_info)
+            .map(|info| self.to_metadata(info))
+            .collect())
+    }
+
+    fn to_metadata(&self, info: VolumeInfo) -> BookMetadata {
+        let identifiers = info.industry_identifiers.unwrap_or_default();
+        let identifier = |kind: &str| {
+            identifiers
+                .iter()
+                .find(|i| i.kind == kind)
+                .map(|i| i.identifier.clone())
+        };
+        let title = match (info.title, info.subtitle) {
+            (Some(t), Some(s)) if !s.is_empty() => Some(format!("{}: {}", t, s)),
+            (t, _) => t,
+        };
+        BookMetadata {
+            source: self.id.clone(),
+            title,
+            authors: info.authors.unwrap_or_default(),
+            publishers: info.publisher.into_iter().collect(),
+            pages: info.page_count.filter(|p| *p > 0),
+            publish_date: info.published_date,
+            binding: None,
+            isbn10: identifier("ISBN_10"),
+            isbn13: identifier("ISBN_13"),
+            subjects: info.categories.unwrap_or_default(),
+            description: info.description,
+            language: info.language,
+            series: None,
+            open_library_key: None,
+            cover_url: info.image_links.and_then(|l| l.thumbnail).map(|url| clean_thumbnail(&url)),
+        }
+    }
+}
+
+/// Fix http → https and request a larger image.
+fn clean_thumbnail(url: &str) -> String {
+    url.replace("http://", "https://")
+        .replace("&zoom=1", "&zoom=0")
+        .replace("zoom=1", "zoom=0")
+}
+
+impl MetadataProvider for GoogleBooks {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn lookup_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, String>> {
+        Box::pin(async move {
+            let query = format!("isbn:{}", isbn);
+            Ok(self.volumes(client, &query, 1).await?.into_iter().next())
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        title: &'a str,
+        author: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<BookMetadata>, String>> {
+        Box::pin(async move {
+            let query = format!("intitle:{}+inauthor:{}", title, author);
+            self.volumes(client, &query, 5).await
+        })
+    }
+
+    fn cover_by_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move { Ok(self.lookup_isbn(client, isbn).await?.and_then(|m| m.cover_url)) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProviderKind;
+    use crate::providers::test_server::serve;
+
+    const VOLUMES: &[u8] = br#"{
+        "kind": "books#volumes",
+        "totalItems": 1,
+        "items": [{
+            "id": "B1hSG45JCX4C",
+            "volumeInfo": {
+                "title": "Dune",
+                "subtitle": "",
+                "authors": ["Frank Herbert"],
+                "publisher": "Penguin",
+                "publishedDate": "2005-08-02",
+                "description": "Set on the desert planet Arrakis.",
+                "industryIdentifiers": [
+                    {"type": "ISBN_13", "identifier": "9780441013593"},
+                    {"type": "ISBN_10", "identifier": "0441013597"}
+                ],
+                "pageCount": 0,
+                "categories": ["Fiction"],
+                "language": "en",
+                "imageLinks": {
+                    "smallThumbnail": "http://books.google.com/books/content?id=B1hSG45JCX4C&printsec=frontcover&img=1&zoom=5",
+                    "thumbnail": "http://books.google.com/books/content?id=B1hSG45JCX4C&printsec=frontcover&img=1&zoom=1"
+                }
+            }
+        }]
+    }"#;
+
+    fn provider(id: &str, base_url: &str, api_key: Option<&str>) -> GoogleBooks {
+        GoogleBooks::from_config(&ProviderConfig {
+            id: id.to_string(),
+            kind: ProviderKind::GoogleBooks,
+            base_url: Some(base_url.to_string()),
+            covers_base_url: None,
+            api_key: api_key.map(str::to_string),
+            enabled: true,
+            requests_per_minute: Some(6000),
+        })
+    }
+
+    #[tokio::test]
+    async fn parses_volume_info() {
+        let (url, requests) = serve(200, VOLUMES).await;
+        let meta = provider("gb-volume", &url, Some("secret key"))
+            .lookup_isbn(&Client::new(), "9780441013593")
+            .await
+            .unwrap()
+            .expect("volume found");
+
+        assert_eq!(
+            requests.lock().unwrap()[0],
+            "GET /volumes?q=isbn%3A9780441013593&maxResults=1&key=secret%20key HTTP/1.1"
+        );
+        assert_eq!(meta.source, "gb-volume");
+        // An empty subtitle isn't appended
+        assert_eq!(meta.title.as_deref(), Some("Dune"));
+        assert_eq!(meta.authors, vec!["Frank Herbert"]);
+        assert_eq!(meta.publishers, vec!["Penguin"]);
+        // Google reports 0 when it doesn't know the page count
+        assert_eq!(meta.pages, None);
+        assert_eq!(meta.publish_date.as_deref(), Some("2005-08-02"));
+        assert_eq!(meta.isbn10.as_deref(), Some("0441013597"));
+        assert_eq!(meta.isbn13.as_deref(), Some("9780441013593"));
+        assert_eq!(meta.subjects, vec!["Fiction"]);
+        assert_eq!(meta.language.as_deref(), Some("en"));
+        assert_eq!(
+            meta.cover_url.as_deref(),
+            Some("https://books.google.com/books/content?id=B1hSG45JCX4C&printsec=frontcover&img=1&zoom=0")
+        );
+    }
+
+    #[tokio::test]
+    async fn no_items_is_a_miss() {
+        let (url, requests) = serve(200, br#"{"kind": "books#volumes", "totalItems": 0}"#).await;
+        let meta = provider("gb-miss", &url, None).lookup_isbn(&Client::new(), "9780000000002").await;
+        assert!(matches!(meta, Ok(None)), "{:?}", meta);
+        // Blank keys aren't sent
+        assert!(!requests.lock().unwrap()[0].contains("key="));
+    }
+
+    #[tokio::test]
+    async fn search_queries_title_and_author() {
+        let (url, requests) = serve(200, VOLUMES).await;
+        let results = provider("gb-search", &url, None)
+            .search(&Client::new(), "Dune", "Herbert")
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            requests.lock().unwrap()[0],
+            "GET /volumes?q=intitle%3ADune%2Binauthor%3AHerbert&maxResults=5 HTTP/1.1"
+        );
+    }
+}
@@ -0,0 +1,90 @@
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+/// Requests per minute per provider unless its config sets `requestsPerMinute`
+pub const DEFAULT_REQUESTS_PER_MINUTE: u32 = 60;
+
+const MAX_ATTEMPTS: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Earliest time the next request may start, per throttle key. Global so the
+/// limit holds across concurrent jobs and freshly built provider instances.
+static NEXT_SLOT: LazyLock<Mutex<HashMap<String, Instant>>> = LazyLock::new(Default::default);
+
+/// Spaces out requests to one provider and retries transient failures.
+#[derive(Debug, Clone)]
+pub struct Throttle {
+    key: String,
+    interval: Duration,
+}
+
+impl Throttle {
+    pub fn new(key: &str, requests_per_minute: Option<u32>) -> Self {
+        let rpm = requests_per_minute.unwrap_or(DEFAULT_REQUESTS_PER_MINUTE).max(1);
+        Self {
+            key: key.to_string(),
+            interval: Duration::from_secs(60) / rpm,
+        }
+    }
+
+    /// Send once a slot is free. Connection errors, 429 and 5xx responses are
+    /// retried with exponential backoff (honouring `Retry-After`); the last
+    /// response is returned as-is if every attempt failed.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, String> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let Some(this_try) = request.try_clone() else {
+                // Streaming bodies can't be replayed; send once
+                self.wait_turn().await;
+                return request.send().await.map_err(|e| e.to_string());
+            };
+            self.wait_turn().await;
+
+            let delay = match this_try.send().await {
+                Ok(resp) if !is_transient(resp.status()) || attempt >= MAX_ATTEMPTS => return Ok(resp),
+                Ok(resp) => retry_after(&resp).unwrap_or_else(|| backoff(attempt)),
+                Err(e) if attempt >= MAX_ATTEMPTS || !(e.is_timeout() || e.is_connect()) => {
+                    return Err(e.to_string())
+                }
+                Err(_) => backoff(attempt),
+            };
+            tokio::time::sleep(delay.min(MAX_BACKOFF)).await;
+        }
+    }
+
+    async fn wait_turn(&self) {
+        let wait = {
+            let mut slots = NEXT_SLOT.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let slot = slots.get(&self.key).copied().filter(|t| *t > now).unwrap_or(now);
+            slots.insert(self.key.clone(), slot + self.interval);
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF * 2u32.pow(attempt - 1)
+}
+
+fn retry_after(resp: &Response) -> Option<Duration> {
+    resp.headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(Duration::from_secs)
+}
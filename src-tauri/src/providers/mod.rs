@@ -0,0 +1,309 @@
+pub mod cache;
+mod google_books;
+pub mod http;
+mod open_library;
+
+pub use google_books::GoogleBooks;
+pub use open_library::OpenLibrary;
+
+use crate::cover_cache;
+use crate::db::{get_setting, set_setting, DbState};
+use crate::models::{BookMetadata, ProviderConfig, ProviderKind};
+use http::Throttle;
+use reqwest::{Client, StatusCode};
+use rusqlite::Connection;
+use std::future::Future;
+use std::pin::Pin;
+
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+const SETTINGS_KEY: &str = "metadata_providers";
+
+/// A source of book metadata and cover art.
+///
+/// Methods return boxed futures so providers can be stored as trait objects
+/// and tried in the user's priority order.
+pub trait MetadataProvider: Send + Sync {
+    /// Identifier from the provider's config, used for ordering and attribution
+    fn id(&self) -> &str;
+
+    /// Root URL requests go to. Part of the lookup cache key, so answers
+    /// from one server aren't reused after pointing the provider at another.
+    fn base_url(&self) -> &str;
+
+    fn lookup_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, String>>;
+
+    /// Best matches for a title and author, most relevant first.
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        title: &'a str,
+        author: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<BookMetadata>, String>>;
+
+    /// A cover URL for the ISBN that is known to point at a real image.
+    /// `Ok(None)` means the provider has none; errors are request failures.
+    fn cover_by_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>>;
+}
+
+/// Built-in providers in their default priority order. Google Books comes
+/// first because Open Library often serves placeholder covers.
+pub fn default_configs() -> Vec<ProviderConfig> {
+    vec![
+        ProviderConfig {
+            id: "googleBooks".to_string(),
+            kind: ProviderKind::GoogleBooks,
+            base_url: None,
+            covers_base_url: None,
+            api_key: None,
+            requests_per_minute: None,
+            enabled: true,
+        },
+        ProviderConfig {
+            id: "openLibrary".to_string(),
+            kind: ProviderKind::OpenLibrary,
+            base_url: None,
+            covers_base_url: None,
+            api_key: None,
+            requests_per_minute: None,
+            enabled: true,
+        },
+    ]
+}
+
+/// Provider configs in priority order, falling back to the defaults when
+/// nothing (or something unreadable) is stored.
+pub fn load_configs(conn: &Connection) -> Vec<ProviderConfig> {
+    get_setting(conn, SETTINGS_KEY)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_else(default_configs)
+}
+
+pub fn save_configs(conn: &Connection, configs: &[ProviderConfig]) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    for config in configs {
+        if config.id.trim().is_empty() {
+            return Err("Provider id cannot be empty".to_string());
+        }
+        if !seen.insert(config.id.as_str()) {
+            return Err(format!("Duplicate provider id: {}", config.id));
+        }
+    }
+    let json = serde_json::to_string(configs).map_err(|e| e.to_string())?;
+    set_setting(conn, SETTINGS_KEY, &json)
+}
+
+/// Instantiate the enabled providers, preserving priority order.
+pub fn build(configs: &[ProviderConfig]) -> Vec<Box<dyn MetadataProvider>> {
+    configs
+        .iter()
+        .filter(|c| c.enabled)
+        .map(|c| -> Box<dyn MetadataProvider> {
+            match c.kind {
+                ProviderKind::OpenLibrary => Box::new(OpenLibrary::from_config(c)),
+                ProviderKind::GoogleBooks => Box::new(GoogleBooks::from_config(c)),
+            }
+        })
+        .collect()
+}
+
+/// Load and instantiate the configured providers.
+pub fn load(conn: &Connection) -> Vec<Box<dyn MetadataProvider>> {
+    build(&load_configs(conn))
+}
+
+/// HTTP client with the timeouts used for all provider requests. One is
+/// built at startup and shared through app state.
+pub fn http_client() -> Result<Client, String> {
+    Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Every provider's record for the ISBN, in priority order. Errors are only
+/// surfaced when every provider failed.
+pub async fn lookup_all(
+    db: &DbState,
+    providers: &[Box<dyn MetadataProvider>],
+    client: &Client,
+    isbn: &str,
+) -> Result<Vec<BookMetadata>, String> {
+    let mut found = Vec::new();
+    let mut last_err = None;
+    let mut any_ok = false;
+    for provider in providers {
+        let lookup = provider.lookup_isbn(client, isbn);
+        match cache::cached(db, &cache_scope(provider.as_ref()), "isbn", isbn, lookup).await {
+            Ok(Some(meta)) => {
+                any_ok = true;
+                found.push(meta);
+            }
+            Ok(None) => any_ok = true,
+            Err(e) => last_err = Some(format!("{}: {}", provider.id(), e)),
+        }
+    }
+    match last_err {
+        Some(e) if !any_ok => Err(e),
+        _ => Ok(found),
+    }
+}
+
+/// `MetadataProvider::cover_by_isbn` through the lookup cache. Failed
+/// requests aren't cached, so they are retried next time.
+pub async fn cover_by_isbn(
+    db: &DbState,
+    provider: &dyn MetadataProvider,
+    client: &Client,
+    isbn: &str,
+) -> Result<Option<String>, String> {
+    let fetch = provider.cover_by_isbn(client, isbn);
+    cache::cached(db, &cache_scope(provider), "cover", isbn, fetch).await
+}
+
+/// `MetadataProvider::search` through the lookup cache.
+pub async fn search(
+    db: &DbState,
+    provider: &dyn MetadataProvider,
+    client: &Client,
+    title: &str,
+    author: &str,
+) -> Result<Vec<BookMetadata>, String> {
+    let query = format!("{}|{}", title, author);
+    let fetch = async {
+        let matches = provider.search(client, title, author).await?;
+        // An empty result is stored as a miss so it expires sooner
+        Ok((!matches.is_empty()).then_some(matches))
+    };
+    Ok(cache::cached(db, &cache_scope(provider), "search", &query, fetch).await?.unwrap_or_default())
+}
+
+/// The `lookup_cache` provider column: config id plus the server it talks to.
+fn cache_scope(provider: &dyn MetadataProvider) -> String {
+    format!("{} {}", provider.id(), provider.base_url())
+}
+
+/// Download an image and check that it decodes to a supported format of a
+/// plausible size (Open Library answers with a 1x1 pixel when it has no
+/// cover). A missing image is `Ok(false)`; errors mean the download failed.
+/// See `cover_cache::check_url` for the recorded, blocklist-aware check.
+pub async fn validate_image_url(client: &Client, throttle: &Throttle, url: &str) -> Result<bool, String> {
+    Ok(fetch_image(client, throttle, url)
+        .await?
+        .is_some_and(|bytes| cover_cache::inspect(&bytes).is_ok()))
+}
+
+/// GET an image body. `Ok(None)` when the server says there is nothing at
+/// the URL (404 or 410); other non-success statuses are errors.
+pub async fn fetch_image(client: &Client, throttle: &Throttle, url: &str) -> Result<Option<Vec<u8>>, String> {
+    let resp = throttle.send(client.get(url)).await?;
+    let status = resp.status();
+    if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(format!("HTTP {}", status));
+    }
+    resp.bytes().await.map(|b| Some(b.to_vec())).map_err(|e| e.to_string())
+}
+
+/// Strip the trailing slash so paths can be appended with `format!`.
+fn base(url: &Option<String>, default: &str) -> String {
+    url.as_deref()
+        .filter(|u| !u.trim().is_empty())
+        .unwrap_or(default)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// A throwaway HTTP server for pointing a provider's `base_url` at fixtures.
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// Answer every request with `status` and `body`. Returns the base URL
+    /// and the request lines (`GET /path?query HTTP/1.1`) seen so far.
+    pub async fn serve(status: u16, body: &'static [u8]) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = vec![0; 8192];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                seen.lock().unwrap().push(request.lines().next().unwrap_or_default().to_string());
+                let head = format!(
+                    "HTTP/1.1 {} Fixture\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                    status,
+                    body.len()
+                );
+                let _ = socket.write_all(head.as_bytes()).await;
+                let _ = socket.write_all(body).await;
+            }
+        });
+        (format!("http://{}", addr), requests)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::apply_schema;
+    use std::sync::Mutex;
+
+    fn memory_db() -> DbState {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_schema(&conn).unwrap();
+        DbState { conn: Mutex::new(conn), data_dir: std::env::temp_dir() }
+    }
+
+    fn google(id: &str, base_url: &str) -> GoogleBooks {
+        GoogleBooks::from_config(&ProviderConfig {
+            id: id.to_string(),
+            kind: ProviderKind::GoogleBooks,
+            base_url: Some(base_url.to_string()),
+            covers_base_url: None,
+            api_key: None,
+            enabled: true,
+            requests_per_minute: Some(6000),
+        })
+    }
+
+    fn cached_scopes(db: &DbState) -> Vec<String> {
+        let conn = db.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT provider FROM lookup_cache WHERE kind = 'cover'").unwrap();
+        let scopes = stmt.query_map([], |r| r.get(0)).unwrap().map(Result::unwrap).collect();
+        scopes
+    }
+
+    #[tokio::test]
+    async fn cover_lookup_failures_are_not_cached() {
+        let db = memory_db();
+        let client = Client::new();
+
+        let (broken, _) = test_server::serve(403, b"quota exceeded").await;
+        let cover = cover_by_isbn(&db, &google("gb-cover", &broken), &client, "9780441013593").await;
+        assert!(cover.is_err());
+        assert!(cached_scopes(&db).is_empty());
+
+        // A real miss is remembered, keyed by the server that answered
+        let (empty, requests) = test_server::serve(200, br#"{"totalItems": 0}"#).await;
+        let provider = google("gb-cover", &empty);
+        assert_eq!(cover_by_isbn(&db, &provider, &client, "9780441013593").await, Ok(None));
+        assert_eq!(cover_by_isbn(&db, &provider, &client, "9780441013593").await, Ok(None));
+        assert_eq!(requests.lock().unwrap().len(), 1);
+        assert_eq!(cached_scopes(&db), vec![format!("gb-cover {}", empty)]);
+    }
+}
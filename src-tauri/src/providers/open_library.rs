@@ -0,0 +1,345 @@
+use super::http::Throttle;
+use super::{base, validate_image_url, BoxFuture, MetadataProvider};
+use crate::models::{BookMetadata, ProviderConfig};
+use reqwest::Client;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+const DEFAULT_BASE_URL: &str = "https://openlibrary.org";
+const DEFAULT_COVERS_URL: &str = "https://covers.openlibrary.org";
+
+/// Open Library's books API (`jscmd=details`), search API and covers API.
+pub struct OpenLibrary {
+    id: String,
+    base_url: String,
+    covers_url: String,
+    throttle: Throttle,
+}
+
+#[derive(Debug, Deserialize)]
+struct BibEntry {
+    details: Option<EditionDetails>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EditionDetails {
+    key: Option<String>,
+    title: Option<String>,
+    subtitle: Option<String>,
+    authors: Option<Vec<Named>>,
+    publishers: Option<Vec<String>>,
+    number_of_pages: Option<i64>,
+    publish_date: Option<String>,
+    subjects: Option<Vec<String>>,
+    series: Option<Vec<String>>,
+    languages: Option<Vec<Keyed>>,
+    /// Either a plain string or `{ "type": ..., "value": ... }`
+    description: Option<serde_json::Value>,
+    physical_format: Option<String>,
+    isbn_10: Option<Vec<String>>,
+    isbn_13: Option<Vec<String>>,
+    covers: Option<Vec<i64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Keyed {
+    key: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    docs: Option<Vec<SearchDoc>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchDoc {
+    key: Option<String>,
+    title: Option<String>,
+    author_name: Option<Vec<String>>,
+    publisher: Option<Vec<String>>,
+    number_of_pages_median: Option<i64>,
+    first_publish_year: Option<i64>,
+    isbn: Option<Vec<String>>,
+    language: Option<Vec<String>>,
+    subject: Option<Vec<String>>,
+    cover_i: Option<i64>,
+}
+
+impl OpenLibrary {
+    pub fn from_config(config: &ProviderConfig) -> Self {
+        Self {
+            id: config.id.clone(),
+            base_url: base(&config.base_url, DEFAULT_BASE_URL),
+            covers_url: base(&config.covers_base_url, DEFAULT_COVERS_URL),
+            throttle: Throttle::new(&config.id, config.requests_per_minute),
+        }
+    }
+
+    fn isbn_cover_url(&self, isbn: &str) -> String {
+        format!("{}/b/isbn/{}-M.jpg", self.covers_url, isbn)
+    }
+
+    fn edition_to_metadata(&self, data: EditionDetails) -> BookMetadata {
+        let first = |v: Option<Vec<String>>| v.and_then(|ids| ids.into_iter().next());
+        let title = match (data.title, data.subtitle) {
+            (Some(t), Some(s)) if !s.is_empty() => Some(format!("{}: {}", t, s)),
+            (t, _) => t,
+        };
+        let description = data.description.and_then(|d| match d {
+            serde_json::Value::String(s) => Some(s),
+            serde_json::Value::Object(o) => o.get("value").and_then(|v| v.as_str()).map(str::to_string),
+            _ => None,
+        });
+        BookMetadata {
+            source: self.id.clone(),
+            title,
+            authors: data.authors.unwrap_or_default().into_iter().map(|a| a.name).collect(),
+            publishers: data.publishers.unwrap_or_default(),
+            pages: data.number_of_pages,
+            publish_date: data.publish_date,
+            binding: data.physical_format,
+            isbn10: first(data.isbn_10),
+            isbn13: first(data.isbn_13),
+            subjects: data.subjects.unwrap_or_default(),
+            description,
+            // "/languages/eng" → "eng"
+            language: data
+                .languages
+                .and_then(|l| l.into_iter().next())
+                .map(|l| l.key.rsplit('/').next().unwrap_or_default().to_string()),
+            series: first(data.series),
+            open_library_key: data.key,
+            cover_url: data
+                .covers
+                .and_then(|c| c.into_iter().find(|id| *id > 0))
+                .map(|id| format!("{}/b/id/{}-M.jpg", self.covers_url, id)),
+        }
+    }
+
+    fn doc_to_metadata(&self, doc: SearchDoc) -> BookMetadata {
+        let isbns = doc.isbn.unwrap_or_default();
+        BookMetadata {
+            source: self.id.clone(),
+            title: doc.title,
+            authors: doc.author_name.unwrap_or_default(),
+            publishers: doc.publisher.unwrap_or_default().into_iter().take(1).collect(),
+            pages: doc.number_of_pages_median,
+            publish_date: doc.first_publish_year.map(|y| y.to_string()),
+            binding: None,
+            isbn10: isbns.iter().find(|i| i.len() == 10).cloned(),
+            isbn13: isbns.iter().find(|i| i.len() == 13).cloned(),
+            subjects: doc.subject.unwrap_or_default().into_iter().take(10).collect(),
+            description: None,
+            language: doc.language.and_then(|l| l.into_iter().next()),
+            series: None,
+            open_library_key: doc.key,
+            cover_url: doc.cover_i.map(|id| format!("{}/b/id/{}-M.jpg", self.covers_url, id)),
+        }
+    }
+}
+
+impl MetadataProvider for OpenLibrary {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    fn lookup_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<BookMetadata>, String>> {
+        Box::pin(async move {
+            let url = format!(
+                "{}/api/books?bibkeys=ISBN:{}&format=json&jscmd=details",
+                self.base_url, isbn
+            );
+            let data: HashMap<String, BibEntry> = self
+                .throttle
+                .send(client.get(&url))
+                .await?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+
+            let key = format!("ISBN:{}", isbn);
+            Ok(data
+                .into_iter()
+                .find(|(k, _)| *k == key)
+                .and_then(|(_, entry)| entry.details)
+                .map(|d| self.edition_to_metadata(d)))
+        })
+    }
+
+    fn search<'a>(
+        &'a self,
+        client: &'a Client,
+        title: &'a str,
+        author: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<BookMetadata>, String>> {
+        Box::pin(async move {
+            let url = format!(
+                "{}/search.json?title={}&author={}&limit=5",
+                self.base_url,
+                urlencoding::encode(title),
+                urlencoding::encode(author)
+            );
+            let resp: SearchResponse = self
+                .throttle
+                .send(client.get(&url))
+                .await?
+                .json()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(resp
+                .docs
+                .unwrap_or_default()
+                .into_iter()
+                .map(|d| self.doc_to_metadata(d))
+                .collect())
+        })
+    }
+
+    fn cover_by_isbn<'a>(
+        &'a self,
+        client: &'a Client,
+        isbn: &'a str,
+    ) -> BoxFuture<'a, Result<Option<String>, String>> {
+        Box::pin(async move {
+            // Open Library always answers, so validate that it isn't the blank placeholder
+            let url = self.isbn_cover_url(isbn);
+            Ok(validate_image_url(client, &self.throttle, &url).await?.then_some(url))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProviderKind;
+    use crate::providers::test_server::serve;
+
+    const DETAILS: &[u8] = br#"{
+        "ISBN:9780441013593": {
+            "bib_key": "ISBN:9780441013593",
+            "details": {
+                "key": "/books/OL7353617M",
+                "title": "Dune",
+                "subtitle": "Deluxe Edition",
+                "authors": [{"key": "/authors/OL79034A", "name": "Frank Herbert"}],
+                "publishers": ["Ace Books"],
+                "number_of_pages": 617,
+                "publish_date": "August 2, 2005",
+                "subjects": ["Science fiction", "Dune (Imaginary place)"],
+                "series": ["Dune Chronicles"],
+                "languages": [{"key": "/languages/eng"}],
+                "description": {"type": "/type/text", "value": "Set on the desert planet Arrakis."},
+                "physical_format": "Paperback",
+                "isbn_10": ["0441013597"],
+                "isbn_13": ["9780441013593"],
+                "covers": [-1, 12345]
+            }
+        }
+    }"#;
+
+    const SEARCH: &[u8] = br#"{
+        "numFound": 1,
+        "docs": [{
+            "key": "/works/OL893415W",
+            "title": "Dune",
+            "author_name": ["Frank Herbert"],
+            "publisher": ["Chilton Books", "Ace"],
+            "number_of_pages_median": 612,
+            "first_publish_year": 1965,
+            "isbn": ["9780441013593", "0441013597"],
+            "language": ["eng"],
+            "subject": ["Science fiction"],
+            "cover_i": 11481354
+        }]
+    }"#;
+
+    fn provider(id: &str, base_url: &str) -> OpenLibrary {
+        OpenLibrary::from_config(&ProviderConfig {
+            id: id.to_string(),
+            kind: ProviderKind::OpenLibrary,
+            base_url: Some(format!("{}/", base_url)),
+            covers_base_url: Some("https://covers.test".to_string()),
+            api_key: None,
+            enabled: true,
+            requests_per_minute: Some(6000),
+        })
+    }
+
+    #[tokio::test]
+    async fn parses_edition_details() {
+        let (url, requests) = serve(200, DETAILS).await;
+        let meta = provider("ol-details", &url)
+            .lookup_isbn(&Client::new(), "9780441013593")
+            .await
+            .unwrap()
+            .expect("edition found");
+
+        assert_eq!(
+            requests.lock().unwrap()[0],
+            "GET /api/books?bibkeys=ISBN:9780441013593&format=json&jscmd=details HTTP/1.1"
+        );
+        assert_eq!(meta.source, "ol-details");
+        assert_eq!(meta.title.as_deref(), Some("Dune: Deluxe Edition"));
+        assert_eq!(meta.authors, vec!["Frank Herbert"]);
+        assert_eq!(meta.publishers, vec!["Ace Books"]);
+        assert_eq!(meta.pages, Some(617));
+        assert_eq!(meta.publish_date.as_deref(), Some("August 2, 2005"));
+        assert_eq!(meta.binding.as_deref(), Some("Paperback"));
+        assert_eq!(meta.isbn10.as_deref(), Some("0441013597"));
+        assert_eq!(meta.isbn13.as_deref(), Some("9780441013593"));
+        assert_eq!(meta.series.as_deref(), Some("Dune Chronicles"));
+        assert_eq!(meta.language.as_deref(), Some("eng"));
+        assert_eq!(meta.description.as_deref(), Some("Set on the desert planet Arrakis."));
+        assert_eq!(meta.open_library_key.as_deref(), Some("/books/OL7353617M"));
+        // Negative ids are Open Library's "no cover" marker
+        assert_eq!(meta.cover_url.as_deref(), Some("https://covers.test/b/id/12345-M.jpg"));
+    }
+
+    #[tokio::test]
+    async fn unknown_isbn_is_a_miss() {
+        let (url, _) = serve(200, b"{}").await;
+        let meta = provider("ol-miss", &url).lookup_isbn(&Client::new(), "9780000000002").await;
+        assert!(matches!(meta, Ok(None)), "{:?}", meta);
+    }
+
+    #[tokio::test]
+    async fn parses_search_docs() {
+        let (url, requests) = serve(200, SEARCH).await;
+        let results = provider("ol-search", &url)
+            .search(&Client::new(), "Dune", "Frank Herbert")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            requests.lock().unwrap()[0],
+            "GET /search.json?title=Dune&author=Frank%20Herbert&limit=5 HTTP/1.1"
+        );
+        let [meta] = results.as_slice() else { panic!("expected one result, got {:?}", results) };
+        assert_eq!(meta.title.as_deref(), Some("Dune"));
+        assert_eq!(meta.publishers, vec!["Chilton Books"]);
+        assert_eq!(meta.pages, Some(612));
+        assert_eq!(meta.publish_date.as_deref(), Some("1965"));
+        assert_eq!(meta.isbn10.as_deref(), Some("0441013597"));
+        assert_eq!(meta.isbn13.as_deref(), Some("9780441013593"));
+        assert_eq!(meta.cover_url.as_deref(), Some("https://covers.test/b/id/11481354-M.jpg"));
+    }
+
+    #[tokio::test]
+    async fn malformed_response_is_an_error() {
+        let (url, _) = serve(200, b"<html>maintenance</html>").await;
+        assert!(provider("ol-bad", &url).lookup_isbn(&Client::new(), "9780441013593").await.is_err());
+    }
+}
@@ -0,0 +1,298 @@
+use crate::commands::stats::{breakdowns, read_in, reading_series, round2, today};
+use crate::goals;
+use crate::models::{ReviewBook, YearReview, YearTotals};
+use rusqlite::{Connection, OptionalExtension};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+fn report_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("reports")
+}
+
+/// Gather the "Year in Books" report for `year`. Call `beq::sync` first.
+pub fn build(conn: &Connection, year: i64) -> Result<YearReview, String> {
+    let generated_at: String = conn
+        .query_row("SELECT datetime('now', 'localtime')", [], |r| r.get(0))
+        .map_err(|e| e.to_string())?;
+    let dated = "date(replace(books.date_read, '/', '-')) IS NOT NULL";
+    let by_date = "date(replace(books.date_read, '/', '-'))";
+
+    let today = today(conn)?;
+    let goals = goals::list(conn, Some(year))?
+        .iter()
+        .filter(|goal| goal.period == "year")
+        .map(|goal| goals::progress(conn, goal, today))
+        .collect::<Result<_, _>>()?;
+
+    Ok(YearReview {
+        year,
+        generated_at,
+        totals: totals(conn, year)?,
+        previous: totals(conn, year - 1)?,
+        first_book: pick(conn, year, dated, &format!("{} ASC", by_date))?,
+        last_book: pick(conn, year, dated, &format!("{} DESC", by_date))?,
+        longest_book: pick(conn, year, "books.pages > 0", "books.pages DESC")?,
+        shortest_book: pick(conn, year, "books.pages > 0", "books.pages ASC")?,
+        highest_rated: pick(conn, year, "books.my_rating > 0", &format!("books.my_rating DESC, {} ASC", by_date))?,
+        lowest_rated: pick(conn, year, "books.my_rating > 0", &format!("books.my_rating ASC, {} ASC", by_date))?,
+        most_read_author: breakdowns(conn, Some(year), None, Some(1))?.authors.into_iter().next(),
+        months: reading_series(conn, "month".to_string(), year, None)?,
+        goals,
+    })
+}
+
+fn totals(conn: &Connection, year: i64) -> Result<YearTotals, String> {
+    conn.query_row(
+        &format!(
+            "SELECT count(*), coalesce(sum(books.pages), 0), coalesce(sum(books.beq), 0),
+                    avg(CASE WHEN books.pages > 0 THEN books.pages END),
+                    avg(CASE WHEN books.my_rating > 0 THEN books.my_rating END)
+             FROM books WHERE {}",
+            read_in(year, None)
+        ),
+        [],
+        |r| {
+            Ok(YearTotals {
+                year,
+                books: r.get(0)?,
+                pages: r.get(1)?,
+                beq: round2(r.get(2)?),
+                avg_pages: r.get::<_, Option<f64>>(3)?.map(round2),
+                avg_rating: r.get::<_, Option<f64>>(4)?.map(round2),
+            })
+        },
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// First book read in `year` matching `condition` under `order`.
+fn pick(conn: &Connection, year: i64, condition: &str, order: &str) -> Result<Option<ReviewBook>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT books.id, books.title, books.author, books.pages, coalesce(books.my_rating, 0), books.date_read
+             FROM books WHERE {} AND {} ORDER BY {}, books.id LIMIT 1",
+            read_in(year, None),
+            condition,
+            order
+        ),
+        [],
+        |r| {
+            Ok(ReviewBook {
+                id: r.get(0)?,
+                title: r.get(1)?,
+                author: r.get(2)?,
+                pages: r.get(3)?,
+                my_rating: r.get(4)?,
+                date_read: r.get(5)?,
+            })
+        },
+    )
+    .optional()
+    .map_err(|e| e.to_string())
+}
+
+/// Write the report as `reports/year-in-books-<year>.json` and `.html`,
+/// replacing earlier exports for the same year. Returns both paths.
+pub fn export(data_dir: &Path, review: &YearReview) -> Result<(PathBuf, PathBuf), String> {
+    let dir = report_dir(data_dir);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create report dir: {}", e))?;
+    let stem = format!("year-in-books-{}", review.year);
+
+    let json_path = dir.join(format!("{}.json", stem));
+    let json = serde_json::to_string_pretty(review).map_err(|e| e.to_string())?;
+    std::fs::write(&json_path, json).map_err(|e| format!("Failed to write report: {}", e))?;
+
+    let html_path = dir.join(format!("{}.html", stem));
+    std::fs::write(&html_path, render_html(review)).map_err(|e| format!("Failed to write report: {}", e))?;
+    Ok((json_path, html_path))
+}
+
+/// A self-contained HTML page (inline styles, no scripts or external assets).
+pub fn render_html(review: &YearReview) -> String {
+    let t = &review.totals;
+    let p = &review.previous;
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{year} in Books</title>\n<style>{css}</style>\n</head>\n<body>\n\
+         <h1>{year} in Books</h1>\n<p class=\"muted\">Generated {generated}</p>\n",
+        year = review.year,
+        css = CSS,
+        generated = escape(&review.generated_at),
+    );
+
+    html.push_str("<section class=\"tiles\">\n");
+    tile(&mut html, "Books", &t.books.to_string(), &change(t.books as f64, p.books as f64, p.year));
+    tile(&mut html, "Pages", &t.pages.to_string(), &change(t.pages as f64, p.pages as f64, p.year));
+    tile(&mut html, "BEq", &format!("{:.2}", t.beq), &change(t.beq, p.beq, p.year));
+    if let Some(avg) = t.avg_pages {
+        tile(&mut html, "Average length", &format!("{:.0} pages", avg), "");
+    }
+    if let Some(avg) = t.avg_rating {
+        tile(&mut html, "Average rating", &format!("{:.2} ★", avg), "");
+    }
+    html.push_str("</section>\n");
+
+    if !review.goals.is_empty() {
+        html.push_str("<h2>Goals</h2>\n");
+        for goal in &review.goals {
+            let _ = writeln!(
+                html,
+                "<div class=\"goal\"><div>{name} — {current} / {target} ({percent:.0}%, {pace})</div>\
+                 <div class=\"bar\"><span style=\"width:{percent:.0}%\"></span></div></div>",
+                name = escape(&goal.goal.name),
+                current = goal.current,
+                target = goal.goal.target,
+                percent = goal.percent,
+                pace = goal.pace,
+            );
+        }
+    }
+
+    html.push_str("<h2>Month by month</h2>\n<div class=\"chart\">\n");
+    let max = review.months.points.iter().map(|m| m.books).max().unwrap_or(0).max(1);
+    for (point, name) in review.months.points.iter().zip(MONTH_NAMES) {
+        let _ = writeln!(
+            html,
+            "<div class=\"col\" title=\"{name}: {books} books, {pages} pages\">\
+             <span class=\"n\">{books}</span><span class=\"b\" style=\"height:{height}%\"></span>\
+             <span class=\"l\">{label}</span></div>",
+            name = name,
+            books = point.books,
+            pages = point.pages,
+            height = point.books * 100 / max,
+            label = escape(&point.label),
+        );
+    }
+    html.push_str("</div>\n");
+    if review.months.undated > 0 {
+        let _ = writeln!(
+            html,
+            "<p class=\"muted\">{} books without a read date in {} aren't in the chart.</p>",
+            review.months.undated,
+            review.year
+        );
+    }
+
+    html.push_str("<h2>Highlights</h2>\n<dl>\n");
+    let highlights = [
+        ("First book", &review.first_book),
+        ("Last book", &review.last_book),
+        ("Longest", &review.longest_book),
+        ("Shortest", &review.shortest_book),
+        ("Highest rated", &review.highest_rated),
+        ("Lowest rated", &review.lowest_rated),
+    ];
+    for (label, book) in highlights {
+        if let Some(book) = book {
+            let mut details = Vec::new();
+            if let Some(pages) = book.pages {
+                details.push(format!("{} pages", pages));
+            }
+            if book.my_rating > 0 {
+                details.push("★".repeat(book.my_rating as usize));
+            }
+            if let Some(ref date) = book.date_read {
+                details.push(escape(date));
+            }
+            let _ = writeln!(
+                html,
+                "<dt>{}</dt><dd><strong>{}</strong> by {} <span class=\"muted\">{}</span></dd>",
+                label,
+                escape(&book.title),
+                escape(&book.author),
+                details.join(" · ")
+            );
+        }
+    }
+    if let Some(ref author) = review.most_read_author {
+        let _ = writeln!(
+            html,
+            "<dt>Most-read author</dt><dd><strong>{}</strong> <span class=\"muted\">{} books · {} pages</span></dd>",
+            escape(&author.label),
+            author.books,
+            author.pages
+        );
+    }
+    html.push_str("</dl>\n</body>\n</html>\n");
+    html
+}
+
+fn tile(html: &mut String, label: &str, value: &str, note: &str) {
+    let _ = writeln!(
+        html,
+        "<div class=\"tile\"><div class=\"muted\">{}</div><div class=\"big\">{}</div><div class=\"muted\">{}</div></div>",
+        label,
+        escape(value),
+        note
+    );
+}
+
+/// "+12 vs 2023" style comparison against the previous year.
+fn change(current: f64, previous: f64, previous_year: i64) -> String {
+    let delta = ((current - previous) * 100.0).round() / 100.0;
+    if previous == 0.0 {
+        return format!("none in {}", previous_year);
+    }
+    format!("{}{} vs {}", if delta >= 0.0 { "+" } else { "" }, delta, previous_year)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const CSS: &str = "body{font-family:system-ui,sans-serif;max-width:860px;margin:2rem auto;padding:0 1rem;color:#1f2937}\
+h1{font-size:2.2rem;margin-bottom:0}h2{margin-top:2rem}.muted{color:#6b7280;font-size:.85rem}\
+.tiles{display:grid;grid-template-columns:repeat(auto-fit,minmax(150px,1fr));gap:.75rem;margin-top:1.5rem}\
+.tile{border:1px solid #e5e7eb;border-radius:.5rem;padding:.75rem}.big{font-size:1.6rem;font-weight:700}\
+.goal{margin:.5rem 0}.bar{height:.6rem;background:#f3f4f6;border-radius:.3rem;overflow:hidden;margin-top:.25rem}\
+.bar span{display:block;height:100%;background:#4f46e5}\
+.chart{display:flex;align-items:flex-end;gap:.4rem;height:180px;border-bottom:1px solid #e5e7eb;padding-bottom:1.4rem}\
+.col{flex:1;height:100%;display:flex;flex-direction:column;justify-content:flex-end;align-items:center;position:relative}\
+.col .b{width:100%;background:#4f46e5;border-radius:.25rem .25rem 0 0}.col .n{font-size:.75rem}\
+.col .l{position:absolute;bottom:-1.3rem;font-size:.75rem;color:#6b7280}\
+dt{font-weight:600;margin-top:.6rem}dd{margin:0}";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::apply_schema;
+
+    #[test]
+    fn months_and_undated_add_up_to_the_total() {
+        let conn = Connection::open_in_memory().unwrap();
+        apply_schema(&conn).unwrap();
+        let books = [
+            ("Dated", Some(2024), Some("2024/03/05")),
+            ("Finished late", Some(2024), Some("2023/12/30")),
+            ("No date", Some(2024), None),
+            ("Other year", Some(2023), Some("2024-01-02")),
+        ];
+        for (title, year_read, date_read) in books {
+            conn.execute(
+                "INSERT INTO books (title, author, pages, exclusive_shelf, year_read, date_read)
+                 VALUES (?1, 'Someone', 200, 'read', ?2, ?3)",
+                rusqlite::params![title, year_read, date_read],
+            )
+            .unwrap();
+        }
+        crate::beq::sync(&conn).unwrap();
+
+        let review = build(&conn, 2024).unwrap();
+        let charted: i64 = review.months.points.iter().map(|m| m.books).sum();
+        assert_eq!(review.totals.books, 3);
+        assert_eq!(charted, 1);
+        assert_eq!(review.months.points[2].books, 1);
+        assert_eq!(review.months.undated, 2);
+        assert_eq!(charted + review.months.undated, review.totals.books);
+    }
+}
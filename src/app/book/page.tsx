@@ -2,7 +2,7 @@
 
 import { Suspense, useEffect, useState } from "react";
 import { useSearchParams } from "next/navigation";
-import { getBook, getShelves, type BookWithShelves, type ShelfWithCount } from "@/lib/tauri";
+import { getBook, getBookTypes, getShelves, type BookType, type BookWithShelves, type ShelfWithCount } from "@/lib/tauri";
 import { BookForm } from "@/components/book-form";
 import { CoverImage } from "@/components/cover-image";
 import { RatingStars } from "@/components/rating-stars";
@@ -25,6 +25,7 @@ function BookDetailInner() {
 
   const [book, setBook] = useState<BookWithShelves | null>(null);
   const [shelves, setShelves] = useState<ShelfWithCount[]>([]);
+  const [bookTypes, setBookTypes] = useState<BookType[]>([]);
   const [loading, setLoading] = useState(true);
 
   useEffect(() => {
@@ -43,6 +44,8 @@ function BookDetailInner() {
     load();
   }, [id, edit]);
 
+  useEffect(() => { getBookTypes().then(setBookTypes); }, []);
+
   if (loading) return <p className="text-muted-foreground">Loading...</p>;
   if (!book) return <p className="text-muted-foreground">Book not found.</p>;
 
@@ -58,7 +61,7 @@ function BookDetailInner() {
   return (
     <div className="max-w-2xl">
       <div className="flex items-start gap-6 mb-6">
-        <CoverImage url={book.coverUrl} title={book.title} size="lg" />
+        <CoverImage url={book.coverUrl} hash={book.coverHash} title={book.title} size="lg" />
         <div className="flex-1 space-y-2">
           <h2 className="text-2xl font-bold">{book.title}</h2>
           <p className="text-lg text-muted-foreground">{book.author}</p>
@@ -91,11 +94,12 @@ function BookDetailInner() {
         <Detail label="Binding" value={book.binding} />
         <Detail label="Pages" value={book.pages?.toString()} />
         <Detail label="BEq" value={book.beq?.toFixed(2)} />
-        <Detail label="Book Type" value={book.bookType === "graphic_novel" ? "Graphic Novel" : "Traditional"} />
+        <Detail label="Book Type" value={bookTypes.find((t) => t.name === book.bookType)?.label ?? book.bookType} />
         <Detail label="ISBN" value={book.isbn} />
         <Detail label="ISBN-13" value={book.isbn13} />
         <Detail label="Published" value={book.yearPublished?.toString()} />
         <Detail label="Edition" value={book.editionPublished?.toString()} />
+        <Detail label="Date Started" value={formatDate(book.dateStarted)} />
         <Detail label="Date Read" value={formatDate(book.dateRead)} />
         <Detail label="Date Added" value={formatDate(book.dateAdded)} />
         <Detail label="Read Count" value={book.readCount?.toString()} />
@@ -0,0 +1,168 @@
+"use client";
+
+import { useCallback, useEffect, useState, useTransition } from "react";
+import Link from "next/link";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Badge } from "@/components/ui/badge";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { Plus, Trash2 } from "lucide-react";
+import { deleteGoal, getGoals, saveGoal, type Goal, type GoalMetric, type GoalPace, type GoalPeriod, type GoalProgress } from "@/lib/tauri";
+
+const MONTHS = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+
+const PACE_LABELS: Record<GoalPace, string> = {
+  complete: "Complete",
+  ahead: "On pace",
+  behind: "Behind",
+  upcoming: "Not started",
+  missed: "Missed",
+};
+
+const METRIC_LABELS: Record<GoalMetric, string> = { books: "books", pages: "pages", beq: "BEq" };
+
+function emptyGoal(year: number): Goal {
+  return { id: null, name: "", period: "year", year, month: null, metric: "books", target: 12, filter: null, distinctBy: null };
+}
+
+export default function GoalsPage() {
+  const thisYear = new Date().getFullYear();
+  const [year, setYear] = useState(thisYear);
+  const [goals, setGoals] = useState<GoalProgress[] | null>(null);
+  const [draft, setDraft] = useState<Goal>(emptyGoal(thisYear));
+  const [error, setError] = useState<string | null>(null);
+  const [isPending, startTransition] = useTransition();
+
+  const refresh = useCallback(() => { getGoals(year).then(setGoals); }, [year]);
+
+  useEffect(() => { refresh(); }, [refresh]);
+
+  function handleSave(e: React.FormEvent) {
+    e.preventDefault();
+    setError(null);
+    startTransition(async () => {
+      try {
+        await saveGoal(draft);
+        setDraft(emptyGoal(year));
+        refresh();
+      } catch (err) {
+        setError(String(err));
+      }
+    });
+  }
+
+  function handleDelete(goal: GoalProgress) {
+    if (goal.id === null || !confirm(`Delete goal "${goal.name}"?`)) return;
+    startTransition(async () => {
+      await deleteGoal(goal.id!);
+      refresh();
+    });
+  }
+
+  if (!goals) return <p className="text-muted-foreground">Loading...</p>;
+
+  return (
+    <div className="space-y-6">
+      <div className="flex items-center justify-between">
+        <h2 className="text-2xl font-bold">Goals &amp; Challenges</h2>
+        <Select value={String(year)} onValueChange={(v) => { setYear(parseInt(v)); setDraft(emptyGoal(parseInt(v))); }}>
+          <SelectTrigger className="w-28"><SelectValue /></SelectTrigger>
+          <SelectContent>
+            {[thisYear + 1, thisYear, thisYear - 1, thisYear - 2].map((y) => (
+              <SelectItem key={y} value={String(y)}>{y}</SelectItem>
+            ))}
+          </SelectContent>
+        </Select>
+      </div>
+
+      {goals.map((goal) => (
+        <Card key={goal.id}>
+          <CardHeader>
+            <CardTitle className="text-lg flex items-center gap-2">
+              {goal.name}
+              <Badge variant={goal.pace === "behind" || goal.pace === "missed" ? "destructive" : "secondary"}>{PACE_LABELS[goal.pace]}</Badge>
+              <Button size="icon" variant="ghost" className="h-7 w-7 ml-auto text-muted-foreground hover:text-destructive" onClick={() => handleDelete(goal)} disabled={isPending}>
+                <Trash2 className="h-3.5 w-3.5" />
+              </Button>
+            </CardTitle>
+            <CardDescription>
+              {goal.month ? `${MONTHS[goal.month - 1]} ${goal.year}` : goal.year}
+              {" · "}
+              {goal.current} / {goal.target} {goal.distinctBy ? `different ${goal.distinctBy.replace(/^shelf:/, "")}` : METRIC_LABELS[goal.metric]}
+              {goal.filter && ` · ${goal.filter}`}
+              {(goal.pace === "ahead" || goal.pace === "behind") && ` · ${goal.expected} expected by today, on track for ${goal.projected}`}
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-3">
+            <div className="h-3 bg-muted rounded overflow-hidden">
+              <div className="h-full bg-primary/70 rounded" style={{ width: `${goal.percent}%` }} />
+            </div>
+            {goal.books.length > 0 && (
+              <ul className="text-sm space-y-1">
+                {goal.books.map((book) => (
+                  <li key={book.id} className="flex gap-2">
+                    <Link href={`/book?id=${book.id}`} className="hover:underline">{book.title}</Link>
+                    <span className="text-muted-foreground">{book.author}</span>
+                    {book.countedAs && <Badge variant="outline">{book.countedAs}</Badge>}
+                    <span className="text-muted-foreground ml-auto">{book.dateRead}</span>
+                  </li>
+                ))}
+              </ul>
+            )}
+          </CardContent>
+        </Card>
+      ))}
+      {goals.length === 0 && <p className="text-sm text-muted-foreground">No goals for {year} yet.</p>}
+
+      <Card>
+        <CardHeader>
+          <CardTitle className="text-lg">New Goal</CardTitle>
+          <CardDescription>
+            Filter with terms like <code>shelf:classics</code> or <code>type:graphic_novel</code>. Challenges can count distinct
+            authors, publishers, languages or shelves. Books have no country field, so for &quot;12 books from different
+            countries&quot; put each book on a shelf like country-france and count <code>shelf:country-</code>.
+          </CardDescription>
+        </CardHeader>
+        <CardContent>
+          <form onSubmit={handleSave} className="grid grid-cols-2 gap-4 max-w-2xl">
+            <div className="space-y-2 col-span-2"><Label>Name</Label><Input value={draft.name} onChange={(e) => setDraft({ ...draft, name: e.target.value })} placeholder="Read 52 books" /></div>
+            <div className="space-y-2">
+              <Label>Period</Label>
+              <Select value={draft.month ? String(draft.month) : "year"} onValueChange={(v) => setDraft({ ...draft, period: (v === "year" ? "year" : "month") as GoalPeriod, month: v === "year" ? null : parseInt(v) })}>
+                <SelectTrigger><SelectValue /></SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="year">All of {year}</SelectItem>
+                  {MONTHS.map((m, i) => <SelectItem key={m} value={String(i + 1)}>{m} {year}</SelectItem>)}
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="space-y-2">
+              <Label>Target</Label>
+              <div className="flex gap-2">
+                <Input type="number" min={1} value={draft.target} onChange={(e) => setDraft({ ...draft, target: parseFloat(e.target.value) || 0 })} />
+                <Select value={draft.metric} onValueChange={(v) => setDraft({ ...draft, metric: v as GoalMetric })}>
+                  <SelectTrigger className="w-28"><SelectValue /></SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="books">books</SelectItem>
+                    <SelectItem value="pages">pages</SelectItem>
+                    <SelectItem value="beq">BEq</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+            </div>
+            <div className="space-y-2"><Label>Filter</Label><Input value={draft.filter ?? ""} onChange={(e) => setDraft({ ...draft, filter: e.target.value || null })} placeholder="shelf:classics" /></div>
+            <div className="space-y-2"><Label>Count distinct</Label><Input value={draft.distinctBy ?? ""} onChange={(e) => setDraft({ ...draft, distinctBy: e.target.value || null })} placeholder="author, publisher, shelf:country-" /></div>
+            {error && <p className="text-sm text-destructive col-span-2">{error}</p>}
+            <div className="col-span-2">
+              <Button type="submit" disabled={isPending || !draft.name.trim()}>
+                <Plus className="h-4 w-4 mr-1" /> Add Goal
+              </Button>
+            </div>
+          </form>
+        </CardContent>
+      </Card>
+    </div>
+  );
+}
@@ -3,7 +3,7 @@
 import { Suspense, useEffect, useState, useCallback } from "react";
 import { useSearchParams } from "next/navigation";
 import { BookTable } from "@/components/book-table";
-import { getBooks, getShelves, type Book, type ShelfWithCount, type SortField, type SortDir } from "@/lib/tauri";
+import { getBooks, getShelves, type Book, type ShelfWithCount, type SortField, type SortDir, type CoverColor } from "@/lib/tauri";
 
 export default function HomePage() {
   return <Suspense fallback={<p className="text-muted-foreground">Loading...</p>}><HomeInner /></Suspense>;
@@ -21,11 +21,12 @@ function HomeInner() {
   const page = parseInt(searchParams.get("page") || "1") || 1;
   const exclusiveShelf = searchParams.get("exclusiveShelf") || "";
   const shelf = searchParams.get("shelf") || "";
+  const coverColor = (searchParams.get("coverColor") as CoverColor) || undefined;
 
   const fetchData = useCallback(async () => {
     try {
       const [booksResult, shelvesResult] = await Promise.all([
-        getBooks({ search, sortBy, sortDir, page, limit: 50, exclusiveShelf: exclusiveShelf || undefined, shelf: shelf || undefined }),
+        getBooks({ search, sortBy, sortDir, page, limit: 50, exclusiveShelf: exclusiveShelf || undefined, shelf: shelf || undefined, coverColor }),
         getShelves(),
       ]);
       setBooks(booksResult.books);
@@ -34,14 +35,14 @@ function HomeInner() {
     } catch (e) {
       console.error("Failed to fetch books:", e);
     }
-  }, [search, sortBy, sortDir, page, exclusiveShelf, shelf]);
+  }, [search, sortBy, sortDir, page, exclusiveShelf, shelf, coverColor]);
 
   useEffect(() => {
     let cancelled = false;
     (async () => {
       try {
         const [booksResult, shelvesResult] = await Promise.all([
-          getBooks({ search, sortBy, sortDir, page, limit: 50, exclusiveShelf: exclusiveShelf || undefined, shelf: shelf || undefined }),
+          getBooks({ search, sortBy, sortDir, page, limit: 50, exclusiveShelf: exclusiveShelf || undefined, shelf: shelf || undefined, coverColor }),
           getShelves(),
         ]);
         if (!cancelled) {
@@ -54,7 +55,7 @@ function HomeInner() {
       }
     })();
     return () => { cancelled = true; };
-  }, [search, sortBy, sortDir, page, exclusiveShelf, shelf]);
+  }, [search, sortBy, sortDir, page, exclusiveShelf, shelf, coverColor]);
 
   return (
     <div>
@@ -70,6 +71,7 @@ function HomeInner() {
         exclusiveShelf={exclusiveShelf}
         shelves={shelves}
         shelf={shelf}
+        coverColor={coverColor || ""}
         onRefresh={fetchData}
       />
     </div>
@@ -1,10 +1,12 @@
 "use client";
 
-import { useState } from "react";
+import { useEffect, useState } from "react";
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
 import { Button } from "@/components/ui/button";
-import { ImagePlus, Loader2, Trash2 } from "lucide-react";
-import { fixMissingCovers, clearDatabase } from "@/lib/tauri";
+import { BeqSettings } from "@/components/beq-settings";
+import { BookTypeSettings } from "@/components/book-type-settings";
+import { AlertTriangle, ImagePlus, Loader2, Trash2 } from "lucide-react";
+import { fixMissingCovers, generatePlaceholderCovers, extractCoverColors, clearDatabase, cancelJob, getRunningJobs, getSettings, onJobProgress, type CoverColorResult, type JobProgress } from "@/lib/tauri";
 import {
   AlertDialog,
   AlertDialogAction,
@@ -19,19 +21,61 @@ import {
 
 export default function SettingsPage() {
   const [fixing, setFixing] = useState(false);
-  const [coverResult, setCoverResult] = useState<{ fixed: number; checked: number } | null>(null);
+  const [coverProgress, setCoverProgress] = useState<JobProgress | null>(null);
+  const [coverError, setCoverError] = useState<string | null>(null);
+  const [generating, setGenerating] = useState(false);
+  const [generated, setGenerated] = useState<number | null>(null);
+  const [colorsUpdated, setColorsUpdated] = useState<CoverColorResult | null>(null);
+  const [backgroundErrors, setBackgroundErrors] = useState<string[]>([]);
+
+  useEffect(() => {
+    getSettings().then((settings) => {
+      const errors: string[] = [];
+      if (settings.last_backup_error) errors.push(`Automatic backup failed at ${settings.last_backup_error}`);
+      if (settings.last_purge_error) errors.push(`Trash purge failed at ${settings.last_purge_error}`);
+      setBackgroundErrors(errors);
+    });
+    getRunningJobs().then((jobs) => setFixing(jobs.includes("fix-covers")));
+    const unlisten = onJobProgress("fix-covers-progress", (progress) => {
+      setCoverProgress(progress);
+      if (progress.error) setCoverError(progress.title ? `${progress.title}: ${progress.error}` : progress.error);
+      if (progress.done) setFixing(false);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
   async function handleFixCovers() {
     setFixing(true);
-    setCoverResult(null);
+    setCoverProgress(null);
+    setCoverError(null);
     try {
-      const res = await fixMissingCovers();
-      setCoverResult(res);
-    } finally {
+      const total = await fixMissingCovers();
+      if (total === 0) setFixing(false);
+    } catch {
       setFixing(false);
     }
   }
 
+  async function handleGeneratePlaceholders() {
+    setGenerating(true);
+    try {
+      setGenerated(await generatePlaceholderCovers());
+    } finally {
+      setGenerating(false);
+    }
+  }
+
+  async function handleExtractColors() {
+    setGenerating(true);
+    try {
+      setColorsUpdated(await extractCoverColors());
+    } finally {
+      setGenerating(false);
+    }
+  }
+
   async function handleClearDatabase() {
     await clearDatabase();
     window.location.href = "/";
@@ -41,6 +85,23 @@ export default function SettingsPage() {
     <div className="space-y-6">
       <h1 className="text-2xl font-bold">Settings</h1>
 
+      {backgroundErrors.length > 0 && (
+        <Card className="border-destructive/50">
+          <CardHeader>
+            <CardTitle className="flex items-center gap-2 text-destructive">
+              <AlertTriangle className="h-5 w-5" />
+              Background Tasks
+            </CardTitle>
+            <CardDescription>These clear once the task next succeeds.</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-1">
+            {backgroundErrors.map((error) => (
+              <p key={error} className="text-sm text-destructive">{error}</p>
+            ))}
+          </CardContent>
+        </Card>
+      )}
+
       <Card>
         <CardHeader>
           <CardTitle className="flex items-center gap-2">
@@ -52,18 +113,47 @@ export default function SettingsPage() {
           </CardDescription>
         </CardHeader>
         <CardContent className="space-y-3">
-          <Button onClick={handleFixCovers} disabled={fixing}>
-            {fixing ? <Loader2 className="h-4 w-4 mr-2 animate-spin" /> : <ImagePlus className="h-4 w-4 mr-2" />}
-            {fixing ? "Fetching..." : "Re-fetch Covers"}
-          </Button>
-          {coverResult && (
+          <div className="flex gap-2">
+            <Button onClick={handleFixCovers} disabled={fixing}>
+              {fixing ? <Loader2 className="h-4 w-4 mr-2 animate-spin" /> : <ImagePlus className="h-4 w-4 mr-2" />}
+              {fixing ? "Fetching..." : "Re-fetch Covers"}
+            </Button>
+            {fixing && (
+              <Button variant="outline" onClick={() => cancelJob("fix-covers")}>Cancel</Button>
+            )}
+            <Button variant="outline" onClick={handleGeneratePlaceholders} disabled={generating}>
+              {generating && <Loader2 className="h-4 w-4 mr-2 animate-spin" />}
+              Generate Placeholders
+            </Button>
+            <Button variant="outline" onClick={handleExtractColors} disabled={generating}>
+              Extract Colors
+            </Button>
+          </div>
+          {generated !== null && (
+            <p className="text-sm text-muted-foreground">Generated {generated} placeholder covers.</p>
+          )}
+          {colorsUpdated !== null && (
             <p className="text-sm text-muted-foreground">
-              Updated {coverResult.fixed} of {coverResult.checked} covers.
+              Extracted colors for {colorsUpdated.booksUpdated} books.
+              {colorsUpdated.failures.length > 0 && ` ${colorsUpdated.failures.length} covers couldn't be read: ${colorsUpdated.failures.join("; ")}`}
             </p>
           )}
+          {coverProgress && (
+            <p className="text-sm text-muted-foreground">
+              {coverProgress.done
+                ? `${coverProgress.cancelled ? "Stopped" : "Done"}: updated ${coverProgress.succeeded} of ${coverProgress.processed} covers checked.`
+                : `Checked ${coverProgress.processed} of ${coverProgress.total}, updated ${coverProgress.succeeded}${coverProgress.title ? ` — ${coverProgress.title}` : ""}`}
+              {coverProgress.failed > 0 && ` ${coverProgress.failed} failed and will be retried next run.`}
+            </p>
+          )}
+          {coverError && <p className="text-sm text-destructive">Last error: {coverError}</p>}
         </CardContent>
       </Card>
 
+      <BeqSettings />
+
+      <BookTypeSettings />
+
       <Card className="border-destructive/50">
         <CardHeader>
           <CardTitle className="flex items-center gap-2 text-destructive">
@@ -1,9 +1,11 @@
 "use client";
 
 import { useEffect, useState } from "react";
-import { getStats, type Stats } from "@/lib/tauri";
-import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
-import { BookOpen, Star, BarChart3, Calendar } from "lucide-react";
+import { exportYearReview, getBreakdowns, getRatingAnalysis, getReadingPace, getReadingSeries, getStats, getYearReview, type BreakdownRow, type BreakdownSort, type Breakdowns, type RatingAnalysis, type RatingCorrelation, type RatingGap, type ReadingPace, type ReadingSeries, type ReviewBook, type SeriesBucket, type Stats, type TimedRead, type YearReview, type YearReviewExport } from "@/lib/tauri";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { BookOpen, Star, BarChart3, Calendar, Flame, Timer, FileDown, Gift } from "lucide-react";
+import Link from "next/link";
 
 export default function StatsPage() {
   const [stats, setStats] = useState<Stats | null>(null);
@@ -12,6 +14,11 @@ export default function StatsPage() {
 
   if (!stats) return <p className="text-muted-foreground">Loading...</p>;
 
+  const divisorLabel = (pages: number | null) => {
+    if (!pages) return undefined;
+    return `÷ ${Math.round(pages)} ${stats.beqMode === "fixed" ? "pp" : stats.beqMode === "snapshot" ? "pp (snapshot)" : "avg pp"}`;
+  };
+
   return (
     <div className="space-y-6">
       <h2 className="text-2xl font-bold">Reading Stats</h2>
@@ -23,10 +30,19 @@ export default function StatsPage() {
         <StatCard icon={Star} label="Avg Rating" value={stats.avgRating} />
         <StatCard icon={Calendar} label="Books This Year" value={stats.booksThisYear} />
         <StatCard icon={BarChart3} label="BEq This Year" value={stats.beqThisYear} />
-        <StatCard icon={BarChart3} label="BEq_B (Traditional)" value={stats.totalBeqTraditional} subtitle={stats.avgPagesTraditional > 0 ? `÷ ${Math.round(stats.avgPagesTraditional)} avg pp` : undefined} />
-        <StatCard icon={BarChart3} label="BEq_G (Graphic Novel)" value={stats.totalBeqGraphicNovel} subtitle={stats.avgPagesGraphicNovel > 0 ? `÷ ${Math.round(stats.avgPagesGraphicNovel)} avg pp` : undefined} />
+        {stats.byType.map((t) => (
+          <StatCard key={t.bookType} icon={BarChart3} label={`BEq (${t.label})`} value={t.beq} subtitle={divisorLabel(t.beqDivisor)} />
+        ))}
       </div>
 
+      <YearReviewSection years={stats.byYear.map((r) => r.year).filter((y): y is number => y !== null)} />
+
+      <PaceSection />
+
+      <ReadingVolume years={stats.byYear.map((r) => r.year).filter((y): y is number => y !== null)} />
+
+      <BreakdownSection years={stats.byYear.map((r) => r.year).filter((y): y is number => y !== null)} />
+
       {/* Books by Year */}
       <Card>
         <CardHeader>
@@ -52,6 +68,8 @@ export default function StatsPage() {
         </CardContent>
       </Card>
 
+      <RatingSection />
+
       {/* Rating Distribution */}
       <Card>
         <CardHeader>
@@ -83,6 +101,376 @@ export default function StatsPage() {
   );
 }
 
+function BreakdownSection({ years }: { years: number[] }) {
+  const [year, setYear] = useState<number | undefined>(undefined);
+  const [sortBy, setSortBy] = useState<BreakdownSort>("books");
+  const [data, setData] = useState<Breakdowns | null>(null);
+
+  useEffect(() => { getBreakdowns({ year, sortBy }).then(setData); }, [year, sortBy]);
+
+  if (!data) return null;
+  const value = (row: BreakdownRow) => (sortBy === "beq" ? row.beq : sortBy === "pages" ? row.pages : row.books);
+
+  return (
+    <div className="space-y-4">
+      <div className="flex flex-wrap items-center gap-2">
+        <h3 className="text-lg font-semibold mr-2">Breakdowns</h3>
+        <Button size="sm" variant={year === undefined ? "default" : "ghost"} onClick={() => setYear(undefined)}>All time</Button>
+        {years.slice(0, 5).map((y) => (
+          <Button key={y} size="sm" variant={year === y ? "default" : "ghost"} onClick={() => setYear(y)}>{y}</Button>
+        ))}
+        <span className="w-4" />
+        {(["books", "pages", "beq"] as const).map((m) => (
+          <Button key={m} size="sm" variant={sortBy === m ? "default" : "outline"} onClick={() => setSortBy(m)}>
+            {m === "beq" ? "BEq" : m[0].toUpperCase() + m.slice(1)}
+          </Button>
+        ))}
+      </div>
+      <div className="grid md:grid-cols-2 gap-4">
+        <BarList title="Top Authors" rows={data.authors} value={value} />
+        <BarList title="Top Publishers" rows={data.publishers} value={value} />
+        <BarList title="Shelves" rows={data.shelves} value={(r) => r.books} showRating />
+        <BarList title="Book Types" rows={data.bookTypes} value={(r) => r.books} />
+        <BarList title="Publication Decade" rows={data.decades} value={(r) => r.books} />
+        <BarList title="Page Length" rows={data.pageLengths} value={(r) => r.books} />
+      </div>
+    </div>
+  );
+}
+
+function BarList({ title, rows, value, showRating }: { title: string; rows: BreakdownRow[]; value: (row: BreakdownRow) => number; showRating?: boolean }) {
+  const max = Math.max(...rows.map(value), 1);
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg">{title}</CardTitle>
+      </CardHeader>
+      <CardContent>
+        {rows.length === 0 && <p className="text-sm text-muted-foreground">No read books.</p>}
+        <div className="space-y-1.5">
+          {rows.map((row) => (
+            <div key={row.key} className="flex items-center gap-3">
+              <span className="text-sm w-32 truncate" title={row.label}>{row.label}</span>
+              <div className="flex-1 h-4 bg-muted rounded overflow-hidden">
+                <div className="h-full bg-primary/70 rounded" style={{ width: `${(value(row) / max) * 100}%` }} />
+              </div>
+              <span className="text-xs text-muted-foreground w-24 text-right">
+                {value(row)}
+                {showRating && row.avgRating !== null && ` · ${row.avgRating}★`}
+              </span>
+            </div>
+          ))}
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
+
+function YearReviewSection({ years }: { years: number[] }) {
+  const [year, setYear] = useState(new Date().getFullYear());
+  const [review, setReview] = useState<YearReview | null>(null);
+  const [exported, setExported] = useState<YearReviewExport | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => { setExported(null); getYearReview(year).then(setReview); }, [year]);
+
+  async function handleExport() {
+    setError(null);
+    try {
+      setExported(await exportYearReview(year));
+    } catch (e) {
+      setError(String(e));
+    }
+  }
+
+  if (!review) return null;
+  const { totals, previous } = review;
+  const delta = (now: number, before: number) => (before ? `${now >= before ? "+" : ""}${Math.round((now - before) * 100) / 100} vs ${previous.year}` : undefined);
+  const highlights: [string, ReviewBook | null][] = [
+    ["First", review.firstBook],
+    ["Last", review.lastBook],
+    ["Longest", review.longestBook],
+    ["Shortest", review.shortestBook],
+    ["Highest rated", review.highestRated],
+    ["Lowest rated", review.lowestRated],
+  ];
+  const yearOptions = Array.from(new Set([new Date().getFullYear(), ...years])).sort((a, b) => b - a).slice(0, 6);
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg flex items-center gap-2">
+          <Gift className="h-5 w-5" /> {review.year} in Books
+          <span className="ml-auto flex gap-1">
+            {yearOptions.map((y) => (
+              <Button key={y} size="sm" variant={year === y ? "default" : "ghost"} onClick={() => setYear(y)}>{y}</Button>
+            ))}
+            <Button size="sm" variant="outline" onClick={handleExport}>
+              <FileDown className="h-4 w-4 mr-1" /> Export
+            </Button>
+          </span>
+        </CardTitle>
+        {exported && (
+          <CardDescription>
+            Saved {exported.htmlPath} and {exported.jsonPath}
+          </CardDescription>
+        )}
+        {error && <p className="text-sm text-destructive">{error}</p>}
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
+          <StatCard icon={BookOpen} label="Books" value={totals.books} subtitle={delta(totals.books, previous.books)} />
+          <StatCard icon={BookOpen} label="Pages" value={totals.pages} subtitle={delta(totals.pages, previous.pages)} />
+          <StatCard icon={BarChart3} label="BEq" value={totals.beq} subtitle={delta(totals.beq, previous.beq)} />
+          <StatCard icon={Star} label="Avg Rating" value={totals.avgRating ?? 0} />
+        </div>
+        {review.goals.map((goal) => (
+          <div key={goal.id} className="space-y-1">
+            <div className="text-sm">{goal.name} — {goal.current} / {goal.target}</div>
+            <div className="h-2 bg-muted rounded overflow-hidden">
+              <div className="h-full bg-primary/70 rounded" style={{ width: `${goal.percent}%` }} />
+            </div>
+          </div>
+        ))}
+        <ul className="text-sm space-y-1">
+          {highlights.map(([label, book]) => book && (
+            <li key={label} className="flex gap-2">
+              <span className="text-muted-foreground w-28">{label}</span>
+              <Link href={`/book?id=${book.id}`} className="hover:underline truncate">{book.title}</Link>
+              <span className="text-muted-foreground truncate">{book.author}</span>
+            </li>
+          ))}
+          {review.mostReadAuthor && (
+            <li className="flex gap-2">
+              <span className="text-muted-foreground w-28">Most-read author</span>
+              <span>{review.mostReadAuthor.label}</span>
+              <span className="text-muted-foreground">{review.mostReadAuthor.books} books</span>
+            </li>
+          )}
+        </ul>
+      </CardContent>
+    </Card>
+  );
+}
+
+function RatingSection() {
+  const [analysis, setAnalysis] = useState<RatingAnalysis | null>(null);
+
+  useEffect(() => { getRatingAnalysis().then(setAnalysis); }, []);
+
+  if (!analysis || analysis.ratedBooks === 0) return null;
+  const signed = (v: number | null) => (v === null ? "—" : `${v > 0 ? "+" : ""}${v.toFixed(2)}`);
+
+  return (
+    <div className="space-y-4">
+      <h3 className="text-lg font-semibold">You vs. the Community</h3>
+      <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
+        <StatCard icon={Star} label="Rated Books" value={analysis.ratedBooks} subtitle={`${analysis.comparedBooks} with a community average`} />
+        <StatCard icon={Star} label="Mean Deviation" value={analysis.meanDeviation ?? 0} subtitle={analysis.meanDeviation === null ? undefined : analysis.meanDeviation >= 0 ? "stars above average" : "stars below average"} />
+        <StatCard icon={Star} label="Mean Abs. Deviation" value={analysis.meanAbsDeviation ?? 0} subtitle="stars" />
+        <StatCard icon={BarChart3} label="Agreement" value={analysis.communityCorrelation ?? 0} subtitle="correlation with community" />
+      </div>
+      <div className="grid md:grid-cols-2 gap-4">
+        <RatingGapList title="Liked Much More" gaps={analysis.likedMore} />
+        <RatingGapList title="Liked Much Less" gaps={analysis.likedLess} />
+        <RatingCorrelationCard title="Rating by Page Length" data={analysis.pages} />
+        <RatingCorrelationCard title="Rating by Publication Decade" data={analysis.yearPublished} />
+      </div>
+      <Card>
+        <CardHeader>
+          <CardTitle className="text-lg">Rating by Book Type</CardTitle>
+        </CardHeader>
+        <CardContent>
+          <ul className="text-sm space-y-1">
+            {analysis.byType.map((t) => (
+              <li key={t.key} className="flex gap-2">
+                <span className="flex-1">{t.label}</span>
+                <span className="text-muted-foreground">{t.books} books</span>
+                <span className="w-16 text-right">{t.avgMyRating.toFixed(2)} ★</span>
+                <span className="w-28 text-right text-muted-foreground">{t.avgCommunity?.toFixed(2) ?? "—"} ({signed(t.meanDeviation)})</span>
+              </li>
+            ))}
+          </ul>
+        </CardContent>
+      </Card>
+    </div>
+  );
+}
+
+function RatingGapList({ title, gaps }: { title: string; gaps: RatingGap[] }) {
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg">{title}</CardTitle>
+      </CardHeader>
+      <CardContent>
+        {gaps.length === 0 && <p className="text-sm text-muted-foreground">None.</p>}
+        <ul className="text-sm space-y-1">
+          {gaps.map((g) => (
+            <li key={g.id} className="flex gap-2">
+              <Link href={`/book?id=${g.id}`} className="hover:underline truncate">{g.title}</Link>
+              <span className="text-muted-foreground truncate">{g.author}</span>
+              <span className="ml-auto whitespace-nowrap">{"★".repeat(g.myRating)}</span>
+              <span className="text-muted-foreground w-20 text-right whitespace-nowrap">vs {g.averageRating.toFixed(2)}</span>
+            </li>
+          ))}
+        </ul>
+      </CardContent>
+    </Card>
+  );
+}
+
+function RatingCorrelationCard({ title, data }: { title: string; data: RatingCorrelation }) {
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg">{title}</CardTitle>
+        <CardDescription>
+          Correlation: yours {data.myCorrelation?.toFixed(2) ?? "—"}, community {data.communityCorrelation?.toFixed(2) ?? "—"} ({data.books} books)
+        </CardDescription>
+      </CardHeader>
+      <CardContent>
+        <div className="space-y-2">
+          {data.buckets.map((b) => (
+            <div key={b.key} className="flex items-center gap-3">
+              <span className="text-sm w-20 truncate">{b.label}</span>
+              <div className="flex-1 h-4 bg-muted rounded overflow-hidden">
+                <div className="h-full bg-yellow-400/70 rounded" style={{ width: `${(b.avgMyRating / 5) * 100}%` }} />
+              </div>
+              <span className="text-sm text-muted-foreground w-28 text-right">
+                {b.avgMyRating.toFixed(2)} / {b.avgCommunity?.toFixed(2) ?? "—"}
+              </span>
+            </div>
+          ))}
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
+
+function PaceSection() {
+  const [pace, setPace] = useState<ReadingPace | null>(null);
+
+  useEffect(() => { getReadingPace().then(setPace); }, []);
+
+  if (!pace) return null;
+
+  return (
+    <div className="space-y-4">
+      <div className="grid grid-cols-2 md:grid-cols-4 gap-4">
+        <StatCard icon={Flame} label="Current Streak" value={pace.currentStreak} subtitle="days" />
+        <StatCard
+          icon={Flame}
+          label="Longest Streak"
+          value={pace.longestStreak}
+          subtitle={pace.longestStreakStart ? `${pace.longestStreakStart} – ${pace.longestStreakEnd}` : undefined}
+        />
+        <StatCard icon={Timer} label="Avg Days to Finish" value={pace.avgDaysToFinish ?? 0} subtitle={`${pace.timedBooks} books with a start date`} />
+        {pace.byType.map((t) => (
+          <StatCard key={t.bookType} icon={Timer} label={`Pages/Day (${t.label})`} value={t.pagesPerDay} subtitle={`${t.books} books, ${t.avgDays} days avg`} />
+        ))}
+      </div>
+      {pace.fastest.length > 0 && (
+        <div className="grid md:grid-cols-2 gap-4">
+          <TimedReadList title="Fastest Reads" reads={pace.fastest} />
+          <TimedReadList title="Slowest Reads" reads={pace.slowest} />
+        </div>
+      )}
+    </div>
+  );
+}
+
+function TimedReadList({ title, reads }: { title: string; reads: TimedRead[] }) {
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg">{title}</CardTitle>
+      </CardHeader>
+      <CardContent>
+        <ul className="text-sm space-y-1">
+          {reads.map((r) => (
+            <li key={r.id} className="flex gap-2">
+              <Link href={`/book?id=${r.id}`} className="hover:underline truncate">{r.title}</Link>
+              <span className="text-muted-foreground ml-auto whitespace-nowrap">
+                {r.pagesPerDay} pp/day · {r.days} {r.days === 1 ? "day" : "days"}
+              </span>
+            </li>
+          ))}
+        </ul>
+      </CardContent>
+    </Card>
+  );
+}
+
+type VolumeMetric = "books" | "pages" | "beq";
+
+const ROLLING: Record<VolumeMetric, "rollingBooks" | "rollingPages" | "rollingBeq"> = {
+  books: "rollingBooks",
+  pages: "rollingPages",
+  beq: "rollingBeq",
+};
+
+function ReadingVolume({ years }: { years: number[] }) {
+  const [bucket, setBucket] = useState<SeriesBucket>("month");
+  const [metric, setMetric] = useState<VolumeMetric>("books");
+  const [year, setYear] = useState<number | undefined>(undefined);
+  const [series, setSeries] = useState<ReadingSeries | null>(null);
+
+  useEffect(() => { getReadingSeries(bucket, year).then(setSeries); }, [bucket, year]);
+
+  if (!series) return null;
+  const max = Math.max(...series.points.map((p) => Math.max(p[metric], p[ROLLING[metric]] ?? 0)), 1);
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="text-lg">Reading Volume {series.year}</CardTitle>
+        <CardDescription>
+          {series.window && `Line: ${series.window}-${bucket} rolling average. `}
+          {series.undated > 0 && `${series.undated} books read in ${series.year} have no full read date in ${series.year} and aren't shown.`}
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap gap-2">
+          {(["month", "week", "weekday"] as const).map((b) => (
+            <Button key={b} size="sm" variant={bucket === b ? "default" : "outline"} onClick={() => setBucket(b)}>
+              {b === "month" ? "Monthly" : b === "week" ? "Weekly" : "By weekday"}
+            </Button>
+          ))}
+          <span className="w-4" />
+          {(["books", "pages", "beq"] as const).map((m) => (
+            <Button key={m} size="sm" variant={metric === m ? "default" : "outline"} onClick={() => setMetric(m)}>
+              {m === "beq" ? "BEq" : m[0].toUpperCase() + m.slice(1)}
+            </Button>
+          ))}
+          <span className="w-4" />
+          {years.slice(0, 5).map((y) => (
+            <Button key={y} size="sm" variant={series.year === y ? "default" : "ghost"} onClick={() => setYear(y)}>{y}</Button>
+          ))}
+        </div>
+        <div className="flex items-end gap-px h-40">
+          {series.points.map((p) => {
+            const rolling = p[ROLLING[metric]];
+            return (
+              <div key={p.key} className="relative flex-1 h-full flex items-end" title={`${p.label}: ${p[metric]}${rolling !== null ? ` (avg ${rolling})` : ""}`}>
+                <div className="w-full bg-primary/70 rounded-t" style={{ height: `${(p[metric] / max) * 100}%` }} />
+                {rolling !== null && (
+                  <div className="absolute inset-x-0 border-t-2 border-orange-500" style={{ bottom: `${(rolling / max) * 100}%` }} />
+                )}
+              </div>
+            );
+          })}
+        </div>
+        <div className="flex gap-px text-[10px] text-muted-foreground">
+          {series.points.map((p, i) => (
+            <span key={p.key} className="flex-1 text-center">{bucket !== "week" || i % 4 === 0 ? p.label : ""}</span>
+          ))}
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
+
 function StatCard({ icon: Icon, label, value, subtitle }: { icon: React.ElementType; label: string; value: number; subtitle?: string }) {
   return (
     <Card>
@@ -0,0 +1,111 @@
+"use client";
+
+import { useEffect, useState } from "react";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Label } from "@/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { BarChart3 } from "lucide-react";
+import { getBeqConfig, getBookTypes, setBeqConfig, snapshotBeqAverages, type BeqConfig, type BeqMode, type BookType } from "@/lib/tauri";
+
+const MODE_DESCRIPTIONS: Record<BeqMode, string> = {
+  live: "Divide by the current average pages of read books of the same type. Every BEq shifts as books are added.",
+  snapshot: "Divide by the averages frozen when the snapshot was taken.",
+  fixed: "Divide by a fixed page count per book type.",
+};
+
+export function BeqSettings() {
+  const [config, setConfig] = useState<BeqConfig | null>(null);
+  const [bookTypes, setBookTypes] = useState<BookType[]>([]);
+  const [baselines, setBaselines] = useState<Record<string, string>>({});
+  const [saving, setSaving] = useState(false);
+
+  function load(next: BeqConfig, types: BookType[] = bookTypes) {
+    setConfig(next);
+    setBaselines(Object.fromEntries(types.map((t) => [t.name, String(next.baselines[t.name] ?? next.defaultBaseline)])));
+  }
+
+  useEffect(() => {
+    Promise.all([getBeqConfig(), getBookTypes()]).then(([next, types]) => {
+      setBookTypes(types);
+      load(next, types);
+    });
+  }, []);
+
+  async function save(mode: BeqMode) {
+    setSaving(true);
+    try {
+      const parsed = Object.fromEntries(
+        Object.entries(baselines)
+          .map(([type, pages]) => [type, parseFloat(pages)] as const)
+          .filter(([, pages]) => pages > 0),
+      );
+      load(await setBeqConfig(mode, parsed));
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  async function resnapshot() {
+    setSaving(true);
+    try {
+      load(await snapshotBeqAverages());
+    } finally {
+      setSaving(false);
+    }
+  }
+
+  if (!config) return null;
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <BarChart3 className="h-5 w-5" />
+          BEq Formula
+        </CardTitle>
+        <CardDescription>{MODE_DESCRIPTIONS[config.mode]}</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <Select value={config.mode} onValueChange={(v) => save(v as BeqMode)} disabled={saving}>
+          <SelectTrigger className="w-56"><SelectValue /></SelectTrigger>
+          <SelectContent>
+            <SelectItem value="live">Live average</SelectItem>
+            <SelectItem value="snapshot">Snapshot average</SelectItem>
+            <SelectItem value="fixed">Fixed baseline</SelectItem>
+          </SelectContent>
+        </Select>
+
+        <div className="grid grid-cols-2 gap-4 max-w-md">
+          {bookTypes.map((t) => {
+            const live = config.liveAverages[t.name];
+            const frozen = config.snapshot[t.name];
+            return (
+              <div key={t.name} className="space-y-1">
+                <Label>{t.label}</Label>
+                {config.mode === "fixed" ? (
+                  <Input type="number" min={1} value={baselines[t.name] ?? ""} onChange={(e) => setBaselines({ ...baselines, [t.name]: e.target.value })} />
+                ) : (
+                  <p className="text-sm text-muted-foreground">
+                    {config.mode === "snapshot" ? (frozen ? `${Math.round(frozen)} pp` : "—") : live ? `${Math.round(live)} avg pp` : "—"}
+                  </p>
+                )}
+              </div>
+            );
+          })}
+        </div>
+
+        {config.mode === "fixed" && (
+          <Button onClick={() => save("fixed")} disabled={saving}>Save Baselines</Button>
+        )}
+        {config.mode === "snapshot" && (
+          <div className="flex items-center gap-3">
+            <Button variant="outline" onClick={resnapshot} disabled={saving}>Take New Snapshot</Button>
+            {config.snapshotAt && <span className="text-sm text-muted-foreground">Taken {config.snapshotAt}</span>}
+          </div>
+        )}
+      </CardContent>
+    </Card>
+  );
+}
@@ -1,6 +1,6 @@
 "use client";
 
-import { useState, useTransition } from "react";
+import { useEffect, useState, useTransition } from "react";
 import { useRouter } from "next/navigation";
 import { Button } from "@/components/ui/button";
 import { Input } from "@/components/ui/input";
@@ -8,7 +8,7 @@ import { Label } from "@/components/ui/label";
 import { Textarea } from "@/components/ui/textarea";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
 import { RatingStars } from "./rating-stars";
-import { createBook, updateBook, lookupISBN, lookupCover, type BookWithShelves, type ShelfWithCount } from "@/lib/tauri";
+import { createBook, updateBook, lookupISBN, lookupCover, cacheBookCover, setCustomCover, getCoverCandidates, getBookTypes, type BookType, type CoverCandidate, type BookWithShelves, type NewBook, type ShelfWithCount } from "@/lib/tauri";
 import { getCoverUrl } from "@/lib/utils";
 import { Loader2, Search } from "lucide-react";
 import { CoverImage } from "./cover-image";
@@ -37,25 +37,47 @@ export function BookForm({ book, allShelves }: BookFormProps) {
   const [rating, setRating] = useState(book?.myRating || 0);
   const [exclusiveShelf, setExclusiveShelf] = useState(book?.exclusiveShelf || "to-read");
   const [dateRead, setDateRead] = useState(book?.dateRead || "");
+  const [dateStarted, setDateStarted] = useState(book?.dateStarted || "");
   const [review, setReview] = useState(book?.myReview || "");
   const [coverUrl, setCoverUrl] = useState(book?.coverUrl || "");
+  const [coverHash, setCoverHash] = useState(book?.coverHash || null);
+  const [candidates, setCandidates] = useState<CoverCandidate[] | null>(null);
+  const [loadingCandidates, setLoadingCandidates] = useState(false);
+  // Lookup-only fields the form has no inputs for, saved along with the book
+  const [lookupExtras, setLookupExtras] = useState<Partial<NewBook>>({});
   const [selectedShelves, setSelectedShelves] = useState<string[]>(
     book?.shelves?.map((s) => s.name) || []
   );
+  const [bookTypes, setBookTypes] = useState<BookType[]>([]);
+
+  useEffect(() => { getBookTypes().then(setBookTypes); }, []);
 
   async function handleIsbnLookup() {
     const lookupIsbn = isbn13 || isbn;
     if (!lookupIsbn) return;
     setFetching(true);
     try {
-      const data = await lookupISBN(lookupIsbn);
-      const fetchedTitle = data?.title || title;
-      const fetchedAuthor = data?.authors?.[0]?.name || author;
-      if (data) {
-        if (data.title && !title) setTitle(data.title);
-        if (data.authors?.[0]?.name && !author) setAuthor(data.authors[0].name);
-        if (data.publishers?.[0]?.name && !publisher) setPublisher(data.publishers[0].name);
-        if (data.number_of_pages && !pages) setPages(data.number_of_pages.toString());
+      const result = await lookupISBN(lookupIsbn);
+      const draft = result?.draft;
+      const fetchedTitle = draft?.title || title;
+      const fetchedAuthor = draft?.author || author;
+      if (draft) {
+        if (draft.title && !title) setTitle(draft.title);
+        if (draft.author && !author) setAuthor(draft.author);
+        if (draft.isbn && !isbn) setIsbn(draft.isbn);
+        if (draft.isbn13 && !isbn13) setIsbn13(draft.isbn13);
+        if (draft.publisher && !publisher) setPublisher(draft.publisher);
+        if (draft.pages && !pages) setPages(draft.pages.toString());
+        if (draft.binding && !binding) setBinding(draft.binding);
+        if (draft.yearPublished && !yearPublished) setYearPublished(draft.yearPublished.toString());
+        if (draft.bookType && !book) setBookType(draft.bookType);
+        setLookupExtras({
+          openLibraryKey: draft.openLibraryKey,
+          subjects: draft.subjects,
+          description: draft.description,
+          language: draft.language,
+          series: draft.series,
+        });
       }
       // Resolve cover via fallback chain
       const cover = await lookupCover(isbn || null, isbn13 || null, fetchedTitle, fetchedAuthor);
@@ -65,6 +87,24 @@ export function BookForm({ book, allShelves }: BookFormProps) {
     }
   }
 
+  async function handleFindCovers() {
+    if (!book) return;
+    setLoadingCandidates(true);
+    try {
+      setCandidates(await getCoverCandidates(book.id));
+    } finally {
+      setLoadingCandidates(false);
+    }
+  }
+
+  async function handleUploadCover(e: React.ChangeEvent<HTMLInputElement>) {
+    const file = e.target.files?.[0];
+    if (!book || !file) return;
+    const updated = await setCustomCover(book.id, file);
+    setCoverUrl("");
+    setCoverHash(updated.coverHash);
+  }
+
   function handleSubmit(e: React.FormEvent) {
     e.preventDefault();
     const pageNum = parseInt(pages) || null;
@@ -82,17 +122,22 @@ export function BookForm({ book, allShelves }: BookFormProps) {
       myRating: rating,
       exclusiveShelf,
       dateRead: dateRead || null,
+      dateStarted: dateStarted || null,
       myReview: review || null,
-      coverUrl: coverUrl || getCoverUrl(isbn, isbn13),
+      coverUrl: coverUrl || (coverHash && !book?.coverUrl ? null : getCoverUrl(isbn, isbn13)),
       shelfNames: selectedShelves,
+      ...lookupExtras,
     };
 
     startTransition(async () => {
       if (book) {
         await updateBook(book.id, data);
+        // Download the cover in the background; views fall back to the URL meanwhile
+        cacheBookCover(book.id).catch(() => {});
         router.push(`/book?id=${book.id}`);
       } else {
         const result = await createBook(data);
+        cacheBookCover(result.id).catch(() => {});
         router.push(`/book?id=${result.id}`);
       }
     });
@@ -115,7 +160,7 @@ export function BookForm({ book, allShelves }: BookFormProps) {
             <span className="ml-1">Fetch</span>
           </Button>
         </div>
-        <p className="text-xs text-muted-foreground">Enter an ISBN and click Fetch to auto-fill book details from Open Library</p>
+        <p className="text-xs text-muted-foreground">Enter an ISBN and click Fetch to auto-fill book details from Google Books and Open Library</p>
       </div>
 
       <div className="grid grid-cols-2 gap-4">
@@ -129,12 +174,14 @@ export function BookForm({ book, allShelves }: BookFormProps) {
           <Select value={bookType} onValueChange={setBookType}>
             <SelectTrigger className="w-48"><SelectValue /></SelectTrigger>
             <SelectContent>
-              <SelectItem value="traditional">Traditional</SelectItem>
-              <SelectItem value="graphic_novel">Graphic Novel</SelectItem>
+              {bookTypes.map((t) => (
+                <SelectItem key={t.name} value={t.name}>{t.label}</SelectItem>
+              ))}
             </SelectContent>
           </Select>
         </div>
         <div className="space-y-2"><Label>Year Published</Label><Input type="number" value={yearPublished} onChange={(e) => setYearPublished(e.target.value)} /></div>
+        <div className="space-y-2"><Label>Date Started</Label><Input type="date" value={dateStarted?.replace(/\//g, "-") || ""} onChange={(e) => setDateStarted(e.target.value)} /></div>
         <div className="space-y-2"><Label>Date Read</Label><Input type="date" value={dateRead?.replace(/\//g, "-") || ""} onChange={(e) => setDateRead(e.target.value)} /></div>
       </div>
 
@@ -147,9 +194,42 @@ export function BookForm({ book, allShelves }: BookFormProps) {
             placeholder="https://... (paste a cover image URL)"
             className="flex-1"
           />
-          <CoverImage url={coverUrl || null} title={title || "Preview"} size="md" />
+          <CoverImage url={coverUrl || null} hash={coverUrl === (book?.coverUrl || "") ? coverHash : null} title={title || "Preview"} size="md" />
         </div>
         <p className="text-xs text-muted-foreground">Auto-filled by ISBN Fetch, or paste your own image URL</p>
+        {book && (
+          <div className="flex gap-2">
+            <Button type="button" variant="outline" size="sm" onClick={handleFindCovers} disabled={loadingCandidates}>
+              {loadingCandidates ? <Loader2 className="h-4 w-4 mr-1 animate-spin" /> : <Search className="h-4 w-4 mr-1" />}
+              Find covers
+            </Button>
+            <Button type="button" variant="outline" size="sm" asChild>
+              <label className="cursor-pointer">
+                Upload image
+                <input type="file" accept="image/*" className="hidden" onChange={handleUploadCover} />
+              </label>
+            </Button>
+          </div>
+        )}
+        {candidates && (
+          candidates.length === 0 ? (
+            <p className="text-xs text-muted-foreground">No covers found</p>
+          ) : (
+            <div className="flex flex-wrap gap-3">
+              {candidates.map((c) => (
+                <button
+                  key={c.url}
+                  type="button"
+                  onClick={() => setCoverUrl(c.url)}
+                  className={`text-left space-y-1 rounded p-1 ${coverUrl === c.url ? "ring-2 ring-primary" : ""}`}
+                >
+                  <img src={c.url} alt={`${c.source} cover`} className="h-24 w-16 object-cover rounded" />
+                  <p className="text-[10px] text-muted-foreground leading-tight">{c.width}×{c.height}<br />{c.source} · {c.method}</p>
+                </button>
+              ))}
+            </div>
+          )
+        )}
       </div>
 
       <div className="space-y-2"><Label>My Rating</Label><RatingStars rating={rating} onChange={setRating} readonly={false} /></div>
@@ -15,7 +15,7 @@ import { CoverImage } from "./cover-image";
 import { RatingStars } from "./rating-stars";
 import { ShelfBadge } from "./shelf-badge";
 import { ArrowUpDown, ArrowUp, ArrowDown, Trash2, ChevronLeft, ChevronRight } from "lucide-react";
-import { deleteBook, type Book, type SortField, type SortDir } from "@/lib/tauri";
+import { deleteBook, COVER_COLORS, type Book, type SortField, type SortDir } from "@/lib/tauri";
 import { formatDate } from "@/lib/utils";
 
 function SortIcon({ field, sortBy, sortDir }: { field: SortField; sortBy: SortField; sortDir: SortDir }) {
@@ -49,6 +49,7 @@ interface BookTableProps {
   exclusiveShelf: string;
   shelves: { name: string; count: number }[];
   shelf: string;
+  coverColor: string;
   onRefresh?: () => void;
 }
 
@@ -86,7 +87,7 @@ function ResizeHandle({ onResize }: { onResize: (delta: number) => void }) {
   );
 }
 
-export function BookTable({ books, total, page, limit, search, sortBy, sortDir, exclusiveShelf, shelves, shelf, onRefresh }: BookTableProps) {
+export function BookTable({ books, total, page, limit, search, sortBy, sortDir, exclusiveShelf, shelves, shelf, coverColor, onRefresh }: BookTableProps) {
   const router = useRouter();
   const [isPending, startTransition] = useTransition();
   const [searchInput, setSearchInput] = useState(search);
@@ -103,7 +104,7 @@ export function BookTable({ books, total, page, limit, search, sortBy, sortDir,
 
   function buildUrl(overrides: Record<string, string | number>) {
     const params = new URLSearchParams();
-    const merged = { search, sortBy, sortDir, page, exclusiveShelf, shelf, ...overrides };
+    const merged = { search, sortBy, sortDir, page, exclusiveShelf, shelf, coverColor, ...overrides };
     for (const [k, v] of Object.entries(merged)) {
       if (v && v !== "" && v !== "all") params.set(k, String(v));
     }
@@ -151,6 +152,13 @@ export function BookTable({ books, total, page, limit, search, sortBy, sortDir,
             {shelves.map((s) => (<SelectItem key={s.name} value={s.name}>{s.name} ({s.count})</SelectItem>))}
           </SelectContent>
         </Select>
+        <Select value={coverColor || "all"} onValueChange={(v) => startTransition(() => router.push(buildUrl({ coverColor: v, page: 1 })))}>
+          <SelectTrigger className="w-36"><SelectValue placeholder="Cover color" /></SelectTrigger>
+          <SelectContent>
+            <SelectItem value="all">Any Color</SelectItem>
+            {COVER_COLORS.map((c) => (<SelectItem key={c} value={c}>{c}</SelectItem>))}
+          </SelectContent>
+        </Select>
         <span className="text-sm text-muted-foreground ml-auto">{total} book{total !== 1 ? "s" : ""}</span>
       </div>
 
@@ -169,7 +177,8 @@ export function BookTable({ books, total, page, limit, search, sortBy, sortDir,
           </colgroup>
           <TableHeader>
             <TableRow>
-              <TableHead className="relative">
+              <TableHead className="relative cursor-pointer select-none" onClick={() => handleSort("coverColor")} title="Sort by cover color">
+                <span className="flex items-center"><SortIcon sortBy={sortBy} sortDir={sortDir} field="coverColor" /></span>
                 <ResizeHandle onResize={(d) => resizeColumn("cover", d)} />
               </TableHead>
               <TableHead className="relative cursor-pointer select-none" onClick={() => handleSort("title")}>
@@ -209,7 +218,7 @@ export function BookTable({ books, total, page, limit, search, sortBy, sortDir,
             )}
             {books.map((book) => (
               <TableRow key={book.id} className={isPending ? "opacity-60" : ""}>
-                <TableCell><CoverImage url={book.coverUrl} title={book.title} size="sm" /></TableCell>
+                <TableCell><CoverImage url={book.coverUrl} hash={book.coverHash} title={book.title} size="sm" /></TableCell>
                 <TableCell className="!whitespace-normal break-words"><Link href={`/book?id=${book.id}`} className="font-medium hover:underline">{book.title}</Link></TableCell>
                 <TableCell className="text-muted-foreground !whitespace-normal break-words">{book.author}</TableCell>
                 <TableCell>{book.myRating ? <RatingStars rating={book.myRating} /> : <span className="text-muted-foreground/40 text-xs">—</span>}</TableCell>
@@ -0,0 +1,186 @@
+"use client";
+
+import { useEffect, useState, useTransition } from "react";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/components/ui/card";
+import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "@/components/ui/select";
+import { BookCopy, Check, Pencil, Plus, Trash2, X } from "lucide-react";
+import { deleteBookType, getBookTypes, saveBookType, type BookType } from "@/lib/tauri";
+
+const DEFAULT_TYPE = "traditional";
+const COVER_STYLES = [
+  { value: "classic", label: "Classic placeholder covers" },
+  { value: "bold", label: "Bold placeholder covers" },
+];
+
+interface Draft {
+  name: string;
+  label: string;
+  defaultBinding: string;
+  aliases: string;
+  coverStyle: string;
+}
+
+function toDraft(t: BookType): Draft {
+  return {
+    name: t.name,
+    label: t.label,
+    defaultBinding: t.defaultBinding ?? "",
+    aliases: t.aliases.join(", "),
+    coverStyle: t.coverStyle ?? "classic",
+  };
+}
+
+export function BookTypeSettings() {
+  const [isPending, startTransition] = useTransition();
+  const [types, setTypes] = useState<BookType[]>([]);
+  const [editing, setEditing] = useState<Draft | null>(null);
+  const [newLabel, setNewLabel] = useState("");
+  const [deleting, setDeleting] = useState<string | null>(null);
+  const [reassignTo, setReassignTo] = useState(DEFAULT_TYPE);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => { getBookTypes().then(setTypes); }, []);
+
+  function run(action: () => Promise<BookType[]>) {
+    setError(null);
+    startTransition(async () => {
+      try {
+        setTypes(await action());
+        setEditing(null);
+        setDeleting(null);
+      } catch (e) {
+        setError(String(e));
+      }
+    });
+  }
+
+  function handleCreate(e: React.FormEvent) {
+    e.preventDefault();
+    const label = newLabel.trim();
+    const name = label.toLowerCase().replace(/[^a-z0-9]+/g, "_").replace(/^_|_$/g, "");
+    if (!name) return;
+    run(async () => {
+      const next = await saveBookType({ name, label, beqBaseline: null, defaultBinding: null, aliases: [], coverStyle: null, bookCount: 0 });
+      setNewLabel("");
+      return next;
+    });
+  }
+
+  function handleSave(draft: Draft) {
+    const current = types.find((t) => t.name === draft.name);
+    run(() => saveBookType({
+      name: draft.name,
+      label: draft.label,
+      beqBaseline: current?.beqBaseline ?? null,
+      defaultBinding: draft.defaultBinding.trim() || null,
+      aliases: draft.aliases.split(",").map((a) => a.trim()).filter(Boolean),
+      coverStyle: draft.coverStyle,
+      bookCount: 0,
+    }));
+  }
+
+  function handleDelete(t: BookType) {
+    run(() => deleteBookType(t.name, t.bookCount > 0 ? reassignTo : undefined));
+  }
+
+  return (
+    <Card>
+      <CardHeader>
+        <CardTitle className="flex items-center gap-2">
+          <BookCopy className="h-5 w-5" />
+          Book Types
+        </CardTitle>
+        <CardDescription>
+          Imported &quot;Format&quot; and &quot;Type&quot; values (or the binding) are matched against each type&apos;s name, label and aliases. Unmatched books are {DEFAULT_TYPE}.
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-3 max-w-2xl">
+        <form onSubmit={handleCreate} className="flex gap-2">
+          <Input placeholder="New type label, e.g. Audiobook" value={newLabel} onChange={(e) => setNewLabel(e.target.value)} />
+          <Button type="submit" disabled={isPending || !newLabel.trim()}>
+            <Plus className="h-4 w-4 mr-1" /> Add
+          </Button>
+        </form>
+
+        {types.map((t) => (
+          <div key={t.name} className="rounded-md border px-4 py-3 space-y-2">
+            {editing?.name === t.name ? (
+              <div className="grid grid-cols-2 gap-2">
+                <Input value={editing.label} onChange={(e) => setEditing({ ...editing, label: e.target.value })} placeholder="Label" />
+                <Input value={editing.defaultBinding} onChange={(e) => setEditing({ ...editing, defaultBinding: e.target.value })} placeholder="Default binding" />
+                <Input className="col-span-2" value={editing.aliases} onChange={(e) => setEditing({ ...editing, aliases: e.target.value })} placeholder="Aliases, comma separated" />
+                <Select value={editing.coverStyle} onValueChange={(coverStyle) => setEditing({ ...editing, coverStyle })}>
+                  <SelectTrigger className="col-span-2"><SelectValue /></SelectTrigger>
+                  <SelectContent>
+                    {COVER_STYLES.map((s) => (
+                      <SelectItem key={s.value} value={s.value}>{s.label}</SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+                <div className="col-span-2 flex gap-1">
+                  <Button size="sm" onClick={() => handleSave(editing)} disabled={isPending || !editing.label.trim()}>
+                    <Check className="h-3.5 w-3.5 mr-1" /> Save
+                  </Button>
+                  <Button size="sm" variant="ghost" onClick={() => setEditing(null)}>
+                    <X className="h-3.5 w-3.5 mr-1" /> Cancel
+                  </Button>
+                </div>
+              </div>
+            ) : (
+              <div className="flex items-center justify-between">
+                <div>
+                  <span className="font-medium">{t.label}</span>
+                  <span className="text-sm text-muted-foreground ml-2">({t.bookCount} books)</span>
+                  <p className="text-xs text-muted-foreground">
+                    {t.name}
+                    {t.defaultBinding && ` · binding ${t.defaultBinding}`}
+                    {t.aliases.length > 0 && ` · ${t.aliases.join(", ")}`}
+                    {t.coverStyle && t.coverStyle !== "classic" && ` · ${t.coverStyle} covers`}
+                  </p>
+                </div>
+                <div className="flex gap-1">
+                  <Button size="icon" variant="ghost" className="h-7 w-7" onClick={() => setEditing(toDraft(t))}>
+                    <Pencil className="h-3.5 w-3.5" />
+                  </Button>
+                  {t.name !== DEFAULT_TYPE && (
+                    <Button
+                      size="icon"
+                      variant="ghost"
+                      className="h-7 w-7 text-muted-foreground hover:text-destructive"
+                      onClick={() => { setDeleting(t.name); setReassignTo(DEFAULT_TYPE); }}
+                    >
+                      <Trash2 className="h-3.5 w-3.5" />
+                    </Button>
+                  )}
+                </div>
+              </div>
+            )}
+            {deleting === t.name && (
+              <div className="flex items-center gap-2 text-sm">
+                {t.bookCount > 0 && (
+                  <>
+                    <span>Move {t.bookCount} books to</span>
+                    <Select value={reassignTo} onValueChange={setReassignTo}>
+                      <SelectTrigger className="w-44 h-8"><SelectValue /></SelectTrigger>
+                      <SelectContent>
+                        {types.filter((o) => o.name !== t.name).map((o) => (
+                          <SelectItem key={o.name} value={o.name}>{o.label}</SelectItem>
+                        ))}
+                      </SelectContent>
+                    </Select>
+                  </>
+                )}
+                <Button size="sm" variant="destructive" onClick={() => handleDelete(t)} disabled={isPending}>Delete</Button>
+                <Button size="sm" variant="ghost" onClick={() => setDeleting(null)}>Cancel</Button>
+              </div>
+            )}
+          </div>
+        ))}
+
+        {error && <p className="text-sm text-destructive">{error}</p>}
+      </CardContent>
+    </Card>
+  );
+}
@@ -2,13 +2,18 @@
 
 import { BookOpen } from "lucide-react";
 import { useState } from "react";
+import { localCoverSrc } from "@/lib/tauri";
 
-export function CoverImage({ url, title, size = "sm" }: { url: string | null; title: string; size?: "sm" | "md" | "lg" }) {
+export function CoverImage({ url, hash, title, size = "sm" }: { url: string | null; hash?: string | null; title: string; size?: "sm" | "md" | "lg" }) {
   const [error, setError] = useState(false);
+  const [localFailed, setLocalFailed] = useState(false);
 
   const dims = { sm: "h-12 w-8", md: "h-24 w-16", lg: "h-48 w-32" }[size];
 
-  if (!url || error) {
+  // Prefer the locally cached copy; fall back to the remote URL if it's missing
+  const src = hash && !localFailed ? localCoverSrc(hash, size === "sm" ? "small" : "medium") : url;
+
+  if (!src || error) {
     return (
       <div className={`${dims} bg-muted rounded flex items-center justify-center shrink-0`}>
         <BookOpen className="h-4 w-4 text-muted-foreground" />
@@ -18,10 +23,10 @@ export function CoverImage({ url, title, size = "sm" }: { url: string | null; ti
 
   return (
     <img
-      src={url}
+      src={src}
       alt={title}
       className={`${dims} object-cover rounded shrink-0`}
-      onError={() => setError(true)}
+      onError={() => (src !== url ? setLocalFailed(true) : setError(true))}
       onLoad={(e) => {
         // Open Library returns a 1x1 transparent pixel when no cover exists
         const img = e.currentTarget;
@@ -24,6 +24,7 @@ const BOOK_FIELDS = [
   { key: "year_published", label: "Year Published" },
   { key: "edition_published", label: "Edition Published" },
   { key: "date_read", label: "Date Read" },
+  { key: "date_started", label: "Date Started" },
   { key: "year_read", label: "Year Read" },
   { key: "date_added", label: "Date Added" },
   { key: "exclusive_shelf", label: "Status / Shelf" },
@@ -41,7 +42,7 @@ export function CsvImporter() {
   const router = useRouter();
   const [isPending, startTransition] = useTransition();
   const [step, setStep] = useState<Step>("upload");
-  const [result, setResult] = useState<{ imported: number; total: number; skipped: string[] } | null>(null);
+  const [result, setResult] = useState<{ imported: number; total: number; skipped: string[]; alsoInTrash: string[] } | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [fileName, setFileName] = useState<string | null>(null);
   const [csvText, setCsvText] = useState<string | null>(null);
@@ -208,6 +209,18 @@ export function CsvImporter() {
                 </ul>
               </details>
             )}
+            {result.alsoInTrash.length > 0 && (
+              <details className="text-sm">
+                <summary className="cursor-pointer text-muted-foreground hover:text-foreground">
+                  Imported {result.alsoInTrash.length} book{result.alsoInTrash.length === 1 ? "" : "s"} that also {result.alsoInTrash.length === 1 ? "has a copy" : "have copies"} in the trash
+                </summary>
+                <ul className="mt-1 max-h-48 overflow-y-auto space-y-0.5 pl-4 text-xs text-muted-foreground">
+                  {result.alsoInTrash.map((title, i) => (
+                    <li key={i}>• {title}</li>
+                  ))}
+                </ul>
+              </details>
+            )}
           </div>
         )}
 
@@ -1,4 +1,5 @@
-import { invoke } from "@tauri-apps/api/core";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 
 // ─── Types ───────────────────────────────────────────────────────────
 
@@ -31,6 +32,19 @@ export interface Book {
   createdAt: string | null;
   updatedAt: string | null;
   bookType: string;
+  deletedAt: string | null;
+  subjects: string[];
+  description: string | null;
+  language: string | null;
+  series: string | null;
+  coverPath: string | null;
+  coverHash: string | null;
+  coverColor: string | null;
+  coverAccent: string | null;
+  /** Stamped when the book moves to currently-reading, unless already set */
+  dateStarted: string | null;
+  /** Bumped on every write; pass back as `expectedRevision` when patching */
+  revision: number;
 }
 
 export interface BookWithShelves extends Book {
@@ -57,21 +71,257 @@ export interface ImportResult {
   imported: number;
   total: number;
   skipped: string[];
+  /** Imported rows matching a book in the trash (left in the trash) */
+  alsoInTrash: string[];
+  invalidIsbns: string[];
 }
 
 export interface Stats {
   totalBooks: number;
   totalRead: number;
   totalBeq: number;
-  totalBeqTraditional: number;
-  totalBeqGraphicNovel: number;
-  avgPagesTraditional: number;
-  avgPagesGraphicNovel: number;
   avgRating: number;
   booksThisYear: number;
   beqThisYear: number;
   byYear: { year: number | null; count: number; beq: number }[];
   ratingDist: { rating: number; count: number }[];
+  beqMode: BeqMode;
+  byType: TypeStat[];
+}
+
+export type SeriesBucket = "month" | "week" | "weekday";
+
+export interface SeriesPoint {
+  /** `2024-03`, `2024-W09` or `1` (Monday) … `7` */
+  key: string;
+  label: string;
+  books: number;
+  pages: number;
+  beq: number;
+  rollingBooks: number | null;
+  rollingPages: number | null;
+  rollingBeq: number | null;
+}
+
+export interface ReadingSeries {
+  bucket: SeriesBucket;
+  year: number;
+  /** Buckets in each trailing average; null for weekdays */
+  window: number | null;
+  points: SeriesPoint[];
+  /** Books read that year without a full read date */
+  undated: number;
+}
+
+export type BreakdownSort = "books" | "pages" | "beq";
+
+export interface BreakdownRow {
+  /** Name, decade start year, or lower page bound */
+  key: string;
+  label: string;
+  books: number;
+  pages: number;
+  beq: number;
+  /** Over rated books only */
+  avgRating: number | null;
+}
+
+export interface Breakdowns {
+  year: number | null;
+  sortBy: BreakdownSort;
+  authors: BreakdownRow[];
+  publishers: BreakdownRow[];
+  shelves: BreakdownRow[];
+  bookTypes: BreakdownRow[];
+  decades: BreakdownRow[];
+  pageLengths: BreakdownRow[];
+}
+
+export interface TimedRead {
+  id: number;
+  title: string;
+  author: string;
+  bookType: string;
+  pages: number;
+  dateStarted: string;
+  dateRead: string;
+  /** Calendar days, counting both the start and finish day */
+  days: number;
+  pagesPerDay: number;
+}
+
+export interface ReadingPace {
+  /** Consecutive reading days up to today (or yesterday) */
+  currentStreak: number;
+  longestStreak: number;
+  longestStreakStart: string | null;
+  longestStreakEnd: string | null;
+  avgDaysToFinish: number | null;
+  /** Finished books with a start date */
+  timedBooks: number;
+  byType: { bookType: string; label: string; books: number; avgDays: number; pagesPerDay: number }[];
+  fastest: TimedRead[];
+  slowest: TimedRead[];
+}
+
+export interface TypeStat {
+  bookType: string;
+  label: string;
+  booksRead: number;
+  beq: number;
+  avgPages: number;
+  /** Pages per BEq under `beqMode`; null in live mode before any book of this type is read */
+  beqDivisor: number | null;
+}
+
+export type GoalPeriod = "year" | "month";
+export type GoalMetric = "books" | "pages" | "beq";
+export type GoalPace = "complete" | "ahead" | "behind" | "upcoming" | "missed";
+
+export interface Goal {
+  /** null when creating */
+  id: number | null;
+  name: string;
+  period: GoalPeriod;
+  year: number;
+  /** 1–12, for monthly goals */
+  month: number | null;
+  metric: GoalMetric;
+  target: number;
+  /** Comma-separated `key:value` terms (shelf, type, author, publisher, language) */
+  filter: string | null;
+  /** Count distinct `author`, `publisher`, `language`, `shelf` or `shelf:<prefix>` values instead of books. Books have no country field. */
+  distinctBy: string | null;
+}
+
+export interface GoalBook {
+  id: number;
+  title: string;
+  author: string;
+  dateRead: string | null;
+  pages: number | null;
+  beq: number | null;
+  countedAs: string | null;
+}
+
+export interface GoalProgress extends Goal {
+  current: number;
+  percent: number;
+  /** Where progress should be today for an even pace */
+  expected: number;
+  projected: number;
+  pace: GoalPace;
+  books: GoalBook[];
+}
+
+export interface RatingGap {
+  id: number;
+  title: string;
+  author: string;
+  myRating: number;
+  averageRating: number;
+  /** My rating minus the community average */
+  difference: number;
+}
+
+export interface RatingGroup {
+  /** Book type name, or the range's lower bound */
+  key: string;
+  label: string;
+  books: number;
+  avgMyRating: number;
+  avgCommunity: number | null;
+  meanDeviation: number | null;
+}
+
+export interface RatingCorrelation {
+  books: number;
+  /** Pearson r; null with fewer than 3 books or no variation */
+  myCorrelation: number | null;
+  communityCorrelation: number | null;
+  buckets: RatingGroup[];
+}
+
+export interface RatingAnalysis {
+  ratedBooks: number;
+  /** Rated books that also have a community average */
+  comparedBooks: number;
+  threshold: number;
+  /** Positive when you rate above the community */
+  meanDeviation: number | null;
+  meanAbsDeviation: number | null;
+  communityCorrelation: number | null;
+  likedMore: RatingGap[];
+  likedLess: RatingGap[];
+  pages: RatingCorrelation;
+  yearPublished: RatingCorrelation;
+  byType: RatingGroup[];
+}
+
+export interface YearTotals {
+  year: number;
+  books: number;
+  pages: number;
+  beq: number;
+  avgPages: number | null;
+  avgRating: number | null;
+}
+
+export interface ReviewBook {
+  id: number;
+  title: string;
+  author: string;
+  pages: number | null;
+  /** 0 when unrated */
+  myRating: number;
+  dateRead: string | null;
+}
+
+export interface YearReview {
+  year: number;
+  generatedAt: string;
+  totals: YearTotals;
+  previous: YearTotals;
+  firstBook: ReviewBook | null;
+  lastBook: ReviewBook | null;
+  longestBook: ReviewBook | null;
+  shortestBook: ReviewBook | null;
+  highestRated: ReviewBook | null;
+  lowestRated: ReviewBook | null;
+  mostReadAuthor: BreakdownRow | null;
+  months: ReadingSeries;
+  /** Yearly goals for `year` */
+  goals: GoalProgress[];
+}
+
+export interface YearReviewExport {
+  jsonPath: string;
+  htmlPath: string;
+  review: YearReview;
+}
+
+export interface BookType {
+  name: string;
+  label: string;
+  /** Pages per BEq in fixed mode; the default baseline when null */
+  beqBaseline: number | null;
+  defaultBinding: string | null;
+  /** Imported "Format"/"Type" values that map to this type */
+  aliases: string[];
+  /** Look of generated placeholder covers ("classic" or "bold"); classic when null */
+  coverStyle: string | null;
+  bookCount: number;
+}
+
+export type BeqMode = "live" | "snapshot" | "fixed";
+
+export interface BeqConfig {
+  mode: BeqMode;
+  baselines: Record<string, number>;
+  snapshot: Record<string, number>;
+  snapshotAt: string | null;
+  liveAverages: Record<string, number>;
+  defaultBaseline: number;
 }
 
 export interface ShelfCount {
@@ -79,13 +329,10 @@ export interface ShelfCount {
   count: number;
 }
 
-export interface OpenLibraryBookData {
-  title?: string;
-  authors?: { name: string }[];
-  publishers?: { name: string }[];
-  number_of_pages?: number;
-  publish_date?: string;
-  cover?: { small?: string; medium?: string; large?: string };
+export interface IsbnLookup {
+  draft: NewBook;
+  /** camelCase field name → provider id ("input" / "suggested" for derived values) */
+  sources: Record<string, string>;
 }
 
 export interface NewBook {
@@ -113,9 +360,25 @@ export interface NewBook {
   coverUrl?: string | null;
   shelfNames?: string[];
   bookType?: string;
+  openLibraryKey?: string | null;
+  subjects?: string[] | null;
+  description?: string | null;
+  language?: string | null;
+  series?: string | null;
+  dateStarted?: string | null;
 }
 
-export type SortField = "title" | "author" | "myRating" | "pages" | "beq" | "dateRead" | "dateAdded" | "yearPublished" | "averageRating";
+/** Partial update: omit a key to leave it unchanged, pass null to clear it. */
+export type BookPatch = Partial<Omit<NewBook, "shelfNames">> & {
+  addShelves?: string[];
+  removeShelves?: string[];
+  /** Reject the patch if the book's `revision` has moved on since it was read */
+  expectedRevision?: number | null;
+};
+
+export type SortField = "title" | "author" | "myRating" | "pages" | "beq" | "dateRead" | "dateAdded" | "yearPublished" | "averageRating" | "coverColor";
+export const COVER_COLORS = ["red", "orange", "yellow", "green", "teal", "blue", "purple", "pink", "neutral"] as const;
+export type CoverColor = (typeof COVER_COLORS)[number];
 export type SortDir = "asc" | "desc";
 
 // ─── Commands ────────────────────────────────────────────────────────
@@ -124,6 +387,7 @@ export async function getBooks(opts?: {
   search?: string;
   shelf?: string;
   exclusiveShelf?: string;
+  coverColor?: CoverColor;
   sortBy?: SortField;
   sortDir?: SortDir;
   page?: number;
@@ -133,6 +397,7 @@ export async function getBooks(opts?: {
     search: opts?.search || null,
     shelf: opts?.shelf || null,
     exclusiveShelf: opts?.exclusiveShelf || null,
+    coverColor: opts?.coverColor || null,
     sortBy: opts?.sortBy || null,
     sortDir: opts?.sortDir || null,
     page: opts?.page || null,
@@ -152,6 +417,25 @@ export async function updateBook(id: number, data: NewBook): Promise<void> {
   return invoke("update_book", { id, data });
 }
 
+export async function patchBook(id: number, patch: BookPatch): Promise<Book> {
+  return invoke("patch_book", { id, patch });
+}
+
+export interface IsbnFix {
+  bookId: number;
+  title: string;
+  oldIsbn: string | null;
+  oldIsbn13: string | null;
+  isbn: string | null;
+  isbn13: string | null;
+  invalid: string[];
+}
+
+/** Lists books with malformed or invalid ISBNs; pass `apply` to rewrite them. */
+export async function fixInvalidIsbns(apply = false): Promise<IsbnFix[]> {
+  return invoke("fix_invalid_isbns", { apply });
+}
+
 export async function deleteBook(id: number): Promise<void> {
   return invoke("delete_book", { id });
 }
@@ -184,23 +468,111 @@ export async function importCsv(csvText: string, columnMap?: Record<string, stri
   return invoke("import_csv", { csvText, columnMap: columnMap || null });
 }
 
-export async function lookupISBN(isbn: string): Promise<OpenLibraryBookData | null> {
+export async function lookupISBN(isbn: string): Promise<IsbnLookup | null> {
   return invoke("lookup_isbn", { isbn });
 }
 
-export interface FixCoversResult {
-  fixed: number;
-  checked: number;
-}
-
-export async function fixMissingCovers(): Promise<FixCoversResult> {
-  return invoke("fix_missing_covers");
+/**
+ * Starts the cover re-fetch job and returns how many books it queued.
+ * Progress arrives as `fix-covers-progress` events; an interrupted run
+ * resumes where it stopped unless `restart` is set.
+ */
+export async function fixMissingCovers(restart = false): Promise<number> {
+  return invoke("fix_missing_covers", { restart });
 }
 
 export async function lookupCover(isbn: string | null, isbn13: string | null, title: string, author: string): Promise<string | null> {
   return invoke("lookup_cover", { isbn, isbn13, title, author });
 }
 
+// ─── Cover cache ─────────────────────────────────────────────────────
+
+export type CoverSize = "small" | "medium" | "original";
+
+/** URL of a locally cached cover, served over the `bookcover` protocol. */
+export function localCoverSrc(hash: string, size: CoverSize = "medium"): string {
+  return convertFileSrc(`${hash}/${size}`, "bookcover");
+}
+
+export interface CoverPruneResult {
+  filesRemoved: number;
+  bytesFreed: number;
+}
+
+export async function cacheBookCover(id: number): Promise<string | null> {
+  return invoke("cache_book_cover", { id });
+}
+
+/** Starts the cover download job; progress arrives as `cache-covers-progress` events. */
+export async function cacheCovers(): Promise<number> {
+  return invoke("cache_covers");
+}
+
+export async function pruneCoverCache(): Promise<CoverPruneResult> {
+  return invoke("prune_cover_cache");
+}
+
+export interface CoverColorResult {
+  booksUpdated: number;
+  /** `<hash>: <reason>` for cached covers whose colors couldn't be read */
+  failures: string[];
+}
+
+/** Fills in colors for covers cached before colors were extracted. */
+export async function extractCoverColors(): Promise<CoverColorResult> {
+  return invoke("extract_cover_colors");
+}
+
+/** Draws title/author placeholder covers for books without artwork; returns books updated. */
+export async function generatePlaceholderCovers(): Promise<number> {
+  return invoke("generate_placeholder_covers");
+}
+
+/** Stores a user-picked image (validated and resized) as the book's cover. */
+export async function setCustomCover(id: number, file: File): Promise<Book> {
+  const bytes = Array.from(new Uint8Array(await file.arrayBuffer()));
+  return invoke("set_custom_cover", { id, bytes });
+}
+
+export interface CoverCandidate {
+  url: string;
+  source: string;
+  method: "isbn" | "search";
+  width: number;
+  height: number;
+}
+
+export async function getCoverCandidates(id: number): Promise<CoverCandidate[]> {
+  return invoke("get_cover_candidates", { id });
+}
+
+export interface CoverValidation {
+  url: string;
+  valid: boolean;
+  width: number | null;
+  height: number | null;
+  hash: string | null;
+  reason: string | null;
+  checkedAt: string;
+}
+
+export async function getCoverValidation(url: string): Promise<CoverValidation | null> {
+  return invoke("get_cover_validation", { url });
+}
+
+/** Blocklists the book's cover image as a placeholder; returns how many books lost that cover. */
+export async function blockCover(id: number, note?: string): Promise<number> {
+  return invoke("block_cover", { id, note: note ?? null });
+}
+
+export async function getCoverBlocklist(): Promise<string[]> {
+  return invoke("get_cover_blocklist");
+}
+
+export async function unblockCover(hash: string): Promise<void> {
+  return invoke("unblock_cover", { hash });
+}
+
 export async function getStats(): Promise<Stats> {
   return invoke("get_stats");
 }
@@ -209,6 +581,274 @@ export async function getShelfCounts(): Promise<ShelfCount[]> {
   return invoke("get_shelf_counts");
 }
 
+/** Defaults to the current year and a 3-month / 4-week rolling window. */
+export async function getReadingSeries(bucket: SeriesBucket, year?: number, window?: number): Promise<ReadingSeries> {
+  return invoke("get_reading_series", { bucket, year: year ?? null, window: window ?? null });
+}
+
+/** Read books grouped several ways; all time unless `year` is given. */
+export async function getBreakdowns(opts?: { year?: number; sortBy?: BreakdownSort; limit?: number }): Promise<Breakdowns> {
+  return invoke("get_breakdowns", { year: opts?.year ?? null, sortBy: opts?.sortBy ?? null, limit: opts?.limit ?? null });
+}
+
+export async function getReadingPace(): Promise<ReadingPace> {
+  return invoke("get_reading_pace");
+}
+
+/** Defaults to a 1-star threshold and 10 books per list. */
+export async function getRatingAnalysis(threshold?: number, limit?: number): Promise<RatingAnalysis> {
+  return invoke("get_rating_analysis", { threshold: threshold ?? null, limit: limit ?? null });
+}
+
+export async function getYearReview(year?: number): Promise<YearReview> {
+  return invoke("get_year_review", { year: year ?? null });
+}
+
+/** Writes `reports/year-in-books-<year>.json` and `.html` to the app data dir. */
+export async function exportYearReview(year?: number): Promise<YearReviewExport> {
+  return invoke("export_year_review", { year: year ?? null });
+}
+
+export async function getGoals(year?: number): Promise<GoalProgress[]> {
+  return invoke("get_goals", { year: year ?? null });
+}
+
+/** Creates the goal, or updates it when `id` is set. */
+export async function saveGoal(goal: Goal): Promise<GoalProgress> {
+  return invoke("save_goal", { goal });
+}
+
+export async function deleteGoal(id: number): Promise<void> {
+  return invoke("delete_goal", { id });
+}
+
 export async function clearDatabase(): Promise<void> {
   return invoke("clear_database");
 }
+
+// ─── Backups ─────────────────────────────────────────────────────────
+
+export interface BackupInfo {
+  fileName: string;
+  reason: string;
+  createdAt: string;
+  sizeBytes: number;
+}
+
+export interface BackupVerification {
+  fileName: string;
+  ok: boolean;
+  messages: string[];
+  bookCount: number | null;
+}
+
+export async function listBackups(): Promise<BackupInfo[]> {
+  return invoke("list_backups");
+}
+
+export async function createBackup(): Promise<BackupInfo> {
+  return invoke("create_backup");
+}
+
+export async function verifyBackup(fileName: string): Promise<BackupVerification> {
+  return invoke("verify_backup", { fileName });
+}
+
+export async function restoreBackup(fileName: string): Promise<BackupInfo> {
+  return invoke("restore_backup", { fileName });
+}
+
+// ─── Settings ────────────────────────────────────────────────────────
+
+export async function getSettings(): Promise<Record<string, string>> {
+  return invoke("get_settings");
+}
+
+export async function updateSetting(key: string, value: string): Promise<void> {
+  return invoke("update_setting", { key, value });
+}
+
+export async function getBeqConfig(): Promise<BeqConfig> {
+  return invoke("get_beq_config");
+}
+
+/** Switching to "snapshot" freezes the current averages if none were saved yet. */
+export async function setBeqConfig(mode: BeqMode, baselines?: Record<string, number>): Promise<BeqConfig> {
+  return invoke("set_beq_config", { mode, baselines: baselines ?? null });
+}
+
+export async function snapshotBeqAverages(): Promise<BeqConfig> {
+  return invoke("snapshot_beq_averages");
+}
+
+// ─── Book types ──────────────────────────────────────────────────────
+
+export async function getBookTypes(): Promise<BookType[]> {
+  return invoke("get_book_types");
+}
+
+/** Adds the type, or updates it when the name already exists. */
+export async function saveBookType(bookType: BookType): Promise<BookType[]> {
+  return invoke("save_book_type", { bookType });
+}
+
+/** Books still using the type must be moved with `reassignTo`. */
+export async function deleteBookType(name: string, reassignTo?: string): Promise<BookType[]> {
+  return invoke("delete_book_type", { name, reassignTo: reassignTo ?? null });
+}
+
+// ─── Trash ───────────────────────────────────────────────────────────
+
+export async function listTrash(): Promise<Book[]> {
+  return invoke("list_trash");
+}
+
+export async function restoreBook(id: number): Promise<void> {
+  return invoke("restore_book", { id });
+}
+
+export async function purgeTrash(olderThanDays?: number): Promise<number> {
+  return invoke("purge_trash", { olderThanDays: olderThanDays ?? null });
+}
+
+// ─── History ─────────────────────────────────────────────────────────
+
+export interface FieldChange {
+  field: string;
+  old: unknown;
+  new: unknown;
+}
+
+export interface HistoryEntry {
+  id: number;
+  bookId: number;
+  revision: number;
+  action: string;
+  changes: FieldChange[];
+  note: string | null;
+  undone: boolean;
+  createdAt: string | null;
+}
+
+export async function getBookHistory(bookId: number): Promise<HistoryEntry[]> {
+  return invoke("get_book_history", { bookId });
+}
+
+export async function revertBook(bookId: number, revision: number): Promise<void> {
+  return invoke("revert_book", { bookId, revision });
+}
+
+export async function undoLastChange(): Promise<HistoryEntry | null> {
+  return invoke("undo_last_change");
+}
+
+// ─── Bulk edit ───────────────────────────────────────────────────────
+
+export type BulkOperation =
+  | { type: "setExclusiveShelf"; shelf: string }
+  | { type: "addShelves"; shelves: string[] }
+  | { type: "removeShelves"; shelves: string[] }
+  | { type: "setBookType"; bookType: string }
+  | { type: "setRating"; rating: number }
+  | { type: "setOwnedCopies"; copies: number }
+  | { type: "delete" };
+
+export interface BulkItemResult {
+  id: number;
+  ok: boolean;
+  error: string | null;
+}
+
+export async function bulkEdit(ids: number[], operation: BulkOperation): Promise<BulkItemResult[]> {
+  return invoke("bulk_edit", { ids, operation });
+}
+
+export async function bulkRefetchMetadata(ids: number[]): Promise<BulkItemResult[]> {
+  return invoke("bulk_refetch_metadata", { ids });
+}
+
+// ─── Metadata providers ──────────────────────────────────────────────
+
+export type ProviderKind = "openLibrary" | "googleBooks";
+
+export interface ProviderConfig {
+  id: string;
+  kind: ProviderKind;
+  baseUrl: string | null;
+  coversBaseUrl: string | null;
+  apiKey: string | null;
+  enabled: boolean;
+  requestsPerMinute: number | null;
+}
+
+export async function getMetadataProviders(): Promise<ProviderConfig[]> {
+  return invoke("get_metadata_providers");
+}
+
+export async function setMetadataProviders(configs: ProviderConfig[]): Promise<void> {
+  return invoke("set_metadata_providers", { configs });
+}
+
+/** Drops cached provider responses; returns how many were removed. */
+export async function clearLookupCache(): Promise<number> {
+  return invoke("clear_lookup_cache");
+}
+
+// ─── Background jobs ─────────────────────────────────────────────────
+
+export interface JobProgress {
+  job: string;
+  processed: number;
+  total: number;
+  bookId: number | null;
+  title: string | null;
+  succeeded: number;
+  /** Items that failed so far */
+  failed: number;
+  /** Why the item in this event failed */
+  error: string | null;
+  done: boolean;
+  cancelled: boolean;
+}
+
+export function onJobProgress(event: string, handler: (progress: JobProgress) => void): Promise<UnlistenFn> {
+  return listen<JobProgress>(event, (e) => handler(e.payload));
+}
+
+export async function cancelJob(job: string): Promise<boolean> {
+  return invoke("cancel_job", { job });
+}
+
+export async function getRunningJobs(): Promise<string[]> {
+  return invoke("get_running_jobs");
+}
+
+// ─── Metadata refresh ────────────────────────────────────────────────
+
+export interface MetadataProposal {
+  id: number;
+  bookId: number;
+  title: string;
+  author: string;
+  field: string;
+  proposed: unknown;
+  source: string;
+  createdAt: string;
+}
+
+/** Starts the refresh job; progress arrives as `metadata-refresh-progress` events. */
+export async function startMetadataRefresh(fields: string[] = []): Promise<number> {
+  return invoke("start_metadata_refresh", { fields });
+}
+
+export async function listMetadataProposals(): Promise<MetadataProposal[]> {
+  return invoke("list_metadata_proposals");
+}
+
+export async function acceptMetadataProposals(bookId: number, fields?: string[]): Promise<string[]> {
+  return invoke("accept_metadata_proposals", { bookId, fields: fields ?? null });
+}
+
+export async function rejectMetadataProposals(bookId: number, fields?: string[]): Promise<void> {
+  return invoke("reject_metadata_proposals", { bookId, fields: fields ?? null });
+}